@@ -0,0 +1,336 @@
+//! Derive macros for structured key-values in the [`log`] crate.
+//!
+//! This crate provides two derives for structs with named fields:
+//!
+//! - `#[derive(ToValue)]` captures a struct as a single [`Value`] that's
+//!   a map of its fields.
+//! - `#[derive(Source)]` makes each field of a struct its own key-value pair.
+//!
+//! Fields are captured using their [`ToValue`] implementations, so they keep
+//! their types when they're visited.
+//!
+//! # Attributes
+//!
+//! Fields can be customized with a `#[log(...)]` attribute:
+//!
+//! - `#[log(rename = "name")]` uses `name` as the key instead of the field name.
+//! - `#[log(skip)]` leaves the field out.
+//! - `#[log(redact)]` captures the field as a [sensitive value], so it's rendered
+//!   as `[REDACTED]`.
+//! - `#[log(flatten)]` inlines the entries of the field into the parent. The
+//!   field must implement [`Map`] for `ToValue`, or [`Source`] for `Source`.
+//!
+//! # Examples
+//!
+//! ```
+//! use log::info;
+//! use log_derive::{Source, ToValue};
+//!
+//! #[derive(ToValue, Source)]
+//! struct Order {
+//!     #[log(rename = "order_id")]
+//!     id: u64,
+//!     quantity: u32,
+//!     #[log(redact)]
+//!     card: String,
+//!     #[log(skip)]
+//!     notes: String,
+//! }
+//!
+//! let order = Order {
+//!     id: 42,
+//!     quantity: 3,
+//!     card: "4111 1111 1111 1111".into(),
+//!     notes: String::new(),
+//! };
+//!
+//! info!(order = order; "placed");
+//! ```
+//!
+//! [`log`]: https://docs.rs/log
+//! [`Value`]: https://docs.rs/log/*/log/kv/struct.Value.html
+//! [`ToValue`]: https://docs.rs/log/*/log/kv/trait.ToValue.html
+//! [`Map`]: https://docs.rs/log/*/log/kv/value/trait.Map.html
+//! [`Source`]: https://docs.rs/log/*/log/kv/trait.Source.html
+//! [sensitive value]: https://docs.rs/log/*/log/kv/struct.Sensitive.html
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Fields, Ident, LitStr, Type};
+
+/// Derive `log::kv::ToValue` for a struct, capturing it as a map of its fields.
+///
+/// This also derives `log::kv::value::Map`.
+#[proc_macro_derive(ToValue, attributes(log))]
+pub fn derive_to_value(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand_to_value(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `log::kv::Source` for a struct, visiting each of its fields as a key-value pair.
+#[proc_macro_derive(Source, attributes(log))]
+pub fn derive_source(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand_source(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+struct Field<'a> {
+    ident: &'a Ident,
+    ty: &'a Type,
+    key: String,
+    kind: Kind,
+}
+
+enum Kind {
+    Value,
+    Redact,
+    Flatten,
+}
+
+fn expand_to_value(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = fields(input)?;
+
+    let entries = fields.iter().map(|field| {
+        let Field {
+            ident, key, kind, ..
+        } = field;
+
+        match kind {
+            Kind::Value => quote! {
+                visitor.visit_entry(
+                    ::log::kv::Value::from(#key),
+                    ::log::kv::ToValue::to_value(&self.#ident),
+                )?;
+            },
+            Kind::Redact => quote! {
+                visitor.visit_entry(
+                    ::log::kv::Value::from(#key),
+                    ::log::kv::Value::from_sensitive(&self.#ident),
+                )?;
+            },
+            Kind::Flatten => quote! {
+                ::log::kv::value::Map::visit_map(&self.#ident, visitor)?;
+            },
+        }
+    });
+
+    let mut generics = input.generics.clone();
+    if generics.type_params().next().is_some() {
+        let where_clause = generics.make_where_clause();
+        for Field { ty, kind, .. } in &fields {
+            where_clause.predicates.push(match kind {
+                Kind::Value | Kind::Redact => parse_quote!(#ty: ::log::kv::ToValue),
+                Kind::Flatten => parse_quote!(#ty: ::log::kv::value::Map),
+            });
+        }
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::log::kv::value::Map for #name #ty_generics #where_clause {
+            fn visit_map<'__v>(
+                &'__v self,
+                visitor: &mut dyn ::log::kv::value::VisitMap<'__v>,
+            ) -> ::core::result::Result<(), ::log::kv::Error> {
+                #(#entries)*
+                ::core::result::Result::Ok(())
+            }
+        }
+
+        impl #impl_generics ::log::kv::ToValue for #name #ty_generics #where_clause {
+            fn to_value(&self) -> ::log::kv::Value<'_> {
+                ::log::kv::Value::from_map(self)
+            }
+        }
+    })
+}
+
+fn expand_source(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = fields(input)?;
+
+    let pairs = fields.iter().map(|field| {
+        let Field {
+            ident, key, kind, ..
+        } = field;
+
+        match kind {
+            Kind::Value => quote! {
+                visitor.visit_pair(
+                    ::log::kv::Key::from_str(#key),
+                    ::log::kv::ToValue::to_value(&self.#ident),
+                )?;
+            },
+            Kind::Redact => quote! {
+                visitor.visit_pair(
+                    ::log::kv::Key::from_str(#key),
+                    ::log::kv::Value::from_sensitive(&self.#ident),
+                )?;
+            },
+            Kind::Flatten => quote! {
+                ::log::kv::Source::visit(&self.#ident, visitor)?;
+            },
+        }
+    });
+
+    let gets = fields.iter().map(|field| {
+        let Field {
+            ident, key, kind, ..
+        } = field;
+
+        match kind {
+            Kind::Value => quote! {
+                if key.as_str() == #key {
+                    return ::core::option::Option::Some(::log::kv::ToValue::to_value(&self.#ident));
+                }
+            },
+            Kind::Redact => quote! {
+                if key.as_str() == #key {
+                    return ::core::option::Option::Some(::log::kv::Value::from_sensitive(&self.#ident));
+                }
+            },
+            Kind::Flatten => quote! {
+                if let ::core::option::Option::Some(value) = ::log::kv::Source::get(&self.#ident, key.clone()) {
+                    return ::core::option::Option::Some(value);
+                }
+            },
+        }
+    });
+
+    let counts = fields.iter().map(|field| match field.kind {
+        Kind::Value | Kind::Redact => quote!(1),
+        Kind::Flatten => {
+            let ident = field.ident;
+            quote!(::log::kv::Source::count(&self.#ident))
+        }
+    });
+
+    let mut generics = input.generics.clone();
+    if generics.type_params().next().is_some() {
+        let where_clause = generics.make_where_clause();
+        for Field { ty, kind, .. } in &fields {
+            where_clause.predicates.push(match kind {
+                Kind::Value | Kind::Redact => parse_quote!(#ty: ::log::kv::ToValue),
+                Kind::Flatten => parse_quote!(#ty: ::log::kv::Source),
+            });
+        }
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::log::kv::Source for #name #ty_generics #where_clause {
+            fn visit<'__kvs>(
+                &'__kvs self,
+                visitor: &mut dyn ::log::kv::Visitor<'__kvs>,
+            ) -> ::core::result::Result<(), ::log::kv::Error> {
+                #(#pairs)*
+                ::core::result::Result::Ok(())
+            }
+
+            #[allow(unused_variables)]
+            fn get(&self, key: ::log::kv::Key<'_>) -> ::core::option::Option<::log::kv::Value<'_>> {
+                #(#gets)*
+                ::core::option::Option::None
+            }
+
+            fn count(&self) -> usize {
+                0 #(+ #counts)*
+            }
+        }
+    })
+}
+
+// Get the fields of a struct that aren't skipped, along with their attributes
+fn fields(input: &DeriveInput) -> syn::Result<Vec<Field<'_>>> {
+    let fields = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(ref fields) => &fields.named,
+            Fields::Unit => return Ok(Vec::new()),
+            Fields::Unnamed(_) => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "only structs with named fields are supported",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "only structs with named fields are supported",
+            ))
+        }
+    };
+
+    let mut parsed = Vec::new();
+
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named fields have idents");
+
+        let mut rename = None;
+        let mut skip = false;
+        let mut redact = false;
+        let mut flatten = false;
+
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("log"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    let key: LitStr = meta.value()?.parse()?;
+                    rename = Some(key.value());
+                } else if meta.path.is_ident("skip") {
+                    skip = true;
+                } else if meta.path.is_ident("redact") {
+                    redact = true;
+                } else if meta.path.is_ident("flatten") {
+                    flatten = true;
+                } else {
+                    return Err(meta.error("unsupported `log` attribute"));
+                }
+
+                Ok(())
+            })?;
+        }
+
+        if flatten && (rename.is_some() || redact) {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "`flatten` can't be combined with `rename` or `redact`",
+            ));
+        }
+
+        if skip {
+            continue;
+        }
+
+        let kind = if flatten {
+            Kind::Flatten
+        } else if redact {
+            Kind::Redact
+        } else {
+            Kind::Value
+        };
+
+        parsed.push(Field {
+            ident,
+            ty: &field.ty,
+            key: rename.unwrap_or_else(|| ident.unraw().to_string()),
+            kind,
+        });
+    }
+
+    Ok(parsed)
+}
@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+
+use log::kv::value::{Map, VisitMap};
+use log::kv::{Error, Key, Source, ToValue, Value, Visitor};
+use log_derive::{Source, ToValue};
+
+#[derive(ToValue, Source)]
+struct Order {
+    #[log(rename = "order_id")]
+    id: u64,
+    quantity: u32,
+    #[log(redact)]
+    card: &'static str,
+    #[log(skip)]
+    #[allow(dead_code)]
+    notes: &'static str,
+    #[log(flatten)]
+    customer: Customer,
+}
+
+#[derive(ToValue, Source)]
+struct Customer {
+    name: &'static str,
+    r#type: &'static str,
+}
+
+#[derive(ToValue, Source)]
+struct Wrapper<T> {
+    inner: T,
+}
+
+#[derive(ToValue, Source)]
+struct Empty {}
+
+fn order() -> Order {
+    Order {
+        id: 42,
+        quantity: 3,
+        card: "4111 1111 1111 1111",
+        notes: "leave at the door",
+        customer: Customer {
+            name: "Ferris",
+            r#type: "crab",
+        },
+    }
+}
+
+fn pairs(source: &dyn Source) -> Vec<(String, String)> {
+    struct Collect(Vec<(String, String)>);
+
+    impl<'kvs> Visitor<'kvs> for Collect {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+            self.0.push((key.to_string(), value.to_string()));
+            Ok(())
+        }
+    }
+
+    let mut collect = Collect(Vec::new());
+    source.visit(&mut collect).unwrap();
+    collect.0
+}
+
+fn entries(map: &dyn Map) -> BTreeMap<String, String> {
+    struct Collect(BTreeMap<String, String>);
+
+    impl<'v> VisitMap<'v> for Collect {
+        fn visit_entry(&mut self, key: Value<'v>, value: Value<'v>) -> Result<(), Error> {
+            self.0.insert(key.to_string(), value.to_string());
+            Ok(())
+        }
+    }
+
+    let mut collect = Collect(BTreeMap::new());
+    map.visit_map(&mut collect).unwrap();
+    collect.0
+}
+
+#[test]
+fn source() {
+    let order = order();
+
+    assert_eq!(
+        vec![
+            ("order_id".to_owned(), "42".to_owned()),
+            ("quantity".to_owned(), "3".to_owned()),
+            ("card".to_owned(), "[REDACTED]".to_owned()),
+            ("name".to_owned(), "Ferris".to_owned()),
+            ("type".to_owned(), "crab".to_owned()),
+        ],
+        pairs(&order)
+    );
+    assert_eq!(5, order.count());
+}
+
+#[test]
+fn source_get() {
+    let order = order();
+
+    assert_eq!(
+        Some(42),
+        order.get(Key::from_str("order_id")).unwrap().to_u64()
+    );
+    assert!(order.get(Key::from_str("card")).unwrap().is_sensitive());
+    assert_eq!(
+        Some("Ferris"),
+        order.get(Key::from_str("name")).unwrap().to_borrowed_str()
+    );
+    assert!(order.get(Key::from_str("id")).is_none());
+    assert!(order.get(Key::from_str("notes")).is_none());
+}
+
+#[test]
+fn to_value() {
+    let order = order();
+
+    let mut expected = BTreeMap::new();
+    expected.insert("order_id".to_owned(), "42".to_owned());
+    expected.insert("quantity".to_owned(), "3".to_owned());
+    expected.insert("card".to_owned(), "[REDACTED]".to_owned());
+    expected.insert("name".to_owned(), "Ferris".to_owned());
+    expected.insert("type".to_owned(), "crab".to_owned());
+
+    assert_eq!(expected, entries(&order));
+    assert_eq!(
+        r#"{"order_id": 42, "quantity": 3, "card": [REDACTED], "name": "Ferris", "type": "crab"}"#,
+        format!("{:?}", order.to_value())
+    );
+}
+
+#[test]
+fn generic() {
+    let wrapper = Wrapper { inner: 1 };
+
+    assert_eq!(vec![("inner".to_owned(), "1".to_owned())], pairs(&wrapper));
+    assert_eq!("{inner: 1}", wrapper.to_value().to_string());
+}
+
+#[test]
+fn empty() {
+    assert_eq!(0, Empty {}.count());
+    assert!(Empty {}.get(Key::from_str("a")).is_none());
+    assert_eq!("{}", Empty {}.to_value().to_string());
+}
+
+#[test]
+fn log_macro() {
+    let order = order();
+
+    log::info!(order = order; "placed");
+}
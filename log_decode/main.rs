@@ -0,0 +1,305 @@
+//! Decode a stream written by `log::binary::BinaryLogger`.
+//!
+//! ```text
+//! log_decode [--json] [FILE]
+//! ```
+//!
+//! Reads from `FILE`, or standard input if it's omitted, and prints one line per
+//! record, either as text or as a JSON object. Control characters in text
+//! output are escaped, like `\n`, so records never span more than one line.
+
+use std::env;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::process;
+
+use log::binary::{DecodedRecord, DecodedValue, Decoder};
+
+const USAGE: &str = "usage: log_decode [--json] [FILE]";
+
+fn main() {
+    let mut json = false;
+    let mut path = None;
+
+    for arg in env::args().skip(1) {
+        match &*arg {
+            "--json" => json = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if path.is_none() && !arg.starts_with('-') => path = Some(arg),
+            _ => {
+                eprintln!("{}", USAGE);
+                process::exit(2);
+            }
+        }
+    }
+
+    let input: Box<dyn Read> = match path {
+        Some(path) => match File::open(&path) {
+            Ok(file) => Box::new(file),
+            Err(e) => {
+                eprintln!("log_decode: {}: {}", path, e);
+                process::exit(1);
+            }
+        },
+        None => Box::new(io::stdin()),
+    };
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    if let Err(e) = decode(BufReader::new(input), &mut out, json) {
+        eprintln!("log_decode: {}", e);
+        process::exit(1);
+    }
+}
+
+fn decode(input: impl Read, mut out: impl Write, json: bool) -> io::Result<()> {
+    for record in Decoder::new(input) {
+        let record = record?;
+
+        let line = if json {
+            to_json(&record)
+        } else {
+            to_text(&record)
+        };
+
+        writeln!(out, "{}", line)?;
+    }
+
+    out.flush()
+}
+
+fn to_text(record: &DecodedRecord) -> String {
+    let mut line = format!("{:<5} ", record.level());
+    text_str(&mut line, record.target());
+
+    if let Some(file) = record.file() {
+        line.push(' ');
+        text_str(&mut line, file);
+        if let Some(n) = record.line() {
+            let _ = write!(line, ":{}", n);
+        }
+    }
+
+    line.push_str(": ");
+    text_str(&mut line, record.args());
+
+    for (key, value) in record.key_values() {
+        line.push(' ');
+        text_str(&mut line, key);
+        line.push('=');
+        text_str(&mut line, &value.to_string());
+    }
+
+    line
+}
+
+// Escape control characters so each record stays on its own line
+fn text_str(out: &mut String, s: &str) {
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{{{:x}}}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+}
+
+fn to_json(record: &DecodedRecord) -> String {
+    let mut line = String::from("{\"level\":");
+    json_str(&mut line, record.level().as_str());
+
+    line.push_str(",\"target\":");
+    json_str(&mut line, record.target());
+
+    if let Some(module_path) = record.module_path() {
+        line.push_str(",\"module_path\":");
+        json_str(&mut line, module_path);
+    }
+
+    if let Some(file) = record.file() {
+        line.push_str(",\"file\":");
+        json_str(&mut line, file);
+    }
+
+    if let Some(n) = record.line() {
+        let _ = write!(line, ",\"line\":{}", n);
+    }
+
+    line.push_str(",\"message\":");
+    json_str(&mut line, record.args());
+
+    if !record.key_values().is_empty() {
+        line.push_str(",\"key_values\":{");
+        for (i, (key, value)) in record.key_values().iter().enumerate() {
+            if i > 0 {
+                line.push(',');
+            }
+
+            json_str(&mut line, key);
+            line.push(':');
+            json_value(&mut line, value);
+        }
+        line.push('}');
+    }
+
+    line.push('}');
+    line
+}
+
+fn json_value(out: &mut String, value: &DecodedValue) {
+    match value {
+        DecodedValue::U64(v) => write_num(out, v),
+        DecodedValue::I64(v) => write_num(out, v),
+        DecodedValue::U128(v) => write_num(out, v),
+        DecodedValue::I128(v) => write_num(out, v),
+        DecodedValue::F32(v) if v.is_finite() => write_num(out, v),
+        DecodedValue::F64(v) if v.is_finite() => write_num(out, v),
+        DecodedValue::F32(_) | DecodedValue::F64(_) => out.push_str("null"),
+        DecodedValue::Bool(v) => write_num(out, v),
+        DecodedValue::Char(v) => json_str(out, v.encode_utf8(&mut [0; 4])),
+        DecodedValue::Str(v) => json_str(out, v),
+    }
+}
+
+fn write_num(out: &mut String, v: impl std::fmt::Display) {
+    let _ = write!(out, "{}", v);
+}
+
+fn json_str(out: &mut String, s: &str) {
+    out.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use log::binary::BinaryLogger;
+    use log::{Level, LevelFilter, Log, Record};
+
+    fn sample() -> Vec<u8> {
+        let logger = BinaryLogger::new(Vec::new(), LevelFilter::Trace);
+
+        logger.log(
+            &Record::builder()
+                .args(format_args!("héllo \"{}\"", "wörld"))
+                .level(Level::Warn)
+                .target("app")
+                .module_path_static(Some("app::net"))
+                .file_static(Some("src/net.rs"))
+                .line(Some(12))
+                .build(),
+        );
+        logger.log(
+            &Record::builder()
+                .args(format_args!("tab\tline\nbell\u{7}"))
+                .level(Level::Debug)
+                .target("ziel")
+                .build(),
+        );
+
+        logger.into_inner()
+    }
+
+    fn run(input: &[u8], json: bool) -> io::Result<String> {
+        let mut out = Vec::new();
+        decode(input, &mut out, json)?;
+
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(
+            "WARN  app src/net.rs:12: héllo \"wörld\"\nDEBUG ziel: tab\\tline\\nbell\\u{7}\n",
+            run(&sample(), false).unwrap()
+        );
+    }
+
+    #[test]
+    fn text_escapes() {
+        let mut out = String::new();
+        text_str(&mut out, "a\\b\r\nc\u{1b}d\u{85}é");
+
+        assert_eq!(r"a\\b\r\nc\u{1b}d\u{85}é", out);
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!(
+            concat!(
+                r#"{"level":"WARN","target":"app","module_path":"app::net","file":"src/net.rs","line":12,"message":"héllo \"wörld\""}"#,
+                "\n",
+                r#"{"level":"DEBUG","target":"ziel","message":"tab\tline\nbell\u0007"}"#,
+                "\n",
+            ),
+            run(&sample(), true).unwrap()
+        );
+    }
+
+    #[test]
+    fn json_values() {
+        let mut out = String::new();
+        for value in &[
+            DecodedValue::U64(1),
+            DecodedValue::I64(-1),
+            DecodedValue::F64(f64::NAN),
+            DecodedValue::F32(0.5),
+            DecodedValue::Bool(true),
+            DecodedValue::Char('⛰'),
+            DecodedValue::Str("a\\b".into()),
+        ] {
+            json_value(&mut out, value);
+            out.push(' ');
+        }
+
+        assert_eq!(r#"1 -1 null 0.5 true "⛰" "a\\b" "#, out);
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!("", run(&[], false).unwrap());
+    }
+
+    #[test]
+    fn invalid() {
+        let err = run(b"not a log", false).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+
+        // Records before a truncated one are still written
+        let bytes = sample();
+        let mut out = Vec::new();
+        let err = decode(&bytes[..bytes.len() - 2], &mut out, false).unwrap_err();
+
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        assert_eq!(
+            "WARN  app src/net.rs:12: héllo \"wörld\"\n",
+            String::from_utf8(out).unwrap()
+        );
+    }
+}
@@ -3,12 +3,12 @@
 use self::sealed::KVs;
 use crate::{Level, Metadata, Record};
 use std::fmt::Arguments;
-pub use std::{file, format_args, line, module_path, stringify};
+pub use std::{compile_error, file, format_args, line, module_path, stringify};
 
-#[cfg(feature = "kv_unstable")]
+#[cfg(feature = "kv")]
 pub type Value<'a> = dyn crate::kv::value::ToValue + 'a;
 
-#[cfg(not(feature = "kv_unstable"))]
+#[cfg(not(feature = "kv"))]
 pub type Value<'a> = str;
 
 mod sealed {
@@ -43,11 +43,9 @@ fn log_impl(
     line: u32,
     kvs: Option<&[(&str, &Value)]>,
 ) {
-    #[cfg(not(feature = "kv_unstable"))]
+    #[cfg(not(feature = "kv"))]
     if kvs.is_some() {
-        panic!(
-            "key-value support is experimental and must be enabled using the `kv_unstable` feature"
-        )
+        panic!("key-value support must be enabled using the `kv` feature")
     }
 
     let mut builder = Record::builder();
@@ -60,7 +58,7 @@ fn log_impl(
         .file_static(Some(file))
         .line(Some(line));
 
-    #[cfg(feature = "kv_unstable")]
+    #[cfg(feature = "kv")]
     builder.key_values(&kvs);
 
     crate::logger().log(&builder.build());
@@ -84,6 +82,63 @@ pub fn log<'a, K>(
     )
 }
 
+#[cfg(feature = "kv")]
+mod kv_support {
+    use crate::kv::Value;
+    use std::fmt;
+
+    pub fn capture_debug<V: fmt::Debug + 'static>(v: &V) -> Value<'_> {
+        Value::capture_debug(v)
+    }
+
+    pub fn capture_display<V: fmt::Display + 'static>(v: &V) -> Value<'_> {
+        Value::capture_display(v)
+    }
+
+    #[cfg(feature = "kv_std")]
+    pub fn capture_error<V: std::error::Error + 'static>(v: &V) -> Value<'_> {
+        Value::capture_error(v)
+    }
+
+    #[cfg(feature = "kv_serde")]
+    pub fn capture_serde<V: serde::Serialize + 'static>(v: &V) -> Value<'_> {
+        Value::capture_serde(v)
+    }
+
+    #[cfg(feature = "kv_sval")]
+    pub fn capture_sval<V: sval::Value + 'static>(v: &V) -> Value<'_> {
+        Value::capture_sval(v)
+    }
+}
+
+#[cfg(feature = "kv")]
+pub use self::kv_support::*;
+
 pub fn enabled(level: Level, target: &str) -> bool {
     crate::logger().enabled(&Metadata::builder().level(level).target(target).build())
 }
+
+#[cfg(feature = "deferred")]
+pub const fn validate_deferred_template(template: &str) -> bool {
+    crate::deferred::validate_template(template)
+}
+
+#[cfg(feature = "deferred")]
+pub fn log_deferred(
+    args: &crate::deferred::DeferredArgs,
+    level: Level,
+    &(target, module_path, file): &(&str, &'static str, &'static str),
+    line: u32,
+) {
+    crate::logger().log(
+        &Record::builder()
+            .args(format_args!("{}", args))
+            .deferred_args(Some(args))
+            .level(level)
+            .target(target)
+            .module_path_static(Some(module_path))
+            .file_static(Some(file))
+            .line(Some(line))
+            .build(),
+    );
+}
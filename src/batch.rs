@@ -0,0 +1,573 @@
+//! Logging records in batches.
+//!
+//! Some sinks, like HTTP endpoints and databases, are much cheaper to write to
+//! once for many records than once per record. [`Batching`] wraps a
+//! [`BatchLog`] and collects records into batches for it, so those sinks only
+//! need to deal with whole batches.
+//!
+//! Requires the `std` feature.
+//!
+//! # Examples
+//!
+//! ```edition2018
+//! use std::time::Duration;
+//!
+//! use log::batch::{BatchLog, Batching};
+//! use log::OwnedRecord;
+//!
+//! struct Database;
+//!
+//! impl BatchLog for Database {
+//!     fn log_batch(&self, records: &[OwnedRecord]) {
+//!         // insert all the records in a single statement
+//!         # let _ = records;
+//!     }
+//! }
+//!
+//! let logger = Batching::new(Database)
+//!     .max_records(500)
+//!     .max_delay(Duration::from_secs(5));
+//!
+//! log::set_boxed_logger(Box::new(logger)).unwrap();
+//! log::set_max_level(log::LevelFilter::Info);
+//! ```
+//!
+//! [`Batching`]: struct.Batching.html
+//! [`BatchLog`]: trait.BatchLog.html
+
+use std::cell::Cell;
+use std::fmt;
+use std::mem;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::{Log, Metadata, OwnedRecord, Record};
+
+thread_local! {
+    // Whether this thread is inside `BatchLog::log_batch`
+    static IN_LOG_BATCH: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A sink that logs records in batches.
+pub trait BatchLog {
+    /// Log a batch of records.
+    ///
+    /// Records are in the order they were logged, and batches are passed in
+    /// the order they were collected. Batches are never empty.
+    fn log_batch(&self, records: &[OwnedRecord]);
+
+    /// Flush any records the sink itself buffers.
+    ///
+    /// This is called by [`Batching`]'s [`Log::flush`] after any partial
+    /// batch has been logged. The default implementation does nothing.
+    ///
+    /// [`Batching`]: struct.Batching.html
+    /// [`Log::flush`]: ../trait.Log.html#tymethod.flush
+    fn flush(&self) {}
+}
+
+impl<T> BatchLog for &'_ T
+where
+    T: ?Sized + BatchLog,
+{
+    fn log_batch(&self, records: &[OwnedRecord]) {
+        (**self).log_batch(records);
+    }
+
+    fn flush(&self) {
+        (**self).flush();
+    }
+}
+
+impl<T> BatchLog for Box<T>
+where
+    T: ?Sized + BatchLog,
+{
+    fn log_batch(&self, records: &[OwnedRecord]) {
+        self.as_ref().log_batch(records);
+    }
+
+    fn flush(&self) {
+        self.as_ref().flush();
+    }
+}
+
+impl<T> BatchLog for Arc<T>
+where
+    T: ?Sized + BatchLog,
+{
+    fn log_batch(&self, records: &[OwnedRecord]) {
+        self.as_ref().log_batch(records);
+    }
+
+    fn flush(&self) {
+        self.as_ref().flush();
+    }
+}
+
+/// A [`Log`] that collects records into batches for a [`BatchLog`].
+///
+/// Records are captured as [`OwnedRecord`]s and added to the current batch.
+/// The batch is passed to [`BatchLog::log_batch`] as soon as any of these
+/// limits is reached:
+///
+/// - it has [`max_records`] records, 100 by default.
+/// - it has [`max_bytes`] bytes of messages, targets, source locations, and
+///   key-values (with `kv_std`), 1 MiB by default.
+/// - its first record was logged [`max_delay`] ago, 1 second by default.
+///
+/// Batches that reach a size limit are logged by the thread that logged their
+/// last record. Batches that reach the time limit are logged by a background
+/// thread, which is started when the first record is logged and stopped when
+/// the `Batching` is dropped.
+///
+/// [`Log::flush`] logs the current batch, even if it hasn't reached a limit,
+/// then flushes the inner sink. Dropping a `Batching` also logs the current
+/// batch.
+///
+/// Sinks like HTTP or database clients may log records themselves. Records
+/// logged while a batch is being logged are added to the next batch, rather
+/// than logging a batch from inside [`BatchLog::log_batch`].
+///
+/// All records are enabled; use [`set_max_level`] to filter them.
+///
+/// [`Log`]: ../trait.Log.html
+/// [`BatchLog`]: trait.BatchLog.html
+/// [`OwnedRecord`]: ../struct.OwnedRecord.html
+/// [`BatchLog::log_batch`]: trait.BatchLog.html#tymethod.log_batch
+/// [`max_records`]: #method.max_records
+/// [`max_bytes`]: #method.max_bytes
+/// [`max_delay`]: #method.max_delay
+/// [`Log::flush`]: ../trait.Log.html#tymethod.flush
+/// [`set_max_level`]: ../fn.set_max_level.html
+pub struct Batching<L>
+where
+    L: BatchLog,
+{
+    shared: Arc<Shared<L>>,
+}
+
+struct Shared<L> {
+    inner: L,
+    // Held while a batch is taken and logged, so batches are logged in order
+    sending: Mutex<()>,
+    state: Mutex<State>,
+    // Signals the timer thread that a batch was started, or that it should stop
+    wake: Condvar,
+}
+
+struct State {
+    max_records: usize,
+    max_bytes: usize,
+    max_delay: Duration,
+    records: Vec<OwnedRecord>,
+    bytes: usize,
+    started: Option<Instant>,
+    timer: Option<JoinHandle<()>>,
+    stopping: bool,
+}
+
+impl<L> Batching<L>
+where
+    L: BatchLog,
+{
+    /// Wrap a sink.
+    pub fn new(inner: L) -> Self {
+        Batching {
+            shared: Arc::new(Shared {
+                inner,
+                sending: Mutex::new(()),
+                state: Mutex::new(State {
+                    max_records: 100,
+                    max_bytes: 1024 * 1024,
+                    max_delay: Duration::from_secs(1),
+                    records: Vec::new(),
+                    bytes: 0,
+                    started: None,
+                    timer: None,
+                    stopping: false,
+                }),
+                wake: Condvar::new(),
+            }),
+        }
+    }
+
+    /// Set the most records in a batch.
+    ///
+    /// A limit of `0` or `1` logs each record in its own batch.
+    pub fn max_records(self, records: usize) -> Self {
+        self.shared.lock().max_records = records;
+        self
+    }
+
+    /// Set the most bytes in a batch.
+    ///
+    /// A batch is logged when its records reach this size, so the last record
+    /// may take it over the limit.
+    pub fn max_bytes(self, bytes: usize) -> Self {
+        self.shared.lock().max_bytes = bytes;
+        self
+    }
+
+    /// Set the longest a record waits in a batch before it's logged.
+    pub fn max_delay(self, delay: Duration) -> Self {
+        self.shared.lock().max_delay = delay;
+        self
+    }
+
+    /// Get a reference to the wrapped sink.
+    pub fn inner(&self) -> &L {
+        &self.shared.inner
+    }
+}
+
+impl<L> Shared<L>
+where
+    L: BatchLog,
+{
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    // Take the current batch and log it
+    fn send(&self) {
+        // A sink that logs would otherwise deadlock on `sending`
+        if IN_LOG_BATCH.with(Cell::get) {
+            return;
+        }
+
+        let _sending = self.sending.lock().unwrap_or_else(|err| err.into_inner());
+
+        let records = {
+            let mut state = self.lock();
+            state.bytes = 0;
+            state.started = None;
+            mem::take(&mut state.records)
+        };
+
+        if !records.is_empty() {
+            struct Reset;
+
+            impl Drop for Reset {
+                fn drop(&mut self) {
+                    IN_LOG_BATCH.with(|flag| flag.set(false));
+                }
+            }
+
+            IN_LOG_BATCH.with(|flag| flag.set(true));
+            let _reset = Reset;
+
+            self.inner.log_batch(&records);
+        }
+    }
+
+    fn run_timer(&self) {
+        let mut state = self.lock();
+
+        while !state.stopping {
+            state = match state.started.map(|started| started.elapsed()) {
+                Some(elapsed) if elapsed >= state.max_delay => {
+                    drop(state);
+                    self.send();
+                    self.lock()
+                }
+                Some(elapsed) => {
+                    let wait = state.max_delay - elapsed;
+                    self.wake
+                        .wait_timeout(state, wait)
+                        .unwrap_or_else(|err| err.into_inner())
+                        .0
+                }
+                None => self.wake.wait(state).unwrap_or_else(|err| err.into_inner()),
+            };
+        }
+    }
+}
+
+impl<L> Log for Batching<L>
+where
+    L: BatchLog + Send + Sync + 'static,
+{
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        let record = OwnedRecord::from_record(record);
+        let bytes = size(&record);
+
+        let full = {
+            let mut state = self.shared.lock();
+
+            if state.timer.is_none() {
+                let shared = self.shared.clone();
+                state.timer = Some(thread::spawn(move || shared.run_timer()));
+            }
+
+            if state.started.is_none() {
+                state.started = Some(Instant::now());
+                self.shared.wake.notify_one();
+            }
+
+            state.records.push(record);
+            state.bytes += bytes;
+
+            state.records.len() >= state.max_records || state.bytes >= state.max_bytes
+        };
+
+        if full {
+            self.shared.send();
+        }
+    }
+
+    fn flush(&self) {
+        self.shared.send();
+        self.shared.inner.flush();
+    }
+}
+
+impl<L> Drop for Batching<L>
+where
+    L: BatchLog,
+{
+    fn drop(&mut self) {
+        let timer = {
+            let mut state = self.shared.lock();
+            state.stopping = true;
+            self.shared.wake.notify_one();
+            state.timer.take()
+        };
+
+        if let Some(timer) = timer {
+            let _ = timer.join();
+        }
+
+        self.shared.send();
+    }
+}
+
+impl<L> fmt::Debug for Batching<L>
+where
+    L: BatchLog + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = self.shared.lock();
+
+        f.debug_struct("Batching")
+            .field("inner", &self.shared.inner)
+            .field("max_records", &state.max_records)
+            .field("max_bytes", &state.max_bytes)
+            .field("max_delay", &state.max_delay)
+            .finish()
+    }
+}
+
+// The number of bytes a record counts towards `max_bytes`
+fn size(record: &OwnedRecord) -> usize {
+    #[allow(unused_mut)]
+    let mut size = record.message().len()
+        + record.target().len()
+        + record.module_path().map_or(0, str::len)
+        + record.file().map_or(0, str::len);
+
+    #[cfg(feature = "kv_std")]
+    {
+        use crate::kv::{Error, Key, Source, Value, Visitor};
+        use std::fmt::Write as _;
+
+        struct Count(usize);
+
+        impl fmt::Write for Count {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.0 += s.len();
+                Ok(())
+            }
+        }
+
+        impl<'kvs> Visitor<'kvs> for Count {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                Ok(write!(self, "{}{}", key, value)?)
+            }
+        }
+
+        let mut count = Count(0);
+        let _ = record.key_values().visit(&mut count);
+        size += count.0;
+    }
+
+    size
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct Collect {
+        batches: Mutex<Vec<Vec<String>>>,
+        flushes: AtomicUsize,
+    }
+
+    impl Collect {
+        fn batches(&self) -> Vec<Vec<String>> {
+            self.batches.lock().unwrap().clone()
+        }
+    }
+
+    impl BatchLog for Collect {
+        fn log_batch(&self, records: &[OwnedRecord]) {
+            self.batches.lock().unwrap().push(
+                records
+                    .iter()
+                    .map(|record| record.message().to_owned())
+                    .collect(),
+            );
+        }
+
+        fn flush(&self) {
+            self.flushes.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn log(logger: &(impl Log + ?Sized), message: &str) {
+        logger.log(&Record::builder().args(format_args!("{}", message)).build());
+    }
+
+    fn batches(batches: &[&[&str]]) -> Vec<Vec<String>> {
+        batches
+            .iter()
+            .map(|batch| batch.iter().map(|message| message.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn max_records() {
+        let logger = Batching::new(Collect::default())
+            .max_records(2)
+            .max_delay(Duration::from_secs(60 * 60));
+
+        for message in &["a", "b", "c", "d", "e"] {
+            log(&logger, message);
+        }
+        assert_eq!(
+            batches(&[&["a", "b"], &["c", "d"]]),
+            logger.inner().batches()
+        );
+
+        logger.flush();
+        assert_eq!(
+            batches(&[&["a", "b"], &["c", "d"], &["e"]]),
+            logger.inner().batches()
+        );
+        assert_eq!(1, logger.inner().flushes.load(Ordering::Relaxed));
+
+        // Flushing without a partial batch doesn't log an empty one
+        logger.flush();
+        assert_eq!(3, logger.inner().batches().len());
+    }
+
+    #[test]
+    fn max_bytes() {
+        let logger = Batching::new(Collect::default())
+            .max_bytes(10)
+            .max_delay(Duration::from_secs(60 * 60));
+
+        log(&logger, "aaaa");
+        log(&logger, "bbbb");
+        assert!(logger.inner().batches().is_empty());
+
+        log(&logger, "cccc");
+        assert_eq!(
+            batches(&[&["aaaa", "bbbb", "cccc"]]),
+            logger.inner().batches()
+        );
+    }
+
+    #[test]
+    fn max_delay() {
+        let logger = Batching::new(Collect::default()).max_delay(Duration::from_millis(10));
+
+        log(&logger, "a");
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while logger.inner().batches().is_empty() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(batches(&[&["a"]]), logger.inner().batches());
+
+        // The timer restarts with the next batch
+        log(&logger, "b");
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while logger.inner().batches().len() < 2 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(batches(&[&["a"], &["b"]]), logger.inner().batches());
+    }
+
+    #[test]
+    fn drop_logs_partial_batch() {
+        let collect = Arc::new(Collect::default());
+
+        let logger = Batching::new(collect.clone()).max_delay(Duration::from_secs(60 * 60));
+        log(&logger, "a");
+        drop(logger);
+
+        assert_eq!(batches(&[&["a"]]), collect.batches());
+    }
+
+    #[test]
+    fn log_batch_logs() {
+        // A sink that logs through the logger it's batched by
+        #[derive(Default)]
+        struct Chatty {
+            logger: Mutex<Option<Arc<dyn Log>>>,
+            collect: Collect,
+        }
+
+        impl BatchLog for Arc<Chatty> {
+            fn log_batch(&self, records: &[OwnedRecord]) {
+                self.collect.log_batch(records);
+
+                let logger = self.logger.lock().unwrap().clone();
+                if let Some(logger) = logger {
+                    log(&*logger, "sent");
+                    logger.flush();
+                }
+            }
+        }
+
+        let chatty = Arc::new(Chatty::default());
+        let logger = Arc::new(
+            Batching::new(chatty.clone())
+                .max_records(1)
+                .max_delay(Duration::from_secs(60 * 60)),
+        );
+        *chatty.logger.lock().unwrap() = Some(logger.clone());
+
+        log(&*logger, "a");
+        assert_eq!(batches(&[&["a"]]), chatty.collect.batches());
+
+        // Records logged by the sink are logged with the next batch
+        log(&*logger, "b");
+        assert_eq!(batches(&[&["a"], &["sent", "b"]]), chatty.collect.batches());
+
+        chatty.logger.lock().unwrap().take();
+    }
+
+    #[cfg(feature = "kv_std")]
+    #[test]
+    fn size_key_values() {
+        let record = OwnedRecord::from_record(
+            &Record::builder()
+                .target("app")
+                .args(format_args!("started"))
+                .key_values(&[("port", 8080)])
+                .build(),
+        );
+
+        assert_eq!(18, size(&record));
+    }
+}
@@ -0,0 +1,1132 @@
+//! A compact binary encoding for log records.
+//!
+//! Formatting every record as text is wasteful when logging at high volume.
+//! A [`BinaryLogger`] instead writes each record in a compact binary form:
+//! strings that repeat across records, like targets, file names, format
+//! strings and keys, are written once and then referred to by a numeric id,
+//! and numbers are written as variable-length integers. Records logged with
+//! [`log_deferred!`] keep their arguments unformatted, so formatting happens
+//! when the stream is decoded rather than when the record is logged.
+//!
+//! A stream can be read back with a [`Decoder`]. The `log_decode` tool in the
+//! repository uses it to print streams as text or JSON.
+//!
+//! Requires the `binary` feature.
+//!
+//! # Examples
+//!
+//! ```edition2018
+//! use log::binary::{BinaryLogger, Decoder};
+//! use log::{Level, LevelFilter, Log, Record};
+//!
+//! # fn main() -> std::io::Result<()> {
+//! let logger = BinaryLogger::new(Vec::new(), LevelFilter::Info);
+//!
+//! logger.log(
+//!     &Record::builder()
+//!         .args(format_args!("hello binary"))
+//!         .level(Level::Info)
+//!         .target("app")
+//!         .build(),
+//! );
+//!
+//! let bytes = logger.into_inner();
+//!
+//! for record in Decoder::new(&bytes[..]) {
+//!     let record = record?;
+//!     assert_eq!("hello binary", record.args());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Format
+//!
+//! A stream starts with the bytes `\0LOGB` followed by a version byte, which is
+//! currently `1`. The rest of the stream is a sequence of frames:
+//!
+//! ```text
+//! frame   = 0x00 id:varint len:varint utf8        ; define a string
+//!         | 0x01 level:varint target:id
+//!                module:opt file:opt line:opt
+//!                message kvs                      ; a record
+//!         | 0x02                                  ; forget all strings
+//! message = 0x00 len:varint utf8                  ; a formatted message
+//!         | 0x01 template:id argc:varint value*   ; deferred arguments
+//! kvs     = count:varint (key:id value)*
+//! value   = 0x00 varint                           ; u64
+//!         | 0x01 zigzag varint                    ; i64
+//!         | 0x02 u128:16 | 0x03 i128:16
+//!         | 0x04 f32:4 | 0x05 f64:8
+//!         | 0x06 u8                               ; bool
+//!         | 0x07 varint                           ; char
+//!         | 0x08 len:varint utf8                  ; string
+//! ```
+//!
+//! Varints are unsigned LEB128, and fixed-size numbers are little-endian.
+//! An optional field `opt` is `0` when absent, and its value plus one otherwise.
+//! A string is defined before the first frame that refers to its id, and ids
+//! are assigned sequentially from `0`. To bound the memory used by the logger
+//! and decoder, once 4096 strings have been defined they're all forgotten
+//! before the next record, and ids start again from `0`.
+//!
+//! Key-values are only written when the `kv` feature is enabled.
+//! Values that aren't primitives are written as strings, and pairs whose value
+//! fails to format are left out.
+//!
+//! [`BinaryLogger`]: struct.BinaryLogger.html
+//! [`Decoder`]: struct.Decoder.html
+//! [`log_deferred!`]: ../macro.log_deferred.html
+
+use std::collections::HashMap;
+use std::fmt::{self, Write as _};
+use std::io::{self, Read, Write};
+use std::sync::{Mutex, PoisonError};
+
+use crate::deferred::{self, Arg};
+use crate::{Level, LevelFilter, Log, Metadata, Record};
+
+const MAGIC: &[u8] = b"\0LOGB";
+const VERSION: u8 = 1;
+
+const FRAME_DEFINE: u8 = 0;
+const FRAME_RECORD: u8 = 1;
+const FRAME_RESET: u8 = 2;
+
+// The number of strings defined before they're all forgotten
+const MAX_STRINGS: usize = 4096;
+
+const MESSAGE_FORMATTED: u8 = 0;
+const MESSAGE_DEFERRED: u8 = 1;
+
+const VALUE_U64: u8 = 0;
+const VALUE_I64: u8 = 1;
+const VALUE_U128: u8 = 2;
+const VALUE_I128: u8 = 3;
+const VALUE_F32: u8 = 4;
+const VALUE_F64: u8 = 5;
+const VALUE_BOOL: u8 = 6;
+const VALUE_CHAR: u8 = 7;
+const VALUE_STR: u8 = 8;
+
+/// A logger that writes records to a writer in a compact binary encoding.
+///
+/// Each record is written to the underlying writer with a single call to
+/// `write_all`, so wrapping a file in a `BufWriter` is recommended. Errors from
+/// the writer are ignored.
+///
+/// See the [module documentation](index.html) for details of the encoding.
+pub struct BinaryLogger<W> {
+    level: LevelFilter,
+    state: Mutex<State<W>>,
+}
+
+struct State<W> {
+    writer: W,
+    started: bool,
+    interner: Interner,
+    buf: Vec<u8>,
+    msg: String,
+}
+
+impl<W> BinaryLogger<W>
+where
+    W: Write + Send,
+{
+    /// Create a logger that writes records up to the given level to `writer`.
+    pub fn new(writer: W, level: LevelFilter) -> Self {
+        BinaryLogger {
+            level,
+            state: Mutex::new(State {
+                writer,
+                started: false,
+                interner: Interner::default(),
+                buf: Vec::new(),
+                msg: String::new(),
+            }),
+        }
+    }
+
+    /// Consume the logger and return the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.state
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+            .writer
+    }
+}
+
+impl<W> Log for BinaryLogger<W>
+where
+    W: Write + Send,
+{
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let state = &mut *state;
+
+        if !state.started {
+            state.interner.defs.extend_from_slice(MAGIC);
+            state.interner.defs.push(VERSION);
+            state.started = true;
+        }
+
+        // A record may refer to any string defined before it, so strings are
+        // only forgotten between records
+        if state.interner.ids.len() >= MAX_STRINGS {
+            state.interner.reset();
+        }
+
+        state.buf.clear();
+        encode_record(record, &mut state.interner, &mut state.buf, &mut state.msg);
+
+        // Any strings defined by this record are written before it
+        let defs = &mut state.interner.defs;
+        if !defs.is_empty() {
+            let _ = state.writer.write_all(defs);
+            defs.clear();
+        }
+
+        let _ = state.writer.write_all(&state.buf);
+    }
+
+    fn flush(&self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let _ = state.writer.flush();
+    }
+}
+
+impl<W> fmt::Debug for BinaryLogger<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BinaryLogger")
+            .field("level", &self.level)
+            .finish()
+    }
+}
+
+#[derive(Default)]
+struct Interner {
+    ids: HashMap<String, u64>,
+    defs: Vec<u8>,
+}
+
+impl Interner {
+    fn intern(&mut self, s: &str) -> u64 {
+        if let Some(&id) = self.ids.get(s) {
+            return id;
+        }
+
+        let id = self.ids.len() as u64;
+        self.ids.insert(s.into(), id);
+
+        self.defs.push(FRAME_DEFINE);
+        write_varint(&mut self.defs, id);
+        write_str(&mut self.defs, s);
+
+        id
+    }
+
+    fn reset(&mut self) {
+        self.ids.clear();
+        self.defs.push(FRAME_RESET);
+    }
+}
+
+fn encode_record(record: &Record, interner: &mut Interner, buf: &mut Vec<u8>, msg: &mut String) {
+    buf.push(FRAME_RECORD);
+    write_varint(buf, record.level() as u64);
+    write_varint(buf, interner.intern(record.target()));
+    write_varint(
+        buf,
+        record.module_path().map_or(0, |s| interner.intern(s) + 1),
+    );
+    write_varint(buf, record.file().map_or(0, |s| interner.intern(s) + 1));
+    write_varint(buf, record.line().map_or(0, |line| line as u64 + 1));
+
+    match record.deferred_args() {
+        Some(args) => {
+            buf.push(MESSAGE_DEFERRED);
+            write_varint(buf, interner.intern(args.template()));
+            write_varint(buf, args.args().count() as u64);
+
+            for arg in args.args() {
+                write_arg(buf, arg);
+            }
+        }
+        None => {
+            buf.push(MESSAGE_FORMATTED);
+            match record.args().as_str() {
+                Some(s) => write_str(buf, s),
+                None => {
+                    msg.clear();
+                    let _ = write!(msg, "{}", record.args());
+                    write_str(buf, msg);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "kv")]
+    kv_support::encode_key_values(record.key_values(), interner, buf);
+
+    #[cfg(not(feature = "kv"))]
+    write_varint(buf, 0);
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        buf.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+
+    buf.push(v as u8);
+}
+
+fn write_str(buf: &mut Vec<u8>, s: &str) {
+    write_varint(buf, s.len() as u64);
+    buf.extend_from_slice(s.as_bytes());
+}
+
+fn write_arg(buf: &mut Vec<u8>, arg: Arg) {
+    match arg {
+        Arg::U64(v) => {
+            buf.push(VALUE_U64);
+            write_varint(buf, v);
+        }
+        Arg::I64(v) => {
+            buf.push(VALUE_I64);
+            write_varint(buf, ((v << 1) ^ (v >> 63)) as u64);
+        }
+        Arg::U128(v) => {
+            buf.push(VALUE_U128);
+            buf.extend_from_slice(&v.to_le_bytes());
+        }
+        Arg::I128(v) => {
+            buf.push(VALUE_I128);
+            buf.extend_from_slice(&v.to_le_bytes());
+        }
+        Arg::F32(v) => {
+            buf.push(VALUE_F32);
+            buf.extend_from_slice(&v.to_le_bytes());
+        }
+        Arg::F64(v) => {
+            buf.push(VALUE_F64);
+            buf.extend_from_slice(&v.to_le_bytes());
+        }
+        Arg::Bool(v) => {
+            buf.push(VALUE_BOOL);
+            buf.push(v as u8);
+        }
+        Arg::Char(v) => {
+            buf.push(VALUE_CHAR);
+            write_varint(buf, v as u64);
+        }
+        Arg::Str(v) => {
+            buf.push(VALUE_STR);
+            write_str(buf, v);
+        }
+    }
+}
+
+#[cfg(feature = "kv")]
+mod kv_support {
+    use super::*;
+
+    use crate::kv::value::Visit;
+    use crate::kv::{Error, Key, Source, Value, Visitor};
+
+    pub(super) fn encode_key_values(
+        source: &dyn Source,
+        interner: &mut Interner,
+        buf: &mut Vec<u8>,
+    ) {
+        struct Encode<'a> {
+            interner: &'a mut Interner,
+            buf: &'a mut Vec<u8>,
+            pair: Vec<u8>,
+            count: u64,
+        }
+
+        impl<'a, 'kvs> Visitor<'kvs> for Encode<'a> {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                // Pairs whose values fail to encode are skipped, so they're
+                // encoded separately to keep their key out of the frame
+                self.pair.clear();
+                write_varint(&mut self.pair, self.interner.intern(key.as_str()));
+
+                if value.visit(EncodeValue(&mut self.pair)).is_ok() {
+                    self.buf.extend_from_slice(&self.pair);
+                    self.count += 1;
+                }
+
+                Ok(())
+            }
+        }
+
+        struct EncodeValue<'a>(&'a mut Vec<u8>);
+
+        impl<'a, 'v> Visit<'v> for EncodeValue<'a> {
+            fn visit_any(&mut self, value: Value) -> Result<(), Error> {
+                let mut s = String::new();
+                write!(s, "{}", value)?;
+
+                write_arg(self.0, Arg::Str(&s));
+                Ok(())
+            }
+
+            fn visit_u64(&mut self, value: u64) -> Result<(), Error> {
+                write_arg(self.0, Arg::U64(value));
+                Ok(())
+            }
+
+            fn visit_i64(&mut self, value: i64) -> Result<(), Error> {
+                write_arg(self.0, Arg::I64(value));
+                Ok(())
+            }
+
+            fn visit_u128(&mut self, value: u128) -> Result<(), Error> {
+                write_arg(self.0, Arg::U128(value));
+                Ok(())
+            }
+
+            fn visit_i128(&mut self, value: i128) -> Result<(), Error> {
+                write_arg(self.0, Arg::I128(value));
+                Ok(())
+            }
+
+            fn visit_f64(&mut self, value: f64) -> Result<(), Error> {
+                write_arg(self.0, Arg::F64(value));
+                Ok(())
+            }
+
+            fn visit_bool(&mut self, value: bool) -> Result<(), Error> {
+                write_arg(self.0, Arg::Bool(value));
+                Ok(())
+            }
+
+            fn visit_str(&mut self, value: &str) -> Result<(), Error> {
+                write_arg(self.0, Arg::Str(value));
+                Ok(())
+            }
+
+            fn visit_char(&mut self, value: char) -> Result<(), Error> {
+                write_arg(self.0, Arg::Char(value));
+                Ok(())
+            }
+        }
+
+        // The count is written before the pairs, so encode them separately first
+        let mut pairs = Vec::new();
+        let mut encode = Encode {
+            interner,
+            buf: &mut pairs,
+            pair: Vec::new(),
+            count: 0,
+        };
+        let _ = source.visit(&mut encode);
+
+        write_varint(buf, encode.count);
+        buf.extend_from_slice(&pairs);
+    }
+}
+
+/// A value decoded from a binary stream.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DecodedValue {
+    /// An unsigned integer.
+    U64(u64),
+    /// A signed integer.
+    I64(i64),
+    /// A big unsigned integer.
+    U128(u128),
+    /// A big signed integer.
+    I128(i128),
+    /// A single-precision floating point.
+    F32(f32),
+    /// A double-precision floating point.
+    F64(f64),
+    /// A boolean.
+    Bool(bool),
+    /// A Unicode character.
+    Char(char),
+    /// A string.
+    Str(String),
+}
+
+impl DecodedValue {
+    fn to_arg(&self) -> Arg<'_> {
+        match *self {
+            DecodedValue::U64(v) => Arg::U64(v),
+            DecodedValue::I64(v) => Arg::I64(v),
+            DecodedValue::U128(v) => Arg::U128(v),
+            DecodedValue::I128(v) => Arg::I128(v),
+            DecodedValue::F32(v) => Arg::F32(v),
+            DecodedValue::F64(v) => Arg::F64(v),
+            DecodedValue::Bool(v) => Arg::Bool(v),
+            DecodedValue::Char(v) => Arg::Char(v),
+            DecodedValue::Str(ref v) => Arg::Str(v),
+        }
+    }
+}
+
+impl fmt::Display for DecodedValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.to_arg(), f)
+    }
+}
+
+/// A record decoded from a binary stream.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedRecord {
+    level: Level,
+    target: String,
+    module_path: Option<String>,
+    file: Option<String>,
+    line: Option<u32>,
+    template: Option<String>,
+    args: String,
+    key_values: Vec<(String, DecodedValue)>,
+}
+
+impl DecodedRecord {
+    /// The verbosity level of the message.
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// The name of the target of the directive.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// The module path of the message.
+    pub fn module_path(&self) -> Option<&str> {
+        self.module_path.as_deref()
+    }
+
+    /// The source file containing the message.
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    /// The line containing the message.
+    pub fn line(&self) -> Option<u32> {
+        self.line
+    }
+
+    /// The message, formatted.
+    pub fn args(&self) -> &str {
+        &self.args
+    }
+
+    /// The format string of the message, if it was logged with deferred arguments.
+    pub fn template(&self) -> Option<&str> {
+        self.template.as_deref()
+    }
+
+    /// The structured key-value pairs associated with the message.
+    pub fn key_values(&self) -> &[(String, DecodedValue)] {
+        &self.key_values
+    }
+}
+
+/// Reads records from a binary stream written by a [`BinaryLogger`].
+///
+/// The decoder is an iterator of records, which ends at the end of the stream.
+///
+/// [`BinaryLogger`]: struct.BinaryLogger.html
+#[derive(Debug)]
+pub struct Decoder<R> {
+    reader: R,
+    started: bool,
+    strings: Vec<String>,
+}
+
+impl<R> Decoder<R>
+where
+    R: Read,
+{
+    /// Create a decoder that reads from the start of a stream.
+    pub fn new(reader: R) -> Self {
+        Decoder {
+            reader,
+            started: false,
+            strings: Vec::new(),
+        }
+    }
+
+    /// Read the next record.
+    ///
+    /// Returns `Ok(None)` at the end of the stream.
+    pub fn read_record(&mut self) -> io::Result<Option<DecodedRecord>> {
+        if !self.started {
+            let mut header = [0; 6];
+            match self.read_tag()? {
+                Some(b) => header[0] = b,
+                None => return Ok(None),
+            }
+            self.reader.read_exact(&mut header[1..])?;
+
+            if &header[..MAGIC.len()] != MAGIC {
+                return Err(invalid_data("not a binary log stream"));
+            }
+            if header[MAGIC.len()] != VERSION {
+                return Err(invalid_data("unsupported binary log version"));
+            }
+
+            self.started = true;
+        }
+
+        loop {
+            match self.read_tag()? {
+                None => return Ok(None),
+                Some(FRAME_DEFINE) => {
+                    let id = self.read_varint()?;
+                    if id != self.strings.len() as u64 {
+                        return Err(invalid_data("string defined out of order"));
+                    }
+
+                    let s = self.read_string()?;
+                    self.strings.push(s);
+                }
+                Some(FRAME_RECORD) => return self.read_record_frame().map(Some),
+                Some(FRAME_RESET) => self.strings.clear(),
+                Some(_) => return Err(invalid_data("unknown frame")),
+            }
+        }
+    }
+
+    /// Consume the decoder and return the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn read_record_frame(&mut self) -> io::Result<DecodedRecord> {
+        let level = match self.read_varint()? {
+            1 => Level::Error,
+            2 => Level::Warn,
+            3 => Level::Info,
+            4 => Level::Debug,
+            5 => Level::Trace,
+            _ => return Err(invalid_data("invalid level")),
+        };
+
+        let target = self.read_id()?.to_owned();
+        let module_path = self.read_opt_id()?;
+        let file = self.read_opt_id()?;
+        let line = match self.read_varint()? {
+            0 => None,
+            line => Some(
+                u32::try_from(line - 1).map_err(|_| invalid_data("line number out of range"))?,
+            ),
+        };
+
+        let (template, args) = match self.read_u8()? {
+            MESSAGE_FORMATTED => (None, self.read_string()?),
+            MESSAGE_DEFERRED => {
+                let template = self.read_id()?.to_owned();
+                if !deferred::validate_template(&template) {
+                    return Err(invalid_data("invalid template"));
+                }
+
+                let argc = self.read_varint()?;
+                let mut values = Vec::new();
+                for _ in 0..argc {
+                    values.push(self.read_value()?);
+                }
+
+                let mut args = String::new();
+                write!(args, "{}", Render(&template, &values))
+                    .map_err(|_| invalid_data("failed to render template"))?;
+                (Some(template), args)
+            }
+            _ => return Err(invalid_data("unknown message encoding")),
+        };
+
+        let count = self.read_varint()?;
+        let mut key_values = Vec::new();
+        for _ in 0..count {
+            let key = self.read_id()?.to_owned();
+            let value = self.read_value()?;
+
+            key_values.push((key, value));
+        }
+
+        Ok(DecodedRecord {
+            level,
+            target,
+            module_path,
+            file,
+            line,
+            template,
+            args,
+            key_values,
+        })
+    }
+
+    fn read_value(&mut self) -> io::Result<DecodedValue> {
+        Ok(match self.read_u8()? {
+            VALUE_U64 => DecodedValue::U64(self.read_varint()?),
+            VALUE_I64 => {
+                let v = self.read_varint()?;
+                DecodedValue::I64((v >> 1) as i64 ^ -((v & 1) as i64))
+            }
+            VALUE_U128 => DecodedValue::U128(u128::from_le_bytes(self.read_array()?)),
+            VALUE_I128 => DecodedValue::I128(i128::from_le_bytes(self.read_array()?)),
+            VALUE_F32 => DecodedValue::F32(f32::from_le_bytes(self.read_array()?)),
+            VALUE_F64 => DecodedValue::F64(f64::from_le_bytes(self.read_array()?)),
+            VALUE_BOOL => DecodedValue::Bool(self.read_u8()? != 0),
+            VALUE_CHAR => DecodedValue::Char(
+                u32::try_from(self.read_varint()?)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| invalid_data("invalid char"))?,
+            ),
+            VALUE_STR => DecodedValue::Str(self.read_string()?),
+            _ => return Err(invalid_data("unknown value type")),
+        })
+    }
+
+    fn read_id(&mut self) -> io::Result<&str> {
+        let id = self.read_varint()?;
+
+        self.strings
+            .get(id as usize)
+            .map(|s| &**s)
+            .ok_or_else(|| invalid_data("undefined string id"))
+    }
+
+    fn read_opt_id(&mut self) -> io::Result<Option<String>> {
+        match self.read_varint()? {
+            0 => Ok(None),
+            id => self
+                .strings
+                .get(id as usize - 1)
+                .map(|s| Some(s.clone()))
+                .ok_or_else(|| invalid_data("undefined string id")),
+        }
+    }
+
+    fn read_string(&mut self) -> io::Result<String> {
+        let len = self.read_varint()?;
+
+        // Don't trust the length for allocating up front
+        let mut bytes = Vec::new();
+        (&mut self.reader).take(len).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        String::from_utf8(bytes).map_err(|_| invalid_data("invalid UTF-8"))
+    }
+
+    fn read_varint(&mut self) -> io::Result<u64> {
+        let mut v = 0;
+        let mut shift = 0;
+
+        loop {
+            let b = self.read_u8()?;
+            if shift == 63 && b > 1 {
+                return Err(invalid_data("varint overflow"));
+            }
+
+            v |= ((b & 0x7f) as u64) << shift;
+            if b & 0x80 == 0 {
+                return Ok(v);
+            }
+
+            shift += 7;
+        }
+    }
+
+    fn read_array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut bytes = [0; N];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> io::Result<u8> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    // Read a byte, or `None` at a clean end of the stream.
+    fn read_tag(&mut self) -> io::Result<Option<u8>> {
+        let mut b = [0];
+        loop {
+            match self.reader.read(&mut b) {
+                Ok(0) => return Ok(None),
+                Ok(_) => return Ok(Some(b[0])),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl<R> Iterator for Decoder<R>
+where
+    R: Read,
+{
+    type Item = io::Result<DecodedRecord>;
+
+    fn next(&mut self) -> Option<io::Result<DecodedRecord>> {
+        self.read_record().transpose()
+    }
+}
+
+struct Render<'a>(&'a str, &'a [DecodedValue]);
+
+impl<'a> fmt::Display for Render<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        deferred::fmt_template(f, self.0, self.1.iter().map(DecodedValue::to_arg))
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deferred::DeferredArgs;
+
+    fn log(logger: &BinaryLogger<Vec<u8>>, level: Level, args: fmt::Arguments) {
+        logger.log(
+            &Record::builder()
+                .args(args)
+                .level(level)
+                .target("binary")
+                .module_path_static(Some("binary::tests"))
+                .file_static(Some("binary.rs"))
+                .line(Some(7))
+                .build(),
+        );
+    }
+
+    fn decode(bytes: &[u8]) -> Vec<DecodedRecord> {
+        Decoder::new(bytes).collect::<io::Result<_>>().unwrap()
+    }
+
+    #[test]
+    fn varint_roundtrip() {
+        for &v in &[0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+            let mut buf = vec![FRAME_RECORD];
+            write_varint(&mut buf, v);
+
+            let mut decoder = Decoder::new(&buf[1..]);
+            assert_eq!(v, decoder.read_varint().unwrap());
+        }
+    }
+
+    #[test]
+    fn value_roundtrip() {
+        let args = [
+            Arg::U64(u64::MAX),
+            Arg::I64(i64::MIN),
+            Arg::I64(-1),
+            Arg::U128(u128::MAX),
+            Arg::I128(i128::MIN),
+            Arg::F32(1.5),
+            Arg::F64(-0.25),
+            Arg::Bool(true),
+            Arg::Char('⛰'),
+            Arg::Str("a string"),
+        ];
+
+        let mut buf = Vec::new();
+        for &arg in &args {
+            write_arg(&mut buf, arg);
+        }
+
+        let mut decoder = Decoder::new(&buf[..]);
+        for &arg in &args {
+            assert_eq!(arg, decoder.read_value().unwrap().to_arg());
+        }
+    }
+
+    #[test]
+    fn formatted_records() {
+        let logger = BinaryLogger::new(Vec::new(), LevelFilter::Info);
+
+        log(&logger, Level::Info, format_args!("first {}", 1));
+        log(&logger, Level::Debug, format_args!("filtered"));
+        log(&logger, Level::Error, format_args!("second"));
+
+        let bytes = logger.into_inner();
+        assert!(bytes.starts_with(MAGIC));
+
+        let records = decode(&bytes);
+
+        assert_eq!(2, records.len());
+        assert_eq!(
+            DecodedRecord {
+                level: Level::Info,
+                target: "binary".into(),
+                module_path: Some("binary::tests".into()),
+                file: Some("binary.rs".into()),
+                line: Some(7),
+                template: None,
+                args: "first 1".into(),
+                key_values: Vec::new(),
+            },
+            records[0]
+        );
+        assert_eq!(Level::Error, records[1].level());
+        assert_eq!("second", records[1].args());
+    }
+
+    #[test]
+    fn strings_are_interned() {
+        let logger = BinaryLogger::new(Vec::new(), LevelFilter::Trace);
+
+        log(&logger, Level::Info, format_args!("a"));
+        let first = logger.state.lock().unwrap().writer.len();
+
+        log(&logger, Level::Info, format_args!("a"));
+        let second = logger.state.lock().unwrap().writer.len() - first;
+
+        // The header and string definitions are only written for the first record
+        let bytes = logger.into_inner();
+        assert!(second < first);
+        assert!(!bytes[first..].windows(9).any(|w| w == b"binary.rs"));
+
+        assert_eq!(2, decode(&bytes).len());
+    }
+
+    #[test]
+    fn deferred_records() {
+        let logger = BinaryLogger::new(Vec::new(), LevelFilter::Trace);
+
+        let mut args = DeferredArgs::new("{} is {:?}");
+        args.push("answer").push(&42u8);
+
+        logger.log(
+            &Record::builder()
+                .args(format_args!("{}", args))
+                .deferred_args(Some(&args))
+                .level(Level::Warn)
+                .build(),
+        );
+
+        let records = decode(&logger.into_inner());
+
+        assert_eq!(Some("{} is {:?}"), records[0].template());
+        assert_eq!("answer is 42", records[0].args());
+        assert_eq!(None, records[0].module_path());
+        assert_eq!(None, records[0].line());
+    }
+
+    #[test]
+    fn invalid_streams() {
+        assert!(decode(&[]).is_empty());
+
+        let err = Decoder::new(&b"not a log"[..]).next().unwrap().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+
+        let logger = BinaryLogger::new(Vec::new(), LevelFilter::Trace);
+        log(&logger, Level::Info, format_args!("truncated"));
+
+        let bytes = logger.into_inner();
+        let err = Decoder::new(&bytes[..bytes.len() - 3])
+            .next()
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+    }
+
+    // A stream with a record of each kind, and non-ASCII strings
+    fn sample() -> Vec<u8> {
+        let logger = BinaryLogger::new(Vec::new(), LevelFilter::Trace);
+
+        log(&logger, Level::Info, format_args!("héllo {}", "wörld"));
+
+        let mut args = DeferredArgs::new("ü{}⛰{:?}é");
+        args.push(&1u8).push("ß");
+
+        logger.log(
+            &Record::builder()
+                .args(format_args!("{}", args))
+                .deferred_args(Some(&args))
+                .level(Level::Warn)
+                .target("zielé")
+                .build(),
+        );
+
+        logger.into_inner()
+    }
+
+    #[test]
+    fn non_ascii() {
+        let records = decode(&sample());
+
+        assert_eq!("héllo wörld", records[0].args());
+        assert_eq!("zielé", records[1].target());
+        assert_eq!(Some("ü{}⛰{:?}é"), records[1].template());
+        assert_eq!("ü1⛰\"ß\"é", records[1].args());
+    }
+
+    #[test]
+    fn truncated_streams() {
+        let bytes = sample();
+
+        for len in 0..bytes.len() {
+            let decoded = Decoder::new(&bytes[..len]).collect::<Vec<_>>();
+
+            assert!(decoded.len() <= 2);
+            for result in &decoded[..decoded.len().saturating_sub(1)] {
+                assert!(result.is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn corrupted_streams() {
+        let bytes = sample();
+
+        // Decoding any corruption must end in an error or a record, never a panic
+        for i in 0..bytes.len() {
+            for &b in &[0x00, 0x01, 0x02, 0x7f, 0x80, 0xc3, 0xff, bytes[i] ^ 0x20] {
+                let mut corrupted = bytes.clone();
+                corrupted[i] = b;
+
+                for result in Decoder::new(&corrupted[..]) {
+                    if result.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_frames() {
+        fn frame(bytes: &[u8]) -> io::Error {
+            let mut stream = MAGIC.to_vec();
+            stream.push(VERSION);
+            stream.extend_from_slice(bytes);
+
+            Decoder::new(&stream[..]).next().unwrap().unwrap_err()
+        }
+
+        // A template with unsupported braces
+        let err = frame(&[0, 0, 3, b'{', b'x', b'}', 1, 3, 0, 0, 0, 0, 1, 0, 0, 0]);
+        assert_eq!("invalid template", err.to_string());
+
+        // A string that isn't UTF-8
+        let err = frame(&[0, 0, 2, 0xc3, 0x28]);
+        assert_eq!("invalid UTF-8", err.to_string());
+
+        // A string id that's forgotten by a reset
+        let err = frame(&[0, 0, 1, b'a', 2, 1, 3, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!("undefined string id", err.to_string());
+
+        // A char that isn't a Unicode scalar value
+        let err = frame(&[0, 0, 0, 1, 3, 0, 0, 0, 0, 1, 0, 1, 7, 0x80, 0xb0, 0x03]);
+        assert_eq!("invalid char", err.to_string());
+
+        let err = frame(&[3]);
+        assert_eq!("unknown frame", err.to_string());
+    }
+
+    #[test]
+    fn strings_are_reset() {
+        let logger = BinaryLogger::new(Vec::new(), LevelFilter::Trace);
+
+        for i in 0..MAX_STRINGS * 2 {
+            logger.log(
+                &Record::builder()
+                    .args(format_args!("{}", i))
+                    .target(&i.to_string())
+                    .build(),
+            );
+
+            assert!(logger.state.lock().unwrap().interner.ids.len() <= MAX_STRINGS);
+        }
+
+        let records = decode(&logger.into_inner());
+
+        assert_eq!(MAX_STRINGS * 2, records.len());
+        for (i, record) in records.iter().enumerate() {
+            assert_eq!(i.to_string(), record.target());
+            assert_eq!(i.to_string(), record.args());
+        }
+    }
+
+    #[cfg(feature = "kv")]
+    #[test]
+    fn key_values() {
+        let logger = BinaryLogger::new(Vec::new(), LevelFilter::Trace);
+
+        let kvs: &[(&str, &dyn crate::kv::ToValue)] = &[
+            ("a", &1u8),
+            ("b", &-1i32),
+            ("c", &"a string"),
+            ("d", &u128::MAX),
+            ("e", &true),
+            ("f", &None::<u8>),
+        ];
+
+        logger.log(&Record::builder().key_values(&kvs).build());
+
+        let records = decode(&logger.into_inner());
+
+        assert_eq!(
+            vec![
+                ("a".to_owned(), DecodedValue::U64(1)),
+                ("b".to_owned(), DecodedValue::I64(-1)),
+                ("c".to_owned(), DecodedValue::Str("a string".into())),
+                ("d".to_owned(), DecodedValue::U128(u128::MAX)),
+                ("e".to_owned(), DecodedValue::Bool(true)),
+                ("f".to_owned(), DecodedValue::Str("None".into())),
+            ],
+            records[0].key_values()
+        );
+    }
+
+    #[cfg(feature = "kv")]
+    #[test]
+    fn key_values_that_fail() {
+        use crate::kv::{ToValue, Value};
+
+        struct Fail;
+
+        impl fmt::Display for Fail {
+            fn fmt(&self, _: &mut fmt::Formatter) -> fmt::Result {
+                Err(fmt::Error)
+            }
+        }
+
+        impl ToValue for Fail {
+            fn to_value(&self) -> Value<'_> {
+                Value::from_display(self)
+            }
+        }
+
+        let logger = BinaryLogger::new(Vec::new(), LevelFilter::Trace);
+
+        let kvs: &[(&str, &dyn ToValue)] = &[("a", &1u8), ("b", &Fail), ("c", &2u8)];
+
+        logger.log(&Record::builder().key_values(&kvs).build());
+        log(&logger, Level::Info, format_args!("after"));
+
+        let records = decode(&logger.into_inner());
+
+        assert_eq!(2, records.len());
+        assert_eq!(
+            vec![
+                ("a".to_owned(), DecodedValue::U64(1)),
+                ("c".to_owned(), DecodedValue::U64(2)),
+            ],
+            records[0].key_values()
+        );
+        assert_eq!("after", records[1].args());
+    }
+}
@@ -0,0 +1,1026 @@
+//! Deferred formatting of log records.
+//!
+//! Formatting a record's message happens on the thread that logs it, which can
+//! be the most expensive part of logging. The [`log_deferred!`] macro instead
+//! captures its format string and a compact encoding of its arguments in
+//! [`DeferredArgs`], and attaches them to the [`Record`] it logs. A logger can
+//! cheaply copy those into a [`DeferredRecord`], send it to another thread, and
+//! format the message there.
+//!
+//! Loggers that don't know about deferred arguments don't need to change:
+//! [`Record::args`] renders the deferred arguments when it's formatted.
+//!
+//! Requires the `deferred` feature.
+//!
+//! # Format strings
+//!
+//! Deferred format strings support `{}` and `{:?}` placeholders, and `{{` and
+//! `}}` escapes. Each argument must implement [`DeferredArg`], which is
+//! implemented for primitive numbers, `bool`, `char`, and strings.
+//!
+//! # Examples
+//!
+//! Logging with deferred arguments:
+//!
+//! ```edition2018
+//! use log::{log_deferred, Level};
+//!
+//! # fn main() {
+//! let (port, host) = (8080, "localhost");
+//!
+//! log_deferred!(Level::Info, "listening on {}:{}", host, port);
+//! log_deferred!(target: "net", Level::Debug, "accepted {:?}", "peer");
+//! # }
+//! ```
+//!
+//! Formatting records on a background thread:
+//!
+//! ```edition2018
+//! use std::sync::{mpsc, Mutex};
+//!
+//! use log::deferred::DeferredRecord;
+//! use log::{Log, Metadata, Record};
+//!
+//! struct BackgroundLogger(Mutex<mpsc::Sender<DeferredRecord>>);
+//!
+//! impl Log for BackgroundLogger {
+//!     fn enabled(&self, _: &Metadata) -> bool {
+//!         true
+//!     }
+//!
+//!     fn log(&self, record: &Record) {
+//!         let record = DeferredRecord::from_record(record);
+//!         let _ = self.0.lock().unwrap().send(record);
+//!     }
+//!
+//!     fn flush(&self) {}
+//! }
+//!
+//! # fn main() {
+//! let (tx, rx) = mpsc::channel::<DeferredRecord>();
+//!
+//! std::thread::spawn(move || {
+//!     for record in rx {
+//!         println!("{} {}", record.level(), record.args());
+//!     }
+//! });
+//! # let _ = BackgroundLogger(Mutex::new(tx));
+//! # }
+//! ```
+//!
+//! [`log_deferred!`]: ../macro.log_deferred.html
+//! [`DeferredArgs`]: struct.DeferredArgs.html
+//! [`DeferredArg`]: trait.DeferredArg.html
+//! [`DeferredRecord`]: struct.DeferredRecord.html
+//! [`Record`]: ../struct.Record.html
+//! [`Record::args`]: ../struct.Record.html#method.args
+
+use std::borrow::Cow;
+use std::fmt;
+use std::mem;
+
+use crate::{Level, Log, Record};
+
+/// Log a message with deferred formatting.
+///
+/// This macro accepts the same target and level arguments as [`log!`], but
+/// captures its arguments as [`DeferredArgs`] instead of formatting them.
+/// See the [`deferred`] module for the supported format strings.
+///
+/// Requires the `deferred` feature.
+///
+/// # Examples
+///
+/// ```edition2018
+/// use log::{log_deferred, Level};
+///
+/// # fn main() {
+/// let (attempt, max) = (2, 5);
+///
+/// log_deferred!(Level::Warn, "retrying ({} of {})", attempt, max);
+/// log_deferred!(target: "app_events", Level::Warn, "retrying ({} of {})", attempt, max);
+/// # }
+/// ```
+///
+/// [`log!`]: macro.log.html
+/// [`DeferredArgs`]: deferred/struct.DeferredArgs.html
+/// [`deferred`]: deferred/index.html
+#[macro_export]
+macro_rules! log_deferred {
+    // log_deferred!(target: "my_target", Level::Info, "a {} event", "log");
+    (target: $target:expr, $lvl:expr, $fmt:literal $(, $arg:expr)* $(,)?) => ({
+        const _: () = ::std::assert!(
+            $crate::__private_api::validate_deferred_template($fmt),
+            "unsupported placeholder in deferred format string"
+        );
+
+        // Check the arguments against the format string without evaluating them
+        if false {
+            let _ = $crate::__private_api::format_args!($fmt $(, $arg)*);
+        }
+
+        let lvl = $lvl;
+        if lvl <= $crate::STATIC_MAX_LEVEL && lvl <= $crate::max_level() {
+            let mut args = $crate::deferred::DeferredArgs::new($fmt);
+            $(
+                args.push(&$arg);
+            )*
+
+            $crate::__private_api::log_deferred(
+                &args,
+                lvl,
+                &($target, $crate::__private_api::module_path!(), $crate::__private_api::file!()),
+                $crate::__private_api::line!(),
+            );
+        }
+    });
+
+    // log_deferred!(Level::Info, "a {} event", "log");
+    ($lvl:expr, $fmt:literal $(, $arg:expr)* $(,)?) => (
+        $crate::log_deferred!(target: $crate::__private_api::module_path!(), $lvl, $fmt $(, $arg)*)
+    );
+}
+
+/// A single deferred argument.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Arg<'a> {
+    /// An unsigned integer.
+    U64(u64),
+    /// A signed integer.
+    I64(i64),
+    /// A big unsigned integer.
+    U128(u128),
+    /// A big signed integer.
+    I128(i128),
+    /// A single-precision floating point.
+    F32(f32),
+    /// A double-precision floating point.
+    F64(f64),
+    /// A boolean.
+    Bool(bool),
+    /// A Unicode character.
+    Char(char),
+    /// A string.
+    Str(&'a str),
+}
+
+impl<'a> Arg<'a> {
+    fn fmt_display(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Arg::U64(v) => fmt::Display::fmt(&v, f),
+            Arg::I64(v) => fmt::Display::fmt(&v, f),
+            Arg::U128(v) => fmt::Display::fmt(&v, f),
+            Arg::I128(v) => fmt::Display::fmt(&v, f),
+            Arg::F32(v) => fmt::Display::fmt(&v, f),
+            Arg::F64(v) => fmt::Display::fmt(&v, f),
+            Arg::Bool(v) => fmt::Display::fmt(&v, f),
+            Arg::Char(v) => fmt::Display::fmt(&v, f),
+            Arg::Str(v) => fmt::Display::fmt(v, f),
+        }
+    }
+
+    fn fmt_debug(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Arg::U64(v) => fmt::Debug::fmt(&v, f),
+            Arg::I64(v) => fmt::Debug::fmt(&v, f),
+            Arg::U128(v) => fmt::Debug::fmt(&v, f),
+            Arg::I128(v) => fmt::Debug::fmt(&v, f),
+            Arg::F32(v) => fmt::Debug::fmt(&v, f),
+            Arg::F64(v) => fmt::Debug::fmt(&v, f),
+            Arg::Bool(v) => fmt::Debug::fmt(&v, f),
+            Arg::Char(v) => fmt::Debug::fmt(&v, f),
+            Arg::Str(v) => fmt::Debug::fmt(v, f),
+        }
+    }
+}
+
+impl<'a> fmt::Display for Arg<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_display(f)
+    }
+}
+
+/// A type that can be captured as a deferred argument.
+pub trait DeferredArg {
+    /// Perform the conversion.
+    fn to_arg(&self) -> Arg<'_>;
+}
+
+impl<T> DeferredArg for &T
+where
+    T: DeferredArg + ?Sized,
+{
+    fn to_arg(&self) -> Arg<'_> {
+        (**self).to_arg()
+    }
+}
+
+impl<'a> DeferredArg for Arg<'a> {
+    fn to_arg(&self) -> Arg<'_> {
+        *self
+    }
+}
+
+macro_rules! impl_deferred_arg {
+    ($($ty:ty => $variant:ident,)*) => {
+        $(
+            impl DeferredArg for $ty {
+                fn to_arg(&self) -> Arg<'_> {
+                    Arg::$variant((*self).into())
+                }
+            }
+        )*
+    };
+}
+
+impl_deferred_arg![
+    u8 => U64,
+    u16 => U64,
+    u32 => U64,
+    u64 => U64,
+    u128 => U128,
+    i8 => I64,
+    i16 => I64,
+    i32 => I64,
+    i64 => I64,
+    i128 => I128,
+    f32 => F32,
+    f64 => F64,
+    bool => Bool,
+    char => Char,
+];
+
+impl DeferredArg for usize {
+    fn to_arg(&self) -> Arg<'_> {
+        Arg::U64(*self as u64)
+    }
+}
+
+impl DeferredArg for isize {
+    fn to_arg(&self) -> Arg<'_> {
+        Arg::I64(*self as i64)
+    }
+}
+
+impl DeferredArg for str {
+    fn to_arg(&self) -> Arg<'_> {
+        Arg::Str(self)
+    }
+}
+
+impl DeferredArg for String {
+    fn to_arg(&self) -> Arg<'_> {
+        Arg::Str(self)
+    }
+}
+
+impl<'a> DeferredArg for Cow<'a, str> {
+    fn to_arg(&self) -> Arg<'_> {
+        Arg::Str(self)
+    }
+}
+
+/// A format string along with its encoded arguments.
+///
+/// The arguments are stored in a compact binary buffer, so cloning or sending
+/// deferred arguments to another thread is a single copy. Formatting the
+/// deferred arguments with `fmt::Display` renders the message.
+#[derive(Clone)]
+pub struct DeferredArgs {
+    template: &'static str,
+    args: Encoded,
+}
+
+impl DeferredArgs {
+    /// Create deferred arguments for a format string.
+    ///
+    /// See the [module documentation](index.html) for the supported format strings.
+    pub fn new(template: &'static str) -> Self {
+        DeferredArgs {
+            template,
+            args: Encoded::default(),
+        }
+    }
+
+    /// Capture the next argument.
+    pub fn push<T>(&mut self, arg: &T) -> &mut Self
+    where
+        T: DeferredArg + ?Sized,
+    {
+        self.args.push(arg.to_arg());
+        self
+    }
+
+    /// The format string.
+    pub fn template(&self) -> &'static str {
+        self.template
+    }
+
+    /// Iterate over the captured arguments.
+    pub fn args(&self) -> Args<'_> {
+        Args(self.args.iter())
+    }
+
+    /// Iterate over the format string, split into literal text and placeholders.
+    pub fn pieces(&self) -> Pieces<'_> {
+        Pieces(self.template)
+    }
+}
+
+impl fmt::Display for DeferredArgs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_template(f, self.template, self.args())
+    }
+}
+
+// Render a format string with its arguments.
+//
+// This is also used to render templates that were decoded from somewhere
+// other than a `DeferredArgs`.
+pub(crate) fn fmt_template<'a>(
+    f: &mut fmt::Formatter,
+    template: &str,
+    mut args: impl Iterator<Item = Arg<'a>>,
+) -> fmt::Result {
+    for piece in Pieces(template) {
+        match piece {
+            Piece::Literal(lit) => f.write_str(lit)?,
+            Piece::Display => match args.next() {
+                Some(arg) => arg.fmt_display(f)?,
+                None => f.write_str("{}")?,
+            },
+            Piece::Debug => match args.next() {
+                Some(arg) => arg.fmt_debug(f)?,
+                None => f.write_str("{:?}")?,
+            },
+        }
+    }
+
+    Ok(())
+}
+
+impl fmt::Debug for DeferredArgs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DeferredArgs")
+            .field("template", &self.template)
+            .field("args", &self.args().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// An iterator over deferred arguments.
+#[derive(Debug)]
+pub struct Args<'a>(Iter<'a>);
+
+impl<'a> Iterator for Args<'a> {
+    type Item = Arg<'a>;
+
+    fn next(&mut self) -> Option<Arg<'a>> {
+        self.0.next()
+    }
+}
+
+/// A part of a deferred format string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Piece<'a> {
+    /// Literal text, with any `{{` or `}}` escapes resolved.
+    Literal(&'a str),
+    /// A `{}` placeholder.
+    Display,
+    /// A `{:?}` placeholder.
+    Debug,
+}
+
+/// An iterator over the pieces of a deferred format string.
+#[derive(Debug)]
+pub struct Pieces<'a>(&'a str);
+
+impl<'a> Iterator for Pieces<'a> {
+    type Item = Piece<'a>;
+
+    fn next(&mut self) -> Option<Piece<'a>> {
+        let s = self.0;
+        if s.is_empty() {
+            return None;
+        }
+
+        for (prefix, piece) in &[
+            ("{{", Piece::Literal("{")),
+            ("}}", Piece::Literal("}")),
+            ("{}", Piece::Display),
+            ("{:?}", Piece::Debug),
+        ] {
+            if let Some(rest) = s.strip_prefix(prefix) {
+                self.0 = rest;
+                return Some(*piece);
+            }
+        }
+
+        // Any other use of braces is rejected by `validate_template`,
+        // so treat them as literal text
+        let end = s
+            .char_indices()
+            .skip(1)
+            .find(|&(_, c)| c == '{' || c == '}')
+            .map_or(s.len(), |(i, _)| i);
+
+        self.0 = &s[end..];
+        Some(Piece::Literal(&s[..end]))
+    }
+}
+
+// Check a deferred format string at compile time.
+pub(crate) const fn validate_template(template: &str) -> bool {
+    let s = template.as_bytes();
+
+    let mut i = 0;
+    while i < s.len() {
+        if s[i] == b'{' {
+            if i + 1 < s.len() && (s[i + 1] == b'{' || s[i + 1] == b'}') {
+                i += 2;
+            } else if i + 3 < s.len() && s[i + 1] == b':' && s[i + 2] == b'?' && s[i + 3] == b'}' {
+                i += 4;
+            } else {
+                return false;
+            }
+        } else if s[i] == b'}' {
+            if i + 1 < s.len() && s[i + 1] == b'}' {
+                i += 2;
+            } else {
+                return false;
+            }
+        } else {
+            i += 1;
+        }
+    }
+
+    true
+}
+
+/// An owned record with deferred arguments.
+///
+/// A `DeferredRecord` owns all of its data, so it can be sent to another thread
+/// or kept around after the call to [`Log::log`] that produced it returns.
+///
+/// [`Log::log`]: ../trait.Log.html#tymethod.log
+#[derive(Clone, Debug)]
+pub struct DeferredRecord {
+    level: Level,
+    target: String,
+    module_path: Option<Cow<'static, str>>,
+    file: Option<Cow<'static, str>>,
+    line: Option<u32>,
+    args: DeferredArgs,
+    #[cfg(feature = "kv")]
+    key_values: kv_support::KeyValues,
+}
+
+impl DeferredRecord {
+    /// Capture a record.
+    ///
+    /// If the record carries [`DeferredArgs`] then they're copied. Otherwise, its
+    /// message is formatted now and captured as a string.
+    ///
+    /// When the `kv` feature is enabled the record's key-values are also
+    /// captured. Primitive values keep their type, and other values are formatted
+    /// as strings.
+    ///
+    /// [`DeferredArgs`]: struct.DeferredArgs.html
+    pub fn from_record(record: &Record) -> Self {
+        let args = match record.deferred_args() {
+            Some(args) => args.clone(),
+            None => {
+                let mut args = DeferredArgs::new("{}");
+                match record.args().as_str() {
+                    Some(msg) => args.push(msg),
+                    None => args.push(&record.args().to_string()),
+                };
+                args
+            }
+        };
+
+        DeferredRecord {
+            level: record.level(),
+            target: record.target().into(),
+            module_path: match (record.module_path_static(), record.module_path()) {
+                (Some(path), _) => Some(Cow::Borrowed(path)),
+                (None, path) => path.map(|path| Cow::Owned(path.into())),
+            },
+            file: match (record.file_static(), record.file()) {
+                (Some(file), _) => Some(Cow::Borrowed(file)),
+                (None, file) => file.map(|file| Cow::Owned(file.into())),
+            },
+            line: record.line(),
+            args,
+            #[cfg(feature = "kv")]
+            key_values: kv_support::KeyValues::from_source(record.key_values()),
+        }
+    }
+
+    /// The verbosity level of the message.
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// The name of the target of the directive.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// The module path of the message.
+    pub fn module_path(&self) -> Option<&str> {
+        self.module_path.as_deref()
+    }
+
+    /// The source file containing the message.
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    /// The line containing the message.
+    pub fn line(&self) -> Option<u32> {
+        self.line
+    }
+
+    /// The deferred message.
+    pub fn args(&self) -> &DeferredArgs {
+        &self.args
+    }
+
+    /// The structured key-value pairs associated with the message.
+    #[cfg(feature = "kv")]
+    pub fn key_values(&self) -> &dyn crate::kv::Source {
+        &self.key_values
+    }
+
+    /// Log this record to a logger.
+    ///
+    /// The logger receives a [`Record`] with these deferred arguments attached, so
+    /// it can be forwarded to loggers that aren't aware of deferred formatting.
+    ///
+    /// [`Record`]: ../struct.Record.html
+    pub fn log(&self, logger: &dyn Log) {
+        self.log_impl(logger, format_args!("{}", self.args));
+    }
+
+    fn log_impl(&self, logger: &dyn Log, args: fmt::Arguments) {
+        let mut builder = Record::builder();
+
+        builder
+            .args(args)
+            .deferred_args(Some(&self.args))
+            .level(self.level)
+            .target(&self.target)
+            .line(self.line);
+
+        match self.module_path {
+            Some(Cow::Borrowed(path)) => builder.module_path_static(Some(path)),
+            ref path => builder.module_path(path.as_deref()),
+        };
+
+        match self.file {
+            Some(Cow::Borrowed(file)) => builder.file_static(Some(file)),
+            ref file => builder.file(file.as_deref()),
+        };
+
+        #[cfg(feature = "kv")]
+        builder.key_values(&self.key_values);
+
+        logger.log(&builder.build());
+    }
+}
+
+// Arguments are encoded as a tag byte followed by their native-endian bytes.
+// Strings are prefixed by their length.
+//
+// The buffer is never shared outside the process that encoded it.
+#[derive(Clone, Default)]
+struct Encoded {
+    buf: Vec<u8>,
+}
+
+const TAG_U64: u8 = 0;
+const TAG_I64: u8 = 1;
+const TAG_U128: u8 = 2;
+const TAG_I128: u8 = 3;
+const TAG_F32: u8 = 4;
+const TAG_F64: u8 = 5;
+const TAG_BOOL: u8 = 6;
+const TAG_CHAR: u8 = 7;
+const TAG_STR: u8 = 8;
+
+impl Encoded {
+    fn push(&mut self, arg: Arg) {
+        match arg {
+            Arg::U64(v) => self.push_tagged(TAG_U64, &v.to_ne_bytes()),
+            Arg::I64(v) => self.push_tagged(TAG_I64, &v.to_ne_bytes()),
+            Arg::U128(v) => self.push_tagged(TAG_U128, &v.to_ne_bytes()),
+            Arg::I128(v) => self.push_tagged(TAG_I128, &v.to_ne_bytes()),
+            Arg::F32(v) => self.push_tagged(TAG_F32, &v.to_ne_bytes()),
+            Arg::F64(v) => self.push_tagged(TAG_F64, &v.to_ne_bytes()),
+            Arg::Bool(v) => self.push_tagged(TAG_BOOL, &[v as u8]),
+            Arg::Char(v) => self.push_tagged(TAG_CHAR, &(v as u32).to_ne_bytes()),
+            Arg::Str(v) => {
+                self.push_tagged(TAG_STR, &v.len().to_ne_bytes());
+                self.buf.extend_from_slice(v.as_bytes());
+            }
+        }
+    }
+
+    fn push_tagged(&mut self, tag: u8, bytes: &[u8]) {
+        self.buf.push(tag);
+        self.buf.extend_from_slice(bytes);
+    }
+
+    fn iter(&self) -> Iter<'_> {
+        Iter(&self.buf)
+    }
+}
+
+#[derive(Debug)]
+struct Iter<'a>(&'a [u8]);
+
+impl<'a> Iter<'a> {
+    fn take<const N: usize>(&mut self) -> [u8; N] {
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(&self.0[..N]);
+        self.0 = &self.0[N..];
+        bytes
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = Arg<'a>;
+
+    fn next(&mut self) -> Option<Arg<'a>> {
+        let (&tag, rest) = self.0.split_first()?;
+        self.0 = rest;
+
+        Some(match tag {
+            TAG_U64 => Arg::U64(u64::from_ne_bytes(self.take())),
+            TAG_I64 => Arg::I64(i64::from_ne_bytes(self.take())),
+            TAG_U128 => Arg::U128(u128::from_ne_bytes(self.take())),
+            TAG_I128 => Arg::I128(i128::from_ne_bytes(self.take())),
+            TAG_F32 => Arg::F32(f32::from_ne_bytes(self.take())),
+            TAG_F64 => Arg::F64(f64::from_ne_bytes(self.take())),
+            TAG_BOOL => Arg::Bool(self.take::<1>()[0] != 0),
+            TAG_CHAR => Arg::Char(
+                char::from_u32(u32::from_ne_bytes(self.take()))
+                    .unwrap_or(char::REPLACEMENT_CHARACTER),
+            ),
+            TAG_STR => {
+                let len = usize::from_ne_bytes(self.take());
+                let (s, rest) = self.0.split_at(len);
+                self.0 = rest;
+
+                // SAFETY: The bytes were copied from a `str` by `Encoded::push`
+                Arg::Str(unsafe { std::str::from_utf8_unchecked(s) })
+            }
+            _ => unreachable!("invalid deferred argument tag {}", tag),
+        })
+    }
+}
+
+#[cfg(feature = "kv")]
+mod kv_support {
+    use super::*;
+
+    use crate::kv::value::Visit;
+    use crate::kv::{Error, Key, Source, Value, Visitor};
+
+    // Key-values are encoded as pairs of a string key followed by its value.
+    #[derive(Clone, Default)]
+    pub(super) struct KeyValues {
+        encoded: Encoded,
+        count: usize,
+    }
+
+    impl KeyValues {
+        pub(super) fn from_source(source: &dyn Source) -> Self {
+            struct Encode<'a>(&'a mut KeyValues);
+
+            impl<'a, 'kvs> Visitor<'kvs> for Encode<'a> {
+                fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                    self.0.encoded.push(Arg::Str(key.as_str()));
+                    value.visit(EncodeValue(&mut self.0.encoded))?;
+                    self.0.count += 1;
+
+                    Ok(())
+                }
+            }
+
+            struct EncodeValue<'a>(&'a mut Encoded);
+
+            impl<'a, 'v> Visit<'v> for EncodeValue<'a> {
+                fn visit_any(&mut self, value: Value) -> Result<(), Error> {
+                    self.0.push(Arg::Str(&value.to_string()));
+                    Ok(())
+                }
+
+                fn visit_u64(&mut self, value: u64) -> Result<(), Error> {
+                    self.0.push(Arg::U64(value));
+                    Ok(())
+                }
+
+                fn visit_i64(&mut self, value: i64) -> Result<(), Error> {
+                    self.0.push(Arg::I64(value));
+                    Ok(())
+                }
+
+                fn visit_f64(&mut self, value: f64) -> Result<(), Error> {
+                    self.0.push(Arg::F64(value));
+                    Ok(())
+                }
+
+                fn visit_bool(&mut self, value: bool) -> Result<(), Error> {
+                    self.0.push(Arg::Bool(value));
+                    Ok(())
+                }
+
+                fn visit_char(&mut self, value: char) -> Result<(), Error> {
+                    self.0.push(Arg::Char(value));
+                    Ok(())
+                }
+
+                fn visit_str(&mut self, value: &str) -> Result<(), Error> {
+                    self.0.push(Arg::Str(value));
+                    Ok(())
+                }
+            }
+
+            let mut kvs = KeyValues::default();
+            let _ = source.visit(&mut Encode(&mut kvs));
+            kvs
+        }
+    }
+
+    impl Source for KeyValues {
+        fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+            let mut iter = self.encoded.iter();
+
+            while let (Some(Arg::Str(key)), Some(value)) = (iter.next(), iter.next()) {
+                let value = match value {
+                    Arg::U64(v) => Value::from(v),
+                    Arg::I64(v) => Value::from(v),
+                    Arg::F32(v) => Value::from(v),
+                    Arg::F64(v) => Value::from(v),
+                    Arg::Bool(v) => Value::from(v),
+                    Arg::Char(v) => Value::from(v),
+                    Arg::Str(v) => Value::from(v),
+                    // Big integers are visited through `visit_any` and encoded as strings
+                    Arg::U128(_) | Arg::I128(_) => unreachable!(),
+                };
+
+                visitor.visit_pair(Key::from_str(key), value)?;
+            }
+
+            Ok(())
+        }
+
+        fn count(&self) -> usize {
+            self.count
+        }
+    }
+
+    impl fmt::Debug for KeyValues {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let mut visitor = f.debug_map();
+            self.visit(&mut visitor).map_err(|_| fmt::Error)?;
+            visitor.finish()
+        }
+    }
+}
+
+// The encoding is an implementation detail, so only show how much space it uses.
+impl fmt::Debug for Encoded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Encoded")
+            .field("len", &self.buf.len())
+            .finish()
+    }
+}
+
+const _: () = assert!(mem::size_of::<char>() == mem::size_of::<u32>());
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Metadata;
+    use std::sync::Mutex;
+
+    fn render(template: &'static str, args: &[&dyn DeferredArg]) -> String {
+        let mut deferred = DeferredArgs::new(template);
+        for arg in args {
+            deferred.push(arg);
+        }
+
+        deferred.to_string()
+    }
+
+    #[test]
+    fn render_placeholders() {
+        assert_eq!("plain text", render("plain text", &[]));
+        assert_eq!("a 1 b -2 c", render("a {} b {} c", &[&1u8, &-2i32]));
+        assert_eq!(
+            "\"quoted\" and unquoted",
+            render("{:?} and {}", &[&"quoted", &String::from("unquoted")])
+        );
+        assert_eq!("{escaped} 1.5", render("{{escaped}} {}", &[&1.5f32]));
+        assert_eq!("0.1 true x", render("{} {} {}", &[&0.1f64, &true, &'x']));
+        assert_eq!(
+            "340282366920938463463374607431768211455",
+            render("{}", &[&u128::MAX])
+        );
+    }
+
+    #[test]
+    fn render_missing_args() {
+        assert_eq!("a {} {:?}", render("a {} {:?}", &[]));
+    }
+
+    #[test]
+    fn args_roundtrip() {
+        let mut deferred = DeferredArgs::new("");
+        deferred
+            .push(&1u64)
+            .push(&-1i64)
+            .push(&i128::MIN)
+            .push(&2.5f64)
+            .push(&false)
+            .push(&'⛰')
+            .push("a string");
+
+        assert_eq!(
+            vec![
+                Arg::U64(1),
+                Arg::I64(-1),
+                Arg::I128(i128::MIN),
+                Arg::F64(2.5),
+                Arg::Bool(false),
+                Arg::Char('⛰'),
+                Arg::Str("a string"),
+            ],
+            deferred.args().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn pieces() {
+        let deferred = DeferredArgs::new("a {{}} {} b {:?}");
+
+        assert_eq!(
+            vec![
+                Piece::Literal("a "),
+                Piece::Literal("{"),
+                Piece::Literal("}"),
+                Piece::Literal(" "),
+                Piece::Display,
+                Piece::Literal(" b "),
+                Piece::Debug,
+            ],
+            deferred.pieces().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn pieces_non_ascii() {
+        assert_eq!("é", DeferredArgs::new("é").to_string());
+        assert_eq!("é 1 ⛰ 2 ü", render("é {} ⛰ {} ü", &[&1u8, &2u8]));
+        assert_eq!("{ü} 1", render("{{ü}} {}", &[&1u8]));
+
+        let deferred = DeferredArgs::new("ü{}⛰{:?}é");
+
+        assert_eq!(
+            vec![
+                Piece::Literal("ü"),
+                Piece::Display,
+                Piece::Literal("⛰"),
+                Piece::Debug,
+                Piece::Literal("é"),
+            ],
+            deferred.pieces().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn validate() {
+        assert!(validate_template(""));
+        assert!(validate_template("a {} {:?} {{ }}"));
+
+        assert!(!validate_template("{name}"));
+        assert!(!validate_template("{:>5}"));
+        assert!(!validate_template("{0}"));
+        assert!(!validate_template("{"));
+        assert!(!validate_template("}"));
+    }
+
+    struct Capture(Mutex<Vec<(String, bool)>>);
+
+    impl Log for Capture {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((record.args().to_string(), record.deferred_args().is_some()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn deferred_record_from_deferred() {
+        let mut args = DeferredArgs::new("{} + {}");
+        args.push(&1).push(&2);
+
+        let record = DeferredRecord::from_record(
+            &Record::builder()
+                .args(format_args!("{}", args))
+                .deferred_args(Some(&args))
+                .level(Level::Warn)
+                .target("deferred")
+                .module_path_static(Some("module"))
+                .file(Some("file.rs"))
+                .line(Some(42))
+                .build(),
+        );
+
+        assert_eq!(Level::Warn, record.level());
+        assert_eq!("deferred", record.target());
+        assert_eq!(Some("module"), record.module_path());
+        assert_eq!(Some("file.rs"), record.file());
+        assert_eq!(Some(42), record.line());
+        assert_eq!("{} + {}", record.args().template());
+
+        // The record can be formatted on another thread
+        let message = std::thread::spawn(move || record.args().to_string())
+            .join()
+            .unwrap();
+
+        assert_eq!("1 + 2", message);
+    }
+
+    #[test]
+    fn deferred_record_log() {
+        let mut args = DeferredArgs::new("{} + {}");
+        args.push(&1).push(&2);
+
+        let record = DeferredRecord::from_record(
+            &Record::builder()
+                .args(format_args!("{}", args))
+                .deferred_args(Some(&args))
+                .build(),
+        );
+
+        let logger = Capture(Mutex::new(Vec::new()));
+        record.log(&logger);
+
+        assert_eq!(vec![("1 + 2".to_owned(), true)], *logger.0.lock().unwrap());
+    }
+
+    #[test]
+    fn deferred_record_from_args() {
+        let record = DeferredRecord::from_record(
+            &Record::builder().args(format_args!("{} {{}}", 1)).build(),
+        );
+
+        assert_eq!("{}", record.args().template());
+        assert_eq!("1 {}", record.args().to_string());
+
+        let record =
+            DeferredRecord::from_record(&Record::builder().args(format_args!("a {{}}")).build());
+
+        assert_eq!("a {}", record.args().to_string());
+    }
+
+    #[cfg(feature = "kv")]
+    #[test]
+    fn deferred_record_key_values() {
+        use crate::kv::Key;
+
+        let kvs: &[(&str, &dyn crate::kv::ToValue)] = &[
+            ("a", &1u8),
+            ("b", &-1i32),
+            ("c", &"a string"),
+            ("d", &u128::MAX),
+        ];
+
+        let record = DeferredRecord::from_record(&Record::builder().key_values(&kvs).build());
+        let kvs = record.key_values();
+
+        assert_eq!(4, kvs.count());
+        assert_eq!(Some(1), kvs.get(Key::from_str("a")).unwrap().to_u64());
+        assert_eq!(Some(-1), kvs.get(Key::from_str("b")).unwrap().to_i64());
+        assert_eq!(
+            Some("a string"),
+            kvs.get(Key::from_str("c")).unwrap().to_borrowed_str()
+        );
+        assert_eq!(
+            Some(u128::MAX.to_string().as_str()),
+            kvs.get(Key::from_str("d")).unwrap().to_borrowed_str()
+        );
+    }
+}
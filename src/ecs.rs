@@ -0,0 +1,229 @@
+//! Elastic Common Schema (ECS) documents.
+//!
+//! This module formats a [`Record`] as an [ECS] JSON document with [`Ecs`],
+//! ready to be shipped to Elasticsearch by a log collector like Filebeat.
+//!
+//! Records map to ECS fields as follows:
+//!
+//! | Record        | ECS field              |
+//! | ------------- | ---------------------- |
+//! | `level`       | `log.level`            |
+//! | `args`        | `message`              |
+//! | `target`      | `log.logger`           |
+//! | `file`        | `log.origin.file.name` |
+//! | `line`        | `log.origin.file.line` |
+//! | `key_values`  | top-level fields       |
+//!
+//! Records don't carry a timestamp, so the time a record is formatted is used
+//! as its `@timestamp`. Documents also carry the `ecs.version` they conform to.
+//!
+//! Requires the `ecs` feature.
+//!
+//! [`Record`]: ../struct.Record.html
+//! [ECS]: https://www.elastic.co/guide/en/ecs/current/index.html
+//! [`Ecs`]: struct.Ecs.html
+
+use std::fmt::{self, Write as _};
+use std::io;
+use std::time::SystemTime;
+
+use crate::format::Format;
+use crate::json;
+use crate::kv::time::Rfc3339;
+use crate::kv::{Error, Key, Value, Visitor};
+use crate::{Level, Record};
+
+/// The version of ECS that documents conform to.
+pub const ECS_VERSION: &str = "1.6.0";
+
+// Fields written for a record's metadata. Key-values with these
+// names are moved under `labels.` so they don't clash
+const RESERVED_FIELDS: &[&str] = &[
+    "@timestamp",
+    "log.level",
+    "message",
+    "ecs.version",
+    "log.logger",
+    "log.origin.file.name",
+    "log.origin.file.line",
+];
+
+/// A [`Format`] that renders records as ECS JSON documents.
+///
+/// Fields are written with dotted names, like `log.level`, rather than as
+/// nested objects. Elasticsearch treats both forms the same way.
+///
+/// Key-values are written as top-level fields, with their values as JSON.
+/// Key-values that would clash with the fields written for the record are
+/// written with a `labels.` prefix instead.
+///
+/// # Examples
+///
+/// ```edition2018
+/// use log::ecs::Ecs;
+/// use log::format::Format;
+/// use log::{Level, Record};
+///
+/// # fn main() -> std::io::Result<()> {
+/// let mut buf = Vec::new();
+/// Ecs::new().format(
+///     &Record::builder()
+///         .level(Level::Warn)
+///         .target("shop::checkout")
+///         .args(format_args!("payment declined"))
+///         .key_values(&[("user.id", "alice")])
+///         .build(),
+///     &mut buf,
+/// )?;
+///
+/// let ecs = String::from_utf8(buf).unwrap();
+/// assert!(ecs.ends_with(
+///     r#""log.level":"warn","message":"payment declined","ecs.version":"1.6.0","log.logger":"shop::checkout","user.id":"alice"}"#
+/// ));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Format`]: ../format/trait.Format.html
+#[derive(Clone, Debug, Default)]
+pub struct Ecs(());
+
+impl Ecs {
+    /// Create a formatter.
+    pub fn new() -> Self {
+        Ecs::default()
+    }
+
+    fn write(&self, out: &mut String, record: &Record) -> fmt::Result {
+        write!(
+            out,
+            r#"{{"@timestamp":"{}","log.level":"#,
+            Rfc3339(SystemTime::now())
+        )?;
+        json::write_str(out, level(record.level()))?;
+
+        out.write_str(r#","message":"#)?;
+        match record.args().as_str() {
+            Some(message) => json::write_str(out, message)?,
+            None => json::write_str(out, &record.args().to_string())?,
+        }
+
+        write!(out, r#","ecs.version":"{}","log.logger":"#, ECS_VERSION)?;
+        json::write_str(out, record.target())?;
+        if let Some(file) = record.file() {
+            out.write_str(r#","log.origin.file.name":"#)?;
+            json::write_str(out, file)?;
+        }
+        if let Some(line) = record.line() {
+            write!(out, r#","log.origin.file.line":{}"#, line)?;
+        }
+
+        record
+            .key_values()
+            .visit(&mut WriteFields(out))
+            .map_err(|_| fmt::Error)?;
+
+        out.write_char('}')
+    }
+}
+
+impl Format for Ecs {
+    fn format(&self, record: &Record, buf: &mut Vec<u8>) -> io::Result<()> {
+        let mut out = String::new();
+        self.write(&mut out, record)
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "failed to format a record"))?;
+
+        buf.extend_from_slice(out.as_bytes());
+        Ok(())
+    }
+}
+
+// ECS recommends lowercase level names
+fn level(level: Level) -> &'static str {
+    match level {
+        Level::Error => "error",
+        Level::Warn => "warn",
+        Level::Info => "info",
+        Level::Debug => "debug",
+        Level::Trace => "trace",
+    }
+}
+
+struct WriteFields<'a>(&'a mut String);
+
+impl<'a, 'kvs> Visitor<'kvs> for WriteFields<'a> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+        self.0.push(',');
+        if RESERVED_FIELDS.contains(&key.as_str()) {
+            json::write_str(self.0, &format!("labels.{}", key))?;
+        } else {
+            json::write_str(self.0, key.as_str())?;
+        }
+        self.0.push(':');
+        Ok(json::write_value(self.0, value)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kv::ToValue;
+
+    fn ecs(record: &Record) -> serde_json::Map<String, serde_json::Value> {
+        let mut buf = Vec::new();
+        Ecs::new().format(record, &mut buf).unwrap();
+
+        match serde_json::from_slice(&buf).unwrap() {
+            serde_json::Value::Object(fields) => fields,
+            ecs => panic!("expected an object, got {}", ecs),
+        }
+    }
+
+    #[test]
+    fn format() {
+        let kvs: &[(&str, &dyn ToValue)] = &[
+            ("user.id", &"alice"),
+            ("http.response.status_code", &503),
+            ("retry", &true),
+            ("missing", &None::<u8>),
+            ("message", &"shadowed"),
+        ];
+
+        let fields = ecs(&Record::builder()
+            .level(Level::Error)
+            .target("shop::checkout")
+            .module_path(Some("shop::checkout"))
+            .file(Some("src/checkout.rs"))
+            .line(Some(42))
+            .args(format_args!("{} retries", 3))
+            .key_values(&kvs)
+            .build());
+
+        let timestamp = fields["@timestamp"].as_str().unwrap();
+        assert!(timestamp.ends_with('Z'), "{}", timestamp);
+
+        assert_eq!("error", fields["log.level"]);
+        assert_eq!("3 retries", fields["message"]);
+        assert_eq!(ECS_VERSION, fields["ecs.version"]);
+        assert_eq!("shop::checkout", fields["log.logger"]);
+        assert_eq!("src/checkout.rs", fields["log.origin.file.name"]);
+        assert_eq!(42, fields["log.origin.file.line"]);
+        assert_eq!("alice", fields["user.id"]);
+        assert_eq!(503, fields["http.response.status_code"]);
+        assert_eq!(true, fields["retry"]);
+        assert!(fields["missing"].is_null());
+        assert_eq!("shadowed", fields["labels.message"]);
+        assert_eq!(12, fields.len());
+    }
+
+    #[test]
+    fn format_minimal() {
+        let fields = ecs(&Record::builder().args(format_args!("started")).build());
+
+        assert_eq!("info", fields["log.level"]);
+        assert_eq!("", fields["log.logger"]);
+        assert!(fields.get("log.origin.file.name").is_none());
+        assert!(fields.get("log.origin.file.line").is_none());
+        assert_eq!(5, fields.len());
+    }
+}
@@ -0,0 +1,223 @@
+//! Rendering records into bytes.
+//!
+//! Sinks that write records somewhere, like a socket, are generic over a
+//! [`Format`] that decides how each [`Record`] is rendered. A closure can be
+//! used as a `Format` with [`from_fn`], so custom layouts don't need a new type.
+//! [`Text`] is a plain text format for sinks that don't need a particular one.
+//!
+//! Requires the `std` feature.
+//!
+//! # Examples
+//!
+//! A closure that renders the level and message:
+//!
+//! ```edition2018
+//! use std::io::{self, Write};
+//!
+//! use log::format::{self, Format};
+//! use log::{Level, Record};
+//!
+//! # fn main() -> io::Result<()> {
+//! let format = format::from_fn(|record: &Record, buf: &mut Vec<u8>| {
+//!     write!(buf, "[{}] {}", record.level(), record.args())
+//! });
+//!
+//! let mut buf = Vec::new();
+//! format.format(
+//!     &Record::builder()
+//!         .level(Level::Info)
+//!         .args(format_args!("hello"))
+//!         .build(),
+//!     &mut buf,
+//! )?;
+//!
+//! assert_eq!(b"[INFO] hello", &*buf);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Format`]: trait.Format.html
+//! [`from_fn`]: fn.from_fn.html
+//! [`Text`]: struct.Text.html
+//! [`Record`]: ../struct.Record.html
+
+use std::fmt;
+use std::io::{self, Write as _};
+
+use crate::Record;
+
+/// A way to render a record into bytes.
+pub trait Format {
+    /// Render a record by appending it to `buf`.
+    ///
+    /// The rendered record shouldn't include any framing, like a trailing
+    /// newline. That's up to the sink writing it.
+    fn format(&self, record: &Record, buf: &mut Vec<u8>) -> io::Result<()>;
+}
+
+/// Use a closure as a [`Format`](trait.Format.html).
+pub fn from_fn<F>(f: F) -> FromFn<F>
+where
+    F: Fn(&Record, &mut Vec<u8>) -> io::Result<()>,
+{
+    FromFn(f)
+}
+
+/// A [`Format`](trait.Format.html) that calls a closure.
+///
+/// This type is returned by [`from_fn`](fn.from_fn.html).
+#[derive(Clone, Copy)]
+pub struct FromFn<F>(F);
+
+impl<F> Format for FromFn<F>
+where
+    F: Fn(&Record, &mut Vec<u8>) -> io::Result<()>,
+{
+    fn format(&self, record: &Record, buf: &mut Vec<u8>) -> io::Result<()> {
+        (self.0)(record, buf)
+    }
+}
+
+impl<F> fmt::Debug for FromFn<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FromFn").finish_non_exhaustive()
+    }
+}
+
+/// A [`Format`](trait.Format.html) that renders records as plain text.
+///
+/// Records are rendered as their level, target, and message, followed by
+/// any key-values as `key=value` pairs:
+///
+/// ```text
+/// WARN shop::checkout: payment declined attempt=3
+/// ```
+///
+/// Key-values are only rendered with the `kv` feature.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Text(());
+
+impl Text {
+    /// Create a plain text format.
+    pub fn new() -> Self {
+        Text::default()
+    }
+}
+
+impl Format for Text {
+    fn format(&self, record: &Record, buf: &mut Vec<u8>) -> io::Result<()> {
+        write!(
+            buf,
+            "{} {}: {}",
+            record.level(),
+            record.target(),
+            record.args()
+        )?;
+
+        #[cfg(feature = "kv")]
+        {
+            use crate::kv::{Error, Key, Value, Visitor};
+
+            struct WritePairs<'a>(&'a mut Vec<u8>);
+
+            impl<'a, 'kvs> Visitor<'kvs> for WritePairs<'a> {
+                fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                    Ok(write!(self.0, " {}={}", key, value)?)
+                }
+            }
+
+            record
+                .key_values()
+                .visit(&mut WritePairs(buf))
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<F> Format for &F
+where
+    F: Format + ?Sized,
+{
+    fn format(&self, record: &Record, buf: &mut Vec<u8>) -> io::Result<()> {
+        (**self).format(record, buf)
+    }
+}
+
+impl<F> Format for Box<F>
+where
+    F: Format + ?Sized,
+{
+    fn format(&self, record: &Record, buf: &mut Vec<u8>) -> io::Result<()> {
+        (**self).format(record, buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Level;
+
+    #[test]
+    fn text() {
+        let mut buf = Vec::new();
+        Text::new()
+            .format(
+                &Record::builder()
+                    .level(Level::Warn)
+                    .target("shop::checkout")
+                    .args(format_args!("payment declined"))
+                    .build(),
+                &mut buf,
+            )
+            .unwrap();
+
+        assert_eq!(
+            "WARN shop::checkout: payment declined",
+            String::from_utf8(buf).unwrap()
+        );
+    }
+
+    #[cfg(feature = "kv")]
+    #[test]
+    fn text_key_values() {
+        let mut buf = Vec::new();
+        Text::new()
+            .format(
+                &Record::builder()
+                    .level(Level::Info)
+                    .target("app")
+                    .args(format_args!("started"))
+                    .key_values(&[("port", 8080), ("workers", 4)])
+                    .build(),
+                &mut buf,
+            )
+            .unwrap();
+
+        assert_eq!(
+            "INFO app: started port=8080 workers=4",
+            String::from_utf8(buf).unwrap()
+        );
+    }
+
+    #[test]
+    fn from_fn() {
+        let format =
+            super::from_fn(|record: &Record, buf: &mut Vec<u8>| write!(buf, "{}", record.args()));
+
+        fn render(format: impl Format) -> Vec<u8> {
+            let mut buf = Vec::new();
+            format
+                .format(
+                    &Record::builder().args(format_args!("hello")).build(),
+                    &mut buf,
+                )
+                .unwrap();
+            buf
+        }
+
+        let boxed: Box<dyn Format> = Box::new(format);
+        assert_eq!(b"hello", &*render(boxed));
+    }
+}
@@ -0,0 +1,762 @@
+//! Graylog Extended Log Format (GELF) messages.
+//!
+//! This module formats a [`Record`] as a [GELF 1.1] message with [`Gelf`], and
+//! sends those messages to a Graylog input over UDP or TCP with [`GelfSink`].
+//!
+//! Records map to GELF fields as follows:
+//!
+//! | Record        | GELF field                                    |
+//! | ------------- | --------------------------------------------- |
+//! | `args`        | `short_message` (first line), `full_message`  |
+//! | `level`       | `level`, as a syslog severity                 |
+//! | `target`      | `_target`                                     |
+//! | `module_path` | `_module_path`                                |
+//! | `file`        | `_file`                                       |
+//! | `line`        | `_line`                                       |
+//! | `key_values`  | `_`-prefixed additional fields                |
+//!
+//! Levels map to syslog severities as follows:
+//!
+//! | Level   | Severity            |
+//! | ------- | ------------------- |
+//! | `Error` | 3 (error)           |
+//! | `Warn`  | 4 (warning)         |
+//! | `Info`  | 6 (informational)   |
+//! | `Debug` | 7 (debug)           |
+//! | `Trace` | 7 (debug)           |
+//!
+//! Records don't carry a timestamp, so the time a record is formatted is used
+//! as its `timestamp`.
+//!
+//! Requires the `gelf` feature.
+//!
+//! # Examples
+//!
+//! Sending records to a Graylog UDP input:
+//!
+//! ```edition2018,no_run
+//! use log::gelf::{Gelf, GelfSink};
+//!
+//! # fn main() -> std::io::Result<()> {
+//! let sink = GelfSink::udp("graylog.local:12201", Gelf::new("checkout-1"))?;
+//!
+//! log::set_boxed_logger(Box::new(sink)).unwrap();
+//! log::set_max_level(log::LevelFilter::Info);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Record`]: ../struct.Record.html
+//! [GELF 1.1]: https://go2docs.graylog.org/current/getting_in_log_data/gelf.html
+//! [`Gelf`]: struct.Gelf.html
+//! [`GelfSink`]: struct.GelfSink.html
+
+use std::fmt::{self, Write as _};
+use std::io::{self, Write as _};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::format::Format;
+use crate::json;
+use crate::kv::value::Visit;
+use crate::kv::{Error, Key, Value, Visitor};
+use crate::{Log, Metadata, Record};
+
+// Additional fields written for a record's metadata. Key-values with
+// these names, or the reserved `_id`, are renamed so they don't clash
+const RESERVED_FIELDS: &[&str] = &["_id", "_target", "_module_path", "_file", "_line"];
+
+// The largest datagram recommended by the GELF spec for UDP
+const DEFAULT_CHUNK_SIZE: usize = 8192;
+
+// Each chunk starts with 2 magic bytes, an 8 byte message id, and
+// a sequence number and count
+const CHUNK_HEADER_LEN: usize = 12;
+
+// Graylog discards messages with more chunks than this
+const MAX_CHUNKS: usize = 128;
+
+/// A [`Format`] that renders records as GELF 1.1 JSON messages.
+///
+/// Key-values become additional fields, with their keys prefixed by `_`.
+/// Characters in keys other than ASCII letters, digits, `_`, `.`, and `-`
+/// are replaced by `_`. Keys that would clash with the reserved `_id` field
+/// or the fields written for the record's metadata have a `_` appended.
+///
+/// GELF field values can only be strings or numbers. Integer and finite
+/// floating point key-values are written as numbers. Empty values, like
+/// `None`, are skipped. Any other value is written as the string produced
+/// by its `fmt::Display` implementation.
+///
+/// # Examples
+///
+/// ```edition2018
+/// use log::format::Format;
+/// use log::gelf::Gelf;
+/// use log::{Level, Record};
+///
+/// # fn main() -> std::io::Result<()> {
+/// let mut buf = Vec::new();
+/// Gelf::new("checkout-1").format(
+///     &Record::builder()
+///         .level(Level::Warn)
+///         .target("shop::checkout")
+///         .args(format_args!("payment declined"))
+///         .key_values(&[("attempt", 3)])
+///         .build(),
+///     &mut buf,
+/// )?;
+///
+/// let gelf = String::from_utf8(buf).unwrap();
+/// assert!(gelf.starts_with(r#"{"version":"1.1","host":"checkout-1","short_message":"payment declined","#));
+/// assert!(gelf.ends_with(r#""level":4,"_target":"shop::checkout","_attempt":3}"#));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Format`]: ../format/trait.Format.html
+#[derive(Clone, Debug)]
+pub struct Gelf {
+    host: String,
+}
+
+impl Gelf {
+    /// Create a formatter for messages sent from `host`.
+    ///
+    /// The host is the name of the machine or service the messages come from,
+    /// and is written as the message's `host` field.
+    pub fn new(host: impl Into<String>) -> Self {
+        Gelf { host: host.into() }
+    }
+
+    /// The host written to messages.
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    fn write(&self, out: &mut String, record: &Record) -> fmt::Result {
+        let message = match record.args().as_str() {
+            Some(message) => std::borrow::Cow::Borrowed(message),
+            None => std::borrow::Cow::Owned(record.args().to_string()),
+        };
+
+        out.write_str(r#"{"version":"1.1","host":"#)?;
+        json::write_str(out, &self.host)?;
+
+        out.write_str(r#","short_message":"#)?;
+        match message.split_once('\n') {
+            Some((first, _)) => {
+                json::write_str(out, first.trim_end_matches('\r'))?;
+                out.write_str(r#","full_message":"#)?;
+                json::write_str(out, &message)?;
+            }
+            None => json::write_str(out, &message)?,
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        write!(
+            out,
+            r#","timestamp":{}.{:03},"level":{}"#,
+            timestamp.as_secs(),
+            timestamp.subsec_millis(),
+            record.level().syslog_severity()
+        )?;
+
+        out.write_str(r#","_target":"#)?;
+        json::write_str(out, record.target())?;
+        if let Some(module_path) = record.module_path() {
+            out.write_str(r#","_module_path":"#)?;
+            json::write_str(out, module_path)?;
+        }
+        if let Some(file) = record.file() {
+            out.write_str(r#","_file":"#)?;
+            json::write_str(out, file)?;
+        }
+        if let Some(line) = record.line() {
+            write!(out, r#","_line":{}"#, line)?;
+        }
+
+        record
+            .key_values()
+            .visit(&mut WriteFields(out))
+            .map_err(|_| fmt::Error)?;
+
+        out.write_char('}')
+    }
+}
+
+impl Format for Gelf {
+    fn format(&self, record: &Record, buf: &mut Vec<u8>) -> io::Result<()> {
+        let mut out = String::new();
+        self.write(&mut out, record)
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "failed to format a record"))?;
+
+        buf.extend_from_slice(out.as_bytes());
+        Ok(())
+    }
+}
+
+struct WriteFields<'a>(&'a mut String);
+
+impl<'a, 'kvs> Visitor<'kvs> for WriteFields<'a> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+        if value.is_unit() {
+            return Ok(());
+        }
+
+        let mut name = String::with_capacity(key.as_str().len() + 2);
+        name.push('_');
+        name.extend(key.as_str().chars().map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        }));
+        if RESERVED_FIELDS.contains(&&*name) {
+            name.push('_');
+        }
+
+        self.0.push(',');
+        json::write_str(self.0, &name)?;
+        self.0.push(':');
+        value.visit(&mut WriteField(self.0))
+    }
+}
+
+struct WriteField<'a>(&'a mut String);
+
+impl<'a, 'v> Visit<'v> for WriteField<'a> {
+    fn visit_any(&mut self, value: Value) -> Result<(), Error> {
+        Ok(json::write_str(self.0, &value.to_string())?)
+    }
+
+    fn visit_u64(&mut self, value: u64) -> Result<(), Error> {
+        Ok(write!(self.0, "{}", value)?)
+    }
+
+    fn visit_i64(&mut self, value: i64) -> Result<(), Error> {
+        Ok(write!(self.0, "{}", value)?)
+    }
+
+    fn visit_u128(&mut self, value: u128) -> Result<(), Error> {
+        Ok(write!(self.0, "{}", value)?)
+    }
+
+    fn visit_i128(&mut self, value: i128) -> Result<(), Error> {
+        Ok(write!(self.0, "{}", value)?)
+    }
+
+    fn visit_f64(&mut self, value: f64) -> Result<(), Error> {
+        if value.is_finite() {
+            Ok(json::write_f64(self.0, value)?)
+        } else {
+            Ok(json::write_str(self.0, &value.to_string())?)
+        }
+    }
+
+    fn visit_str(&mut self, value: &str) -> Result<(), Error> {
+        Ok(json::write_str(self.0, value)?)
+    }
+}
+
+/// A [`Log`] that sends records to a Graylog input as GELF messages.
+///
+/// Over UDP, messages larger than the chunk size are split into GELF chunks.
+/// Over TCP, messages are delimited by a null byte, and the connection is
+/// reopened if sending a message fails.
+///
+/// All records are enabled; use [`set_max_level`] to filter them. Errors
+/// sending records are ignored by [`Log::log`]; use [`GelfSink::send`] to
+/// observe them.
+///
+/// [`Log`]: ../trait.Log.html
+/// [`set_max_level`]: ../fn.set_max_level.html
+/// [`Log::log`]: ../trait.Log.html#tymethod.log
+/// [`GelfSink::send`]: #method.send
+#[derive(Debug)]
+pub struct GelfSink {
+    format: Gelf,
+    transport: Transport,
+}
+
+#[derive(Debug)]
+enum Transport {
+    Udp {
+        socket: UdpSocket,
+        chunk_size: usize,
+    },
+    Tcp {
+        addrs: Vec<SocketAddr>,
+        timeout: Duration,
+        stream: Mutex<Option<TcpStream>>,
+    },
+}
+
+impl GelfSink {
+    /// Create a sink that sends messages to a GELF UDP input at `addr`.
+    pub fn udp(addr: impl ToSocketAddrs, format: Gelf) -> io::Result<Self> {
+        let addr = resolve(addr)?[0];
+
+        let local: SocketAddr = if addr.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            ([0u16; 8], 0).into()
+        };
+
+        let socket = UdpSocket::bind(local)?;
+        socket.connect(addr)?;
+
+        Ok(GelfSink {
+            format,
+            transport: Transport::Udp {
+                socket,
+                chunk_size: DEFAULT_CHUNK_SIZE,
+            },
+        })
+    }
+
+    /// Create a sink that sends messages to a GELF TCP input at `addr`.
+    ///
+    /// The connection is opened immediately, and an error is returned if
+    /// the input can't be reached. Connecting and writing time out after 10
+    /// seconds; use [`GelfSink::tcp_timeout`] to pick another timeout.
+    ///
+    /// [`GelfSink::tcp_timeout`]: #method.tcp_timeout
+    pub fn tcp(addr: impl ToSocketAddrs, format: Gelf) -> io::Result<Self> {
+        GelfSink::tcp_timeout(addr, format, Duration::from_secs(10))
+    }
+
+    /// Create a sink that sends messages to a GELF TCP input at `addr`, with
+    /// a timeout for connecting and writing.
+    ///
+    /// The connection is opened immediately, and an error is returned if
+    /// the input can't be reached within `timeout`.
+    pub fn tcp_timeout(
+        addr: impl ToSocketAddrs,
+        format: Gelf,
+        timeout: Duration,
+    ) -> io::Result<Self> {
+        let addrs = resolve(addr)?;
+        let stream = connect(&addrs, timeout)?;
+
+        Ok(GelfSink {
+            format,
+            transport: Transport::Tcp {
+                addrs,
+                timeout,
+                stream: Mutex::new(Some(stream)),
+            },
+        })
+    }
+
+    /// Set the largest datagram sent over UDP.
+    ///
+    /// Messages larger than this are split into chunks. The default is 8192
+    /// bytes. Use a smaller size, like 1420 bytes, if messages cross networks
+    /// with a small MTU. This has no effect on TCP sinks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` isn't larger than the 12 byte chunk header.
+    pub fn chunk_size(mut self, size: usize) -> Self {
+        assert!(
+            size > CHUNK_HEADER_LEN,
+            "the chunk size must be larger than the chunk header"
+        );
+
+        if let Transport::Udp {
+            ref mut chunk_size, ..
+        } = self.transport
+        {
+            *chunk_size = size;
+        }
+        self
+    }
+
+    /// Set the timeout for reconnecting to and writing to a TCP input.
+    ///
+    /// The sink is already connected by this point, so this applies to
+    /// writes on the open connection and to any later connections. Use
+    /// [`GelfSink::tcp_timeout`] to also limit the first connection. This has
+    /// no effect on UDP sinks.
+    ///
+    /// [`GelfSink::tcp_timeout`]: #method.tcp_timeout
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        if let Transport::Tcp {
+            timeout: ref mut current,
+            ref mut stream,
+            ..
+        } = self.transport
+        {
+            *current = timeout;
+
+            let stream = stream.get_mut().unwrap_or_else(|err| err.into_inner());
+            if let Some(ref open) = *stream {
+                let _ = open.set_write_timeout(Some(timeout));
+            }
+        }
+        self
+    }
+
+    /// The formatter used for messages.
+    pub fn format(&self) -> &Gelf {
+        &self.format
+    }
+
+    /// Format and send a record.
+    ///
+    /// Returns an error if the record couldn't be sent. Over UDP this means
+    /// it couldn't be written to the socket, or needed more than 128 chunks.
+    pub fn send(&self, record: &Record) -> io::Result<()> {
+        let mut buf = Vec::new();
+        self.format.format(record, &mut buf)?;
+
+        match self.transport {
+            Transport::Udp {
+                ref socket,
+                chunk_size,
+            } => send_udp(socket, chunk_size, &buf),
+            Transport::Tcp {
+                ref addrs,
+                timeout,
+                ref stream,
+            } => {
+                buf.push(0);
+
+                let mut stream = stream.lock().unwrap_or_else(|err| err.into_inner());
+
+                // A connection that was open may have since been closed by
+                // the input, so retry once on a new connection
+                if let Some(ref mut open) = *stream {
+                    if open.write_all(&buf).is_ok() {
+                        return Ok(());
+                    }
+                }
+
+                *stream = None;
+                let mut open = connect(addrs, timeout)?;
+                open.write_all(&buf)?;
+                *stream = Some(open);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Log for GelfSink {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        let _ = self.send(record);
+    }
+
+    fn flush(&self) {
+        if let Transport::Tcp { ref stream, .. } = self.transport {
+            let mut stream = stream.lock().unwrap_or_else(|err| err.into_inner());
+            if let Some(ref mut open) = *stream {
+                let _ = open.flush();
+            }
+        }
+    }
+}
+
+fn resolve(addr: impl ToSocketAddrs) -> io::Result<Vec<SocketAddr>> {
+    let addrs: Vec<_> = addr.to_socket_addrs()?.collect();
+    if addrs.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "the GELF input didn't resolve to any addresses",
+        ));
+    }
+    Ok(addrs)
+}
+
+fn connect(addrs: &[SocketAddr], timeout: Duration) -> io::Result<TcpStream> {
+    let mut last_err = None;
+    for addr in addrs {
+        match TcpStream::connect_timeout(addr, timeout) {
+            Ok(stream) => {
+                stream.set_write_timeout(Some(timeout))?;
+                return Ok(stream);
+            }
+            Err(err) => last_err = Some(err),
+        }
+    }
+
+    Err(last_err.expect("addresses are never empty"))
+}
+
+fn send_udp(socket: &UdpSocket, chunk_size: usize, message: &[u8]) -> io::Result<()> {
+    if message.len() <= chunk_size {
+        socket.send(message)?;
+        return Ok(());
+    }
+
+    let chunk_len = chunk_size - CHUNK_HEADER_LEN;
+    let count = (message.len() + chunk_len - 1) / chunk_len;
+    if count > MAX_CHUNKS {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the GELF message is too large to send over UDP",
+        ));
+    }
+
+    let id = message_id();
+    let mut datagram = Vec::with_capacity(chunk_size);
+    for (seq, chunk) in message.chunks(chunk_len).enumerate() {
+        datagram.clear();
+        datagram.extend_from_slice(&[0x1e, 0x0f]);
+        datagram.extend_from_slice(&id);
+        datagram.push(seq as u8);
+        datagram.push(count as u8);
+        datagram.extend_from_slice(chunk);
+
+        socket.send(&datagram)?;
+    }
+
+    Ok(())
+}
+
+// Chunked messages need an id that's unique among messages in flight
+// from this client, so combine the time with a counter
+fn message_id() -> [u8; 8] {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64;
+
+    (now ^ COUNTER.fetch_add(1, Ordering::Relaxed).rotate_right(16)).to_be_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kv::ToValue;
+    use crate::Level;
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    fn gelf(record: &Record) -> serde_json::Value {
+        let mut buf = Vec::new();
+        Gelf::new("test-host").format(record, &mut buf).unwrap();
+        serde_json::from_slice(&buf).unwrap()
+    }
+
+    #[test]
+    fn format() {
+        let kvs: &[(&str, &dyn ToValue)] = &[
+            ("user id", &"alice"),
+            ("attempt", &3),
+            ("ratio", &0.5),
+            ("nan", &f64::NAN),
+            ("retry", &true),
+            ("id", &7),
+            ("line", &"dup"),
+            ("missing", &None::<u8>),
+        ];
+
+        let gelf = gelf(
+            &Record::builder()
+                .level(Level::Warn)
+                .target("shop::checkout")
+                .module_path(Some("shop::checkout"))
+                .file(Some("src/checkout.rs"))
+                .line(Some(42))
+                .args(format_args!("{} retries", 3))
+                .key_values(&kvs)
+                .build(),
+        );
+
+        let fields = gelf.as_object().unwrap();
+
+        assert_eq!("1.1", fields["version"]);
+        assert_eq!("test-host", fields["host"]);
+        assert_eq!("3 retries", fields["short_message"]);
+        assert!(fields.get("full_message").is_none());
+        assert!(fields["timestamp"].as_f64().unwrap() > 0.0);
+        assert_eq!(4, fields["level"]);
+        assert_eq!("shop::checkout", fields["_target"]);
+        assert_eq!("shop::checkout", fields["_module_path"]);
+        assert_eq!("src/checkout.rs", fields["_file"]);
+        assert_eq!(42, fields["_line"]);
+        assert_eq!("alice", fields["_user_id"]);
+        assert_eq!(3, fields["_attempt"]);
+        assert_eq!(0.5, fields["_ratio"]);
+        assert_eq!("NaN", fields["_nan"]);
+        assert_eq!("true", fields["_retry"]);
+        assert_eq!(7, fields["_id_"]);
+        assert_eq!("dup", fields["_line_"]);
+        assert!(fields.get("_id").is_none());
+        assert!(fields.get("_missing").is_none());
+    }
+
+    #[test]
+    fn format_levels() {
+        for &(level, severity) in &[
+            (Level::Error, 3),
+            (Level::Warn, 4),
+            (Level::Info, 6),
+            (Level::Debug, 7),
+            (Level::Trace, 7),
+        ] {
+            let gelf = gelf(&Record::builder().level(level).build());
+            assert_eq!(severity, gelf["level"]);
+        }
+    }
+
+    #[test]
+    fn format_multiline() {
+        let gelf = gelf(
+            &Record::builder()
+                .args(format_args!("failed\r\ncaused by: timeout"))
+                .build(),
+        );
+
+        assert_eq!("failed", gelf["short_message"]);
+        assert_eq!("failed\r\ncaused by: timeout", gelf["full_message"]);
+    }
+
+    fn udp_input() -> UdpSocket {
+        let input = UdpSocket::bind("127.0.0.1:0").unwrap();
+        input
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        input
+    }
+
+    #[test]
+    fn send_udp() {
+        let input = udp_input();
+        let sink = GelfSink::udp(input.local_addr().unwrap(), Gelf::new("test-host")).unwrap();
+
+        sink.log(&Record::builder().args(format_args!("over udp")).build());
+
+        let mut datagram = [0; 1024];
+        let len = input.recv(&mut datagram).unwrap();
+        let gelf: serde_json::Value = serde_json::from_slice(&datagram[..len]).unwrap();
+
+        assert_eq!("over udp", gelf["short_message"]);
+    }
+
+    #[test]
+    fn send_udp_chunked() {
+        let input = udp_input();
+        let sink = GelfSink::udp(input.local_addr().unwrap(), Gelf::new("test-host"))
+            .unwrap()
+            .chunk_size(64);
+
+        let message = "a".repeat(300);
+        sink.send(&Record::builder().args(format_args!("{}", message)).build())
+            .unwrap();
+
+        let mut chunks = Vec::new();
+        loop {
+            let mut datagram = [0; 64];
+            let len = input.recv(&mut datagram).unwrap();
+            assert_eq!([0x1e, 0x0f], datagram[..2]);
+
+            let count = datagram[11] as usize;
+            chunks.push(datagram[..len].to_vec());
+            if chunks.len() == count {
+                break;
+            }
+        }
+
+        // Chunks share an id, and are numbered in order
+        let mut gelf = Vec::new();
+        for (seq, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunks[0][2..10], chunk[2..10]);
+            assert_eq!(seq as u8, chunk[10]);
+            gelf.extend_from_slice(&chunk[CHUNK_HEADER_LEN..]);
+        }
+
+        let gelf: serde_json::Value = serde_json::from_slice(&gelf).unwrap();
+        assert_eq!(message, gelf["short_message"]);
+    }
+
+    #[test]
+    fn send_udp_too_large() {
+        let input = udp_input();
+        let sink = GelfSink::udp(input.local_addr().unwrap(), Gelf::new("test-host"))
+            .unwrap()
+            .chunk_size(13);
+
+        let message = "a".repeat(200);
+        let err = sink
+            .send(&Record::builder().args(format_args!("{}", message)).build())
+            .unwrap_err();
+
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+    }
+
+    #[test]
+    fn send_tcp() {
+        let input = TcpListener::bind("127.0.0.1:0").unwrap();
+        let sink = GelfSink::tcp(input.local_addr().unwrap(), Gelf::new("test-host")).unwrap();
+
+        sink.log(&Record::builder().args(format_args!("first")).build());
+        sink.log(&Record::builder().args(format_args!("second")).build());
+        sink.flush();
+
+        let (mut stream, _) = input.accept().unwrap();
+        drop(sink);
+
+        let mut received = Vec::new();
+        stream.read_to_end(&mut received).unwrap();
+
+        let messages: Vec<serde_json::Value> = received
+            .split(|&b| b == 0)
+            .filter(|message| !message.is_empty())
+            .map(|message| serde_json::from_slice(message).unwrap())
+            .collect();
+
+        assert_eq!(2, messages.len());
+        assert_eq!("first", messages[0]["short_message"]);
+        assert_eq!("second", messages[1]["short_message"]);
+        assert_eq!(0, *received.last().unwrap());
+    }
+
+    #[test]
+    fn tcp_timeout() {
+        fn write_timeout(sink: &GelfSink) -> Option<Duration> {
+            match sink.transport {
+                Transport::Tcp { ref stream, .. } => {
+                    let stream = stream.lock().unwrap();
+                    stream.as_ref().unwrap().write_timeout().unwrap()
+                }
+                Transport::Udp { .. } => unreachable!(),
+            }
+        }
+
+        let input = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = input.local_addr().unwrap();
+
+        let sink = GelfSink::tcp(addr, Gelf::new("test-host")).unwrap();
+        assert_eq!(Some(Duration::from_secs(10)), write_timeout(&sink));
+
+        let sink = sink.timeout(Duration::from_secs(2));
+        assert_eq!(Some(Duration::from_secs(2)), write_timeout(&sink));
+
+        let sink =
+            GelfSink::tcp_timeout(addr, Gelf::new("test-host"), Duration::from_secs(3)).unwrap();
+        assert_eq!(Some(Duration::from_secs(3)), write_timeout(&sink));
+    }
+
+    #[test]
+    fn send_tcp_unreachable() {
+        let input = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = input.local_addr().unwrap();
+        drop(input);
+
+        assert!(GelfSink::tcp(addr, Gelf::new("test-host")).is_err());
+    }
+}
@@ -0,0 +1,644 @@
+//! The systemd journal.
+//!
+//! This module sends records to the systemd journal with [`JournaldSink`],
+//! using the journal's [native protocol] so key-values become journal fields
+//! that can be matched on with `journalctl`.
+//!
+//! Records map to journal fields as follows:
+//!
+//! | Record        | Journal field          |
+//! | ------------- | ---------------------- |
+//! | `args`        | `MESSAGE`              |
+//! | `level`       | `PRIORITY`             |
+//! | `target`      | `TARGET`               |
+//! | `module_path` | `CODE_FUNC`            |
+//! | `file`        | `CODE_FILE`            |
+//! | `line`        | `CODE_LINE`            |
+//! | `key_values`  | uppercased field names |
+//!
+//! Levels map to priorities as follows:
+//!
+//! | Level   | Priority            |
+//! | ------- | ------------------- |
+//! | `Error` | 3 (error)           |
+//! | `Warn`  | 4 (warning)         |
+//! | `Info`  | 6 (informational)   |
+//! | `Debug` | 7 (debug)           |
+//! | `Trace` | 7 (debug)           |
+//!
+//! Requires the `journald` feature, and is only available on Linux.
+//!
+//! # Examples
+//!
+//! ```edition2018,no_run
+//! use log::journald::JournaldSink;
+//!
+//! # fn main() -> std::io::Result<()> {
+//! let sink = JournaldSink::new()?.syslog_identifier("checkout");
+//!
+//! log::set_boxed_logger(Box::new(sink)).unwrap();
+//! log::set_max_level(log::LevelFilter::Info);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`JournaldSink`]: struct.JournaldSink.html
+//! [native protocol]: https://systemd.io/JOURNAL_NATIVE_PROTOCOL/
+
+use std::fs::File;
+use std::io::{self, Write as _};
+use std::mem;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+use crate::kv::{Error, Key, Value, Visitor};
+use crate::{Log, Metadata, Record};
+
+/// The socket the journal listens for native protocol messages on.
+pub const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+
+// The journal rejects field names longer than this
+const MAX_FIELD_NAME_LEN: usize = 64;
+
+// The fields written for the record itself; key-values that map to one of
+// these names are renamed so they don't clash
+const RESERVED_FIELDS: &[&str] = &[
+    "MESSAGE",
+    "PRIORITY",
+    "SYSLOG_IDENTIFIER",
+    "TARGET",
+    "CODE_FUNC",
+    "CODE_FILE",
+    "CODE_LINE",
+];
+
+/// A [`Log`] that sends records to the systemd journal.
+///
+/// Each record is sent as a single journal entry. Entries too large to send
+/// in a datagram are written to a sealed memory file, which is passed to the
+/// journal instead.
+///
+/// Key-values become journal fields. Keys are uppercased, characters other
+/// than ASCII letters, digits, and `_` are replaced by `_`, and leading
+/// characters that aren't letters are removed, so `http.status` becomes
+/// `HTTP_STATUS`. Names are truncated to 64 characters. Key-values whose keys
+/// have no letters, or that have empty values, like `None`, are skipped.
+/// Values are written as the string produced by their `fmt::Display`
+/// implementation.
+///
+/// Key-values that would clash with one of the fields written for the
+/// record, like `message` or `code_file`, have a `_` appended, so they're
+/// written as `MESSAGE_` or `CODE_FILE_`.
+///
+/// All records are enabled; use [`set_max_level`] to filter them. Errors
+/// sending records are ignored by [`Log::log`]; use [`JournaldSink::send`] to
+/// observe them.
+///
+/// [`Log`]: ../trait.Log.html
+/// [`set_max_level`]: ../fn.set_max_level.html
+/// [`Log::log`]: ../trait.Log.html#tymethod.log
+/// [`JournaldSink::send`]: #method.send
+#[derive(Debug)]
+pub struct JournaldSink {
+    socket: UnixDatagram,
+    path: PathBuf,
+    syslog_identifier: Option<String>,
+}
+
+impl JournaldSink {
+    /// Create a sink that sends records to the journal.
+    ///
+    /// This doesn't check that the journal is running; records sent while it
+    /// isn't will fail.
+    pub fn new() -> io::Result<Self> {
+        Ok(JournaldSink {
+            socket: UnixDatagram::unbound()?,
+            path: PathBuf::from(JOURNAL_SOCKET),
+            syslog_identifier: None,
+        })
+    }
+
+    /// Send records to a different socket than the journal's.
+    pub fn socket_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Set the `SYSLOG_IDENTIFIER` written to each entry.
+    ///
+    /// `journalctl` shows this identifier with each entry, and it can be
+    /// matched on with `journalctl -t`. If it isn't set, the journal shows
+    /// the name of the process instead.
+    pub fn syslog_identifier(mut self, identifier: impl Into<String>) -> Self {
+        self.syslog_identifier = Some(identifier.into());
+        self
+    }
+
+    /// Send a record to the journal.
+    pub fn send(&self, record: &Record) -> io::Result<()> {
+        let mut entry = Vec::new();
+
+        match record.args().as_str() {
+            Some(message) => write_field(&mut entry, "MESSAGE", message.as_bytes()),
+            None => write_field(&mut entry, "MESSAGE", record.args().to_string().as_bytes()),
+        }
+        write_field(
+            &mut entry,
+            "PRIORITY",
+            &[b'0' + record.level().syslog_severity()],
+        );
+        if let Some(ref identifier) = self.syslog_identifier {
+            write_field(&mut entry, "SYSLOG_IDENTIFIER", identifier.as_bytes());
+        }
+        write_field(&mut entry, "TARGET", record.target().as_bytes());
+        if let Some(module_path) = record.module_path() {
+            write_field(&mut entry, "CODE_FUNC", module_path.as_bytes());
+        }
+        if let Some(file) = record.file() {
+            write_field(&mut entry, "CODE_FILE", file.as_bytes());
+        }
+        if let Some(line) = record.line() {
+            write_field(&mut entry, "CODE_LINE", line.to_string().as_bytes());
+        }
+
+        record
+            .key_values()
+            .visit(&mut WriteFields(&mut entry))
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+
+        match self.socket.send_to(&entry, &self.path) {
+            Ok(_) => Ok(()),
+            Err(err) if is_too_large(&err) => self.send_memfd(&entry),
+            Err(err) => Err(err),
+        }
+    }
+
+    // Entries that don't fit in a datagram are written to a sealed memfd,
+    // and the file descriptor is sent to the journal instead
+    fn send_memfd(&self, entry: &[u8]) -> io::Result<()> {
+        // SAFETY: the name is a nul-terminated string
+        let fd = unsafe {
+            libc::memfd_create(
+                b"log-journald\0".as_ptr().cast(),
+                libc::MFD_ALLOW_SEALING | libc::MFD_CLOEXEC,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // SAFETY: the fd was just created and is owned by the file
+        let mut memfd = unsafe { File::from_raw_fd(fd) };
+        memfd.write_all(entry)?;
+
+        // SAFETY: the fd is open for as long as `memfd` is
+        let sealed = unsafe {
+            libc::fcntl(
+                memfd.as_raw_fd(),
+                libc::F_ADD_SEALS,
+                libc::F_SEAL_SHRINK | libc::F_SEAL_GROW | libc::F_SEAL_WRITE | libc::F_SEAL_SEAL,
+            )
+        };
+        if sealed < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        send_fd(&self.socket, &self.path, memfd.as_raw_fd())
+    }
+}
+
+impl Log for JournaldSink {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        let _ = self.send(record);
+    }
+
+    fn flush(&self) {}
+}
+
+struct WriteFields<'a>(&'a mut Vec<u8>);
+
+impl<'a, 'kvs> Visitor<'kvs> for WriteFields<'a> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+        if value.is_unit() {
+            return Ok(());
+        }
+
+        if let Some(mut name) = field_name(key.as_str()) {
+            if RESERVED_FIELDS.contains(&&*name) {
+                name.push('_');
+            }
+
+            match value.to_borrowed_str() {
+                Some(value) => write_field(self.0, &name, value.as_bytes()),
+                None => write_field(self.0, &name, value.to_string().as_bytes()),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Convert a key into a valid journal field name, if it has any letters
+fn field_name(key: &str) -> Option<String> {
+    let name: String = key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .skip_while(|c| !c.is_ascii_uppercase())
+        .take(MAX_FIELD_NAME_LEN)
+        .collect();
+
+    if name.is_empty() {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+// Values containing newlines are written with an explicit length,
+// the rest as `NAME=value` lines
+fn write_field(entry: &mut Vec<u8>, name: &str, value: &[u8]) {
+    entry.extend_from_slice(name.as_bytes());
+
+    if value.contains(&b'\n') {
+        entry.push(b'\n');
+        entry.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        entry.push(b'=');
+    }
+
+    entry.extend_from_slice(value);
+    entry.push(b'\n');
+}
+
+fn is_too_large(err: &io::Error) -> bool {
+    matches!(
+        err.raw_os_error(),
+        Some(libc::EMSGSIZE) | Some(libc::ENOBUFS)
+    )
+}
+
+// Send a file descriptor, with no data, in a datagram to `path`
+fn send_fd(socket: &UnixDatagram, path: &Path, fd: RawFd) -> io::Result<()> {
+    // SAFETY: `sockaddr_un` is a plain C struct, valid when zeroed
+    let mut addr: libc::sockaddr_un = unsafe { mem::zeroed() };
+    addr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+
+    let path = path.as_os_str().as_bytes();
+    if path.len() >= addr.sun_path.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the journal socket path is too long",
+        ));
+    }
+    for (dst, &src) in addr.sun_path.iter_mut().zip(path) {
+        *dst = src as std::os::raw::c_char;
+    }
+
+    // Space for a control message carrying a single fd, aligned for `cmsghdr`
+    let mut control = [0u64; 4];
+
+    // SAFETY: the message points at `addr` and `control`, which outlive the
+    // call to `sendmsg`, and `control` is large enough for a single fd
+    unsafe {
+        let control_len = libc::CMSG_SPACE(mem::size_of::<RawFd>() as u32) as usize;
+        debug_assert!(control_len <= mem::size_of_val(&control));
+
+        let mut msg: libc::msghdr = mem::zeroed();
+        msg.msg_name = ptr::addr_of_mut!(addr).cast();
+        msg.msg_namelen = mem::size_of::<libc::sockaddr_un>() as libc::socklen_t;
+        msg.msg_control = control.as_mut_ptr().cast();
+        msg.msg_controllen = control_len as _;
+
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        (*cmsg).cmsg_level = libc::SOL_SOCKET;
+        (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+        (*cmsg).cmsg_len = libc::CMSG_LEN(mem::size_of::<RawFd>() as u32) as _;
+        ptr::write_unaligned(libc::CMSG_DATA(cmsg).cast::<RawFd>(), fd);
+
+        if libc::sendmsg(socket.as_raw_fd(), &msg, libc::MSG_NOSIGNAL) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kv::ToValue;
+    use crate::Level;
+    use std::io::{Read, Seek, SeekFrom};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    // A stand-in for the journal, listening on a socket in a temporary directory
+    struct Journal {
+        socket: UnixDatagram,
+        path: PathBuf,
+    }
+
+    impl Journal {
+        fn bind() -> Self {
+            static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+            let path = std::env::temp_dir().join(format!(
+                "log-journald-{}-{}.sock",
+                std::process::id(),
+                NEXT.fetch_add(1, Ordering::Relaxed)
+            ));
+            let _ = std::fs::remove_file(&path);
+
+            let socket = UnixDatagram::bind(&path).unwrap();
+            socket
+                .set_read_timeout(Some(Duration::from_secs(10)))
+                .unwrap();
+
+            Journal { socket, path }
+        }
+
+        fn sink(&self) -> JournaldSink {
+            JournaldSink::new().unwrap().socket_path(&self.path)
+        }
+
+        // Receive an entry, either inline or passed as a memfd
+        fn recv(&self) -> Vec<(String, Vec<u8>)> {
+            let mut data = vec![0; 1024 * 1024];
+            let mut control = [0u64; 4];
+
+            // SAFETY: the message points at `data` and `control`, which
+            // outlive the call to `recvmsg`
+            let (len, fd) = unsafe {
+                let mut iov = libc::iovec {
+                    iov_base: data.as_mut_ptr().cast(),
+                    iov_len: data.len(),
+                };
+
+                let mut msg: libc::msghdr = mem::zeroed();
+                msg.msg_iov = &mut iov;
+                msg.msg_iovlen = 1;
+                msg.msg_control = control.as_mut_ptr().cast();
+                msg.msg_controllen = mem::size_of_val(&control) as _;
+
+                let len = libc::recvmsg(self.socket.as_raw_fd(), &mut msg, 0);
+                assert!(len >= 0, "{}", io::Error::last_os_error());
+
+                let cmsg = libc::CMSG_FIRSTHDR(&msg);
+                let fd = if cmsg.is_null() {
+                    None
+                } else {
+                    assert_eq!(libc::SCM_RIGHTS, (*cmsg).cmsg_type);
+                    Some(ptr::read_unaligned(libc::CMSG_DATA(cmsg).cast::<RawFd>()))
+                };
+
+                (len as usize, fd)
+            };
+
+            let entry = match fd {
+                Some(fd) => {
+                    assert_eq!(0, len);
+
+                    // SAFETY: the fd was just received and isn't owned elsewhere
+                    let mut memfd = unsafe { File::from_raw_fd(fd) };
+                    let mut entry = Vec::new();
+
+                    // The offset is shared with the sender, which left it at the end
+                    memfd.seek(SeekFrom::Start(0)).unwrap();
+                    memfd.read_to_end(&mut entry).unwrap();
+                    entry
+                }
+                None => data[..len].to_vec(),
+            };
+
+            parse(&entry)
+        }
+    }
+
+    impl Drop for Journal {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+
+    fn parse(mut entry: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let mut fields = Vec::new();
+
+        while !entry.is_empty() {
+            let end = entry.iter().position(|&b| b == b'=' || b == b'\n').unwrap();
+            let name = String::from_utf8(entry[..end].to_vec()).unwrap();
+
+            let value = if entry[end] == b'=' {
+                let rest = &entry[end + 1..];
+                let len = rest.iter().position(|&b| b == b'\n').unwrap();
+                entry = &rest[len + 1..];
+                rest[..len].to_vec()
+            } else {
+                let rest = &entry[end + 1..];
+                let mut len = [0; 8];
+                len.copy_from_slice(&rest[..8]);
+                let len = u64::from_le_bytes(len) as usize;
+                assert_eq!(b'\n', rest[8 + len]);
+                entry = &rest[8 + len + 1..];
+                rest[8..8 + len].to_vec()
+            };
+
+            fields.push((name, value));
+        }
+
+        fields
+    }
+
+    fn field<'a>(fields: &'a [(String, Vec<u8>)], name: &str) -> Option<&'a str> {
+        fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| std::str::from_utf8(value).unwrap())
+    }
+
+    #[test]
+    fn field_names() {
+        assert_eq!(Some("USER_ID".to_owned()), field_name("user_id"));
+        assert_eq!(Some("HTTP_STATUS".to_owned()), field_name("http.status"));
+        assert_eq!(Some("A_B_".to_owned()), field_name("_1a-b😀"));
+        assert_eq!(None, field_name("_1"));
+        assert_eq!(None, field_name(""));
+        assert_eq!(
+            Some("A".repeat(MAX_FIELD_NAME_LEN)),
+            field_name(&"a".repeat(100))
+        );
+    }
+
+    #[test]
+    fn send() {
+        let journal = Journal::bind();
+
+        let kvs: &[(&str, &dyn ToValue)] = &[
+            ("user.id", &"alice"),
+            ("attempt", &3),
+            ("missing", &None::<u8>),
+            ("_", &"skipped"),
+        ];
+
+        journal
+            .sink()
+            .syslog_identifier("checkout")
+            .send(
+                &Record::builder()
+                    .level(Level::Warn)
+                    .target("shop::checkout")
+                    .module_path(Some("shop::checkout"))
+                    .file(Some("src/checkout.rs"))
+                    .line(Some(42))
+                    .args(format_args!("{} retries", 3))
+                    .key_values(&kvs)
+                    .build(),
+            )
+            .unwrap();
+
+        let fields = journal.recv();
+        let names: Vec<_> = fields.iter().map(|(name, _)| &**name).collect();
+
+        assert_eq!(
+            &[
+                "MESSAGE",
+                "PRIORITY",
+                "SYSLOG_IDENTIFIER",
+                "TARGET",
+                "CODE_FUNC",
+                "CODE_FILE",
+                "CODE_LINE",
+                "USER_ID",
+                "ATTEMPT",
+            ][..],
+            &names[..]
+        );
+        assert_eq!(Some("3 retries"), field(&fields, "MESSAGE"));
+        assert_eq!(Some("4"), field(&fields, "PRIORITY"));
+        assert_eq!(Some("checkout"), field(&fields, "SYSLOG_IDENTIFIER"));
+        assert_eq!(Some("shop::checkout"), field(&fields, "TARGET"));
+        assert_eq!(Some("shop::checkout"), field(&fields, "CODE_FUNC"));
+        assert_eq!(Some("src/checkout.rs"), field(&fields, "CODE_FILE"));
+        assert_eq!(Some("42"), field(&fields, "CODE_LINE"));
+        assert_eq!(Some("alice"), field(&fields, "USER_ID"));
+        assert_eq!(Some("3"), field(&fields, "ATTEMPT"));
+    }
+
+    #[test]
+    fn send_reserved_keys() {
+        let journal = Journal::bind();
+
+        let kvs: &[(&str, &dyn ToValue)] = &[
+            ("priority", &"high"),
+            ("message", &"shadowed"),
+            ("code_file", &"other.rs"),
+            ("syslog_identifier", &"other"),
+            ("code.line", &7),
+        ];
+
+        journal
+            .sink()
+            .send(
+                &Record::builder()
+                    .level(Level::Error)
+                    .file(Some("src/main.rs"))
+                    .line(Some(1))
+                    .args(format_args!("failed"))
+                    .key_values(&kvs)
+                    .build(),
+            )
+            .unwrap();
+
+        let fields = journal.recv();
+        let count = |name: &str| fields.iter().filter(|(n, _)| n == name).count();
+
+        for name in &["MESSAGE", "PRIORITY", "CODE_FILE", "CODE_LINE"] {
+            assert_eq!(1, count(name), "{}", name);
+        }
+        assert_eq!(0, count("SYSLOG_IDENTIFIER"));
+
+        assert_eq!(Some("failed"), field(&fields, "MESSAGE"));
+        assert_eq!(Some("3"), field(&fields, "PRIORITY"));
+        assert_eq!(Some("src/main.rs"), field(&fields, "CODE_FILE"));
+        assert_eq!(Some("1"), field(&fields, "CODE_LINE"));
+        assert_eq!(Some("high"), field(&fields, "PRIORITY_"));
+        assert_eq!(Some("shadowed"), field(&fields, "MESSAGE_"));
+        assert_eq!(Some("other.rs"), field(&fields, "CODE_FILE_"));
+        assert_eq!(Some("other"), field(&fields, "SYSLOG_IDENTIFIER_"));
+        assert_eq!(Some("7"), field(&fields, "CODE_LINE_"));
+    }
+
+    #[test]
+    fn send_priorities() {
+        let journal = Journal::bind();
+        let sink = journal.sink();
+
+        for &(level, priority) in &[
+            (Level::Error, "3"),
+            (Level::Warn, "4"),
+            (Level::Info, "6"),
+            (Level::Debug, "7"),
+            (Level::Trace, "7"),
+        ] {
+            sink.log(&Record::builder().level(level).build());
+            assert_eq!(Some(priority), field(&journal.recv(), "PRIORITY"));
+        }
+    }
+
+    #[test]
+    fn send_multiline() {
+        let journal = Journal::bind();
+
+        journal
+            .sink()
+            .send(
+                &Record::builder()
+                    .args(format_args!("failed\ncaused by: timeout"))
+                    .key_values(&[("trace", "a\nb")])
+                    .build(),
+            )
+            .unwrap();
+
+        let fields = journal.recv();
+        assert_eq!(
+            Some("failed\ncaused by: timeout"),
+            field(&fields, "MESSAGE")
+        );
+        assert_eq!(Some("a\nb"), field(&fields, "TRACE"));
+    }
+
+    #[test]
+    fn send_large() {
+        let journal = Journal::bind();
+
+        // Larger than the default maximum datagram size
+        let message = "a".repeat(512 * 1024);
+        journal
+            .sink()
+            .send(&Record::builder().args(format_args!("{}", message)).build())
+            .unwrap();
+
+        assert!(field(&journal.recv(), "MESSAGE") == Some(&*message));
+    }
+
+    #[test]
+    fn send_unavailable() {
+        let journal = Journal::bind();
+        let sink = journal.sink();
+        drop(journal);
+
+        assert!(sink.send(&Record::builder().build()).is_err());
+    }
+}
@@ -0,0 +1,244 @@
+//! Helpers for writing JSON by hand.
+
+use std::fmt::{self, Write};
+
+/// Write a string as a quoted and escaped JSON string.
+pub(crate) fn write_str(out: &mut impl Write, s: &str) -> fmt::Result {
+    out.write_char('"')?;
+
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        let escape = match c {
+            '"' => "\\\"",
+            '\\' => "\\\\",
+            '\n' => "\\n",
+            '\r' => "\\r",
+            '\t' => "\\t",
+            c if (c as u32) < 0x20 => "",
+            _ => continue,
+        };
+
+        out.write_str(&s[start..i])?;
+        if escape.is_empty() {
+            write!(out, "\\u{:04x}", c as u32)?;
+        } else {
+            out.write_str(escape)?;
+        }
+        start = i + c.len_utf8();
+    }
+
+    out.write_str(&s[start..])?;
+    out.write_char('"')
+}
+
+/// Write a floating point number as a JSON number.
+///
+/// JSON has no representation for `NaN` or infinities, so they're written
+/// as `null`.
+pub(crate) fn write_f64(out: &mut impl Write, v: f64) -> fmt::Result {
+    if v.is_finite() {
+        write!(out, "{}", v)
+    } else {
+        out.write_str("null")
+    }
+}
+
+/// Write a structured value as JSON.
+///
+/// Numbers, booleans, strings, sequences, and maps are written as their JSON
+/// equivalents. Map keys are written as strings. Bytes are written as base64
+/// strings, durations as a number of seconds, and timestamps as RFC 3339
+/// strings. Empty values, like `None`, are written as `null`. Any other value is
+/// written as the string produced by its `fmt::Display` implementation.
+#[cfg(feature = "ecs")]
+pub(crate) fn write_value(out: &mut String, value: crate::kv::Value) -> fmt::Result {
+    use crate::kv::value::{BytesFormat, FormatBytes, Map, Seq, Visit, VisitMap, VisitSeq};
+    use crate::kv::{Error, Value};
+    use std::time::{Duration, SystemTime};
+
+    struct WriteValue<'a>(&'a mut String);
+
+    impl<'a, 'v> Visit<'v> for WriteValue<'a> {
+        fn visit_any(&mut self, value: Value) -> Result<(), Error> {
+            if value.is_unit() {
+                self.0.push_str("null");
+            } else {
+                write_str(self.0, &value.to_string())?;
+            }
+            Ok(())
+        }
+
+        fn visit_u64(&mut self, value: u64) -> Result<(), Error> {
+            Ok(write!(self.0, "{}", value)?)
+        }
+
+        fn visit_i64(&mut self, value: i64) -> Result<(), Error> {
+            Ok(write!(self.0, "{}", value)?)
+        }
+
+        fn visit_u128(&mut self, value: u128) -> Result<(), Error> {
+            Ok(write!(self.0, "{}", value)?)
+        }
+
+        fn visit_i128(&mut self, value: i128) -> Result<(), Error> {
+            Ok(write!(self.0, "{}", value)?)
+        }
+
+        fn visit_f64(&mut self, value: f64) -> Result<(), Error> {
+            Ok(write_f64(self.0, value)?)
+        }
+
+        fn visit_bool(&mut self, value: bool) -> Result<(), Error> {
+            Ok(write!(self.0, "{}", value)?)
+        }
+
+        fn visit_str(&mut self, value: &str) -> Result<(), Error> {
+            Ok(write_str(self.0, value)?)
+        }
+
+        fn visit_char(&mut self, value: char) -> Result<(), Error> {
+            Ok(write_str(self.0, value.encode_utf8(&mut [0; 4]))?)
+        }
+
+        fn visit_bytes(&mut self, value: &[u8]) -> Result<(), Error> {
+            Ok(write!(
+                self.0,
+                "\"{}\"",
+                FormatBytes::new(value).format(BytesFormat::Base64)
+            )?)
+        }
+
+        fn visit_duration(&mut self, value: Duration) -> Result<(), Error> {
+            Ok(write_f64(self.0, value.as_secs_f64())?)
+        }
+
+        fn visit_timestamp(&mut self, value: SystemTime) -> Result<(), Error> {
+            Ok(write!(self.0, "\"{}\"", crate::kv::time::Rfc3339(value))?)
+        }
+
+        fn visit_seq(&mut self, seq: &'v dyn Seq) -> Result<(), Error> {
+            struct Elements<'a>(&'a mut String, bool);
+
+            impl<'a, 'v> VisitSeq<'v> for Elements<'a> {
+                fn visit_element(&mut self, value: Value<'v>) -> Result<(), Error> {
+                    if self.1 {
+                        self.0.push(',');
+                    }
+                    self.1 = true;
+
+                    Ok(write_value(self.0, value)?)
+                }
+            }
+
+            self.0.push('[');
+            seq.visit_seq(&mut Elements(self.0, false))?;
+            self.0.push(']');
+            Ok(())
+        }
+
+        fn visit_map(&mut self, map: &'v dyn Map) -> Result<(), Error> {
+            struct Entries<'a>(&'a mut String, bool);
+
+            impl<'a, 'v> VisitMap<'v> for Entries<'a> {
+                fn visit_entry(&mut self, key: Value<'v>, value: Value<'v>) -> Result<(), Error> {
+                    if self.1 {
+                        self.0.push(',');
+                    }
+                    self.1 = true;
+
+                    match key.to_borrowed_str() {
+                        Some(key) => write_str(self.0, key)?,
+                        None => write_str(self.0, &key.to_string())?,
+                    }
+                    self.0.push(':');
+                    Ok(write_value(self.0, value)?)
+                }
+            }
+
+            self.0.push('{');
+            map.visit_map(&mut Entries(self.0, false))?;
+            self.0.push('}');
+            Ok(())
+        }
+    }
+
+    value.visit(&mut WriteValue(out)).map_err(|_| fmt::Error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn str(s: &str) -> String {
+        let mut out = String::new();
+        write_str(&mut out, s).unwrap();
+        out
+    }
+
+    #[test]
+    fn escape_str() {
+        assert_eq!(r#""""#, str(""));
+        assert_eq!(r#""plain""#, str("plain"));
+        assert_eq!(r#""a \"quoted\" \\ path""#, str(r#"a "quoted" \ path"#));
+        assert_eq!(r#""line\nbreak\ttab\r""#, str("line\nbreak\ttab\r"));
+        assert_eq!(r#""\u0000\u001f""#, str("\u{0}\u{1f}"));
+        assert_eq!(r#""ünïcødé ✓""#, str("ünïcødé ✓"));
+    }
+
+    #[test]
+    fn f64() {
+        let f64 = |v| {
+            let mut out = String::new();
+            write_f64(&mut out, v).unwrap();
+            out
+        };
+
+        assert_eq!("1.5", f64(1.5));
+        assert_eq!("1", f64(1.0));
+        assert_eq!("-0.25", f64(-0.25));
+        assert_eq!("null", f64(f64::NAN));
+        assert_eq!("null", f64(f64::INFINITY));
+    }
+
+    #[cfg(feature = "ecs")]
+    #[test]
+    fn value() {
+        use crate::kv::{ToValue, Value};
+        use std::collections::BTreeMap;
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let json = |value: Value| {
+            let mut out = String::new();
+            write_value(&mut out, value).unwrap();
+            out
+        };
+
+        assert_eq!("1", json(1u8.to_value()));
+        assert_eq!("-1", json((-1i64).to_value()));
+        assert_eq!(
+            "340282366920938463463374607431768211455",
+            json(Value::from(&u128::MAX))
+        );
+        assert_eq!("0.5", json(0.5.to_value()));
+        assert_eq!("true", json(true.to_value()));
+        assert_eq!(r#""a\"b""#, json("a\"b".to_value()));
+        assert_eq!(r#""c""#, json('c'.to_value()));
+        assert_eq!(r#""aGk=""#, json(Value::from_bytes(b"hi")));
+        assert_eq!("1.5", json(Duration::from_millis(1500).to_value()));
+        assert_eq!(
+            r#""1970-01-01T00:00:01Z""#,
+            json((UNIX_EPOCH + Duration::from_secs(1)).to_value())
+        );
+        assert_eq!("null", json(None::<u8>.to_value()));
+        assert_eq!(r#""1.0.0""#, json(Value::from_display(&"1.0.0")));
+        assert_eq!(
+            r#"[[1],[2,3]]"#,
+            json(Value::from_seq(&[[1].to_value(), [2, 3].to_value()]))
+        );
+
+        let mut map = BTreeMap::new();
+        map.insert("a", vec![true]);
+        map.insert("b", vec![]);
+        assert_eq!(r#"{"a":[true],"b":[]}"#, json(Value::from_map(&map)));
+    }
+}
@@ -0,0 +1,180 @@
+//! Formatting for byte slices.
+
+use std::fmt;
+
+/// How to render a slice of bytes as text.
+///
+/// Bytes are rendered as lowercase hex by default.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BytesFormat {
+    /// Lowercase hex, like `0a1b2c`.
+    Hex,
+    /// Standard base64 with padding, like `Chss`.
+    Base64,
+}
+
+impl Default for BytesFormat {
+    fn default() -> Self {
+        BytesFormat::Hex
+    }
+}
+
+/// A slice of bytes that's formatted as text.
+///
+/// The bytes are rendered using a [`BytesFormat`], optionally truncated to a
+/// preview of their first few bytes.
+///
+/// # Examples
+///
+/// ```
+/// use log::kv::value::{BytesFormat, FormatBytes};
+///
+/// let bytes = [0xde, 0xad, 0xbe, 0xef];
+///
+/// assert_eq!("deadbeef", FormatBytes::new(&bytes).to_string());
+/// assert_eq!(
+///     "3q2+7w==",
+///     FormatBytes::new(&bytes).format(BytesFormat::Base64).to_string()
+/// );
+/// assert_eq!(
+///     "dead... (4 bytes)",
+///     FormatBytes::new(&bytes).truncate(2).to_string()
+/// );
+/// ```
+///
+/// [`BytesFormat`]: enum.BytesFormat.html
+#[derive(Clone, Copy, Debug)]
+pub struct FormatBytes<'a> {
+    bytes: &'a [u8],
+    format: BytesFormat,
+    limit: Option<usize>,
+}
+
+impl<'a> FormatBytes<'a> {
+    /// Format a slice of bytes as lowercase hex.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        FormatBytes {
+            bytes,
+            format: BytesFormat::Hex,
+            limit: None,
+        }
+    }
+
+    /// Use the given format for the bytes.
+    pub fn format(mut self, format: BytesFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Only render the first `limit` bytes.
+    ///
+    /// If there are more bytes than the limit then the rendered ones are
+    /// followed by `...` and the total number of bytes.
+    pub fn truncate(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+impl<'a> fmt::Display for FormatBytes<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bytes = match self.limit {
+            Some(limit) if limit < self.bytes.len() => &self.bytes[..limit],
+            _ => self.bytes,
+        };
+
+        match self.format {
+            BytesFormat::Hex => fmt_hex(bytes, f)?,
+            BytesFormat::Base64 => fmt_base64(bytes, f)?,
+        }
+
+        if bytes.len() < self.bytes.len() {
+            write!(f, "... ({} bytes)", self.bytes.len())?;
+        }
+
+        Ok(())
+    }
+}
+
+fn fmt_hex(bytes: &[u8], f: &mut fmt::Formatter) -> fmt::Result {
+    for b in bytes {
+        write!(f, "{:02x}", b)?;
+    }
+
+    Ok(())
+}
+
+fn fmt_base64(bytes: &[u8], f: &mut fmt::Formatter) -> fmt::Result {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    for chunk in bytes.chunks(3) {
+        let n = match *chunk {
+            [a] => (a as u32) << 16,
+            [a, b] => (a as u32) << 16 | (b as u32) << 8,
+            [a, b, c] => (a as u32) << 16 | (b as u32) << 8 | c as u32,
+            _ => unreachable!(),
+        };
+
+        let mut buf = [b'='; 4];
+        for (i, c) in buf.iter_mut().take(chunk.len() + 1).enumerate() {
+            *c = ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize];
+        }
+
+        // The buffer only contains ASCII
+        f.write_str(std::str::from_utf8(&buf).map_err(|_| fmt::Error)?)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex() {
+        assert_eq!("", FormatBytes::new(&[]).to_string());
+        assert_eq!("000fff", FormatBytes::new(&[0x00, 0x0f, 0xff]).to_string());
+    }
+
+    #[test]
+    fn base64() {
+        let base64 = |bytes: &[u8]| {
+            FormatBytes::new(bytes)
+                .format(BytesFormat::Base64)
+                .to_string()
+        };
+
+        assert_eq!("", base64(b""));
+        assert_eq!("Zg==", base64(b"f"));
+        assert_eq!("Zm8=", base64(b"fo"));
+        assert_eq!("Zm9v", base64(b"foo"));
+        assert_eq!("Zm9vYg==", base64(b"foob"));
+        assert_eq!("Zm9vYmE=", base64(b"fooba"));
+        assert_eq!("Zm9vYmFy", base64(b"foobar"));
+        assert_eq!("+/8=", base64(&[0xfb, 0xff]));
+    }
+
+    #[test]
+    fn truncate() {
+        let bytes = [1, 2, 3, 4];
+
+        assert_eq!(
+            "0102... (4 bytes)",
+            FormatBytes::new(&bytes).truncate(2).to_string()
+        );
+        assert_eq!(
+            "AQI=... (4 bytes)",
+            FormatBytes::new(&bytes)
+                .format(BytesFormat::Base64)
+                .truncate(2)
+                .to_string()
+        );
+        assert_eq!("01020304", FormatBytes::new(&bytes).truncate(4).to_string());
+        assert_eq!(
+            "... (4 bytes)",
+            FormatBytes::new(&bytes).truncate(0).to_string()
+        );
+    }
+}
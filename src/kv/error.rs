@@ -10,34 +10,119 @@ pub struct Error {
 enum Inner {
     #[cfg(feature = "std")]
     Boxed(std_support::BoxedError),
-    Msg(&'static str),
-    Value(value_bag::Error),
+    Msg(ErrorKind, &'static str),
+    Value(ErrorKind, value_bag::Error),
     Fmt,
+    Break,
+}
+
+/// The kind of an [`Error`].
+///
+/// [`Error`]: struct.Error.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// A visitor asked to stop visiting early.
+    ///
+    /// This isn't a failure. See [`Error::stop`] for details.
+    ///
+    /// [`Error::stop`]: struct.Error.html#method.stop
+    Break,
+    /// A value failed to format.
+    Fmt,
+    /// A value failed to serialize with `serde`.
+    Serde,
+    /// A value failed to stream with `sval`.
+    Sval,
+    /// An IO error.
+    Io,
+    /// Any other error.
+    Custom,
 }
 
 impl Error {
     /// Create an error from a message.
     pub fn msg(msg: &'static str) -> Self {
         Error {
-            inner: Inner::Msg(msg),
+            inner: Inner::Msg(ErrorKind::Custom, msg),
         }
     }
 
-    // Not public so we don't leak the `value_bag` API
-    pub(super) fn from_value(err: value_bag::Error) -> Self {
+    /// Create an error that signals a visitor wants to stop early.
+    ///
+    /// A [`Visitor`] or [`Visit`] can return this error when it's seen
+    /// everything it needs. Sources will stop visiting and return the error
+    /// back to the caller, which can check for it with [`Error::is_break`]
+    /// and treat it as success.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use log::kv::{Error, Key, Source, Value, Visitor};
+    ///
+    /// struct First<'kvs>(Option<Key<'kvs>>);
+    ///
+    /// impl<'kvs> Visitor<'kvs> for First<'kvs> {
+    ///     fn visit_pair(&mut self, key: Key<'kvs>, _: Value<'kvs>) -> Result<(), Error> {
+    ///         self.0 = Some(key);
+    ///
+    ///         Err(Error::stop())
+    ///     }
+    /// }
+    ///
+    /// let source = [("a", 1), ("b", 2)];
+    ///
+    /// let mut first = First(None);
+    /// if let Err(err) = source.visit(&mut first) {
+    ///     assert!(err.is_break());
+    /// }
+    ///
+    /// assert_eq!("a", first.0.unwrap().as_str());
+    /// ```
+    ///
+    /// [`Visitor`]: trait.Visitor.html
+    /// [`Visit`]: trait.Visit.html
+    /// [`Error::is_break`]: #method.is_break
+    pub fn stop() -> Self {
         Error {
-            inner: Inner::Value(err),
+            inner: Inner::Break,
         }
     }
 
-    // Not public so we don't leak the `value_bag` API
-    pub(super) fn into_value(self) -> value_bag::Error {
+    /// Whether this error is a request to stop visiting early.
+    pub fn is_break(&self) -> bool {
+        matches!(self.inner, Inner::Break)
+    }
+
+    /// Get the kind of this error.
+    pub fn kind(&self) -> ErrorKind {
         match self.inner {
-            Inner::Value(err) => err,
-            #[cfg(feature = "kv_unstable_std")]
-            _ => value_bag::Error::boxed(self),
-            #[cfg(not(feature = "kv_unstable_std"))]
-            _ => value_bag::Error::msg("error inspecting a value"),
+            #[cfg(feature = "std")]
+            Inner::Boxed(ref err) => {
+                if err.is::<std::io::Error>() {
+                    ErrorKind::Io
+                } else {
+                    ErrorKind::Custom
+                }
+            }
+            Inner::Msg(kind, _) => kind,
+            Inner::Value(kind, _) => kind,
+            Inner::Fmt => ErrorKind::Fmt,
+            Inner::Break => ErrorKind::Break,
+        }
+    }
+
+    #[cfg(any(feature = "kv_serde", feature = "kv_sval"))]
+    pub(super) fn with_kind(kind: ErrorKind, msg: &'static str) -> Self {
+        Error {
+            inner: Inner::Msg(kind, msg),
+        }
+    }
+
+    // Not public so we don't leak the `value_bag` API
+    pub(super) fn from_value(kind: ErrorKind, err: value_bag::Error) -> Self {
+        Error {
+            inner: Inner::Value(kind, err),
         }
     }
 }
@@ -48,9 +133,10 @@ impl fmt::Display for Error {
         match &self.inner {
             #[cfg(feature = "std")]
             Boxed(err) => err.fmt(f),
-            Value(err) => err.fmt(f),
-            Msg(msg) => msg.fmt(f),
+            Value(_, err) => err.fmt(f),
+            Msg(_, msg) => msg.fmt(f),
             Fmt => fmt::Error.fmt(f),
+            Break => f.write_str("visiting stopped early"),
         }
     }
 }
@@ -70,6 +156,11 @@ mod std_support {
 
     impl Error {
         /// Create an error from a standard error type.
+        ///
+        /// The error's own [`source`] chain is preserved, so it can still be
+        /// walked through the returned `Error`.
+        ///
+        /// [`source`]: https://doc.rust-lang.org/std/error/trait.Error.html#method.source
         pub fn boxed<E>(err: E) -> Self
         where
             E: Into<BoxedError>,
@@ -80,7 +171,14 @@ mod std_support {
         }
     }
 
-    impl error::Error for Error {}
+    impl error::Error for Error {
+        fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+            match self.inner {
+                Inner::Boxed(ref err) => err.source(),
+                _ => None,
+            }
+        }
+    }
 
     impl From<io::Error> for Error {
         fn from(err: io::Error) -> Self {
@@ -88,3 +186,53 @@ mod std_support {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_kind() {
+        assert_eq!(ErrorKind::Custom, Error::msg("failed").kind());
+        assert_eq!(ErrorKind::Fmt, Error::from(fmt::Error).kind());
+        assert_eq!(ErrorKind::Break, Error::stop().kind());
+        #[cfg(any(feature = "kv_serde", feature = "kv_sval"))]
+        assert_eq!(
+            ErrorKind::Serde,
+            Error::with_kind(ErrorKind::Serde, "failed").kind()
+        );
+
+        assert!(Error::stop().is_break());
+        assert!(!Error::msg("failed").is_break());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn error_source_chain() {
+        use std::error::Error as _;
+        use std::io;
+
+        #[derive(Debug)]
+        struct Outer(io::Error);
+
+        impl fmt::Display for Outer {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("outer")
+            }
+        }
+
+        impl std::error::Error for Outer {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                Some(&self.0)
+            }
+        }
+
+        let err = Error::from(io::Error::new(io::ErrorKind::Other, "inner"));
+        assert_eq!(ErrorKind::Io, err.kind());
+
+        let err = Error::boxed(Outer(io::Error::new(io::ErrorKind::Other, "inner")));
+        assert_eq!(ErrorKind::Custom, err.kind());
+        assert_eq!("outer", err.to_string());
+        assert_eq!("inner", err.source().unwrap().to_string());
+    }
+}
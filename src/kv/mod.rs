@@ -1,26 +1,42 @@
-//! **UNSTABLE:** Structured key-value pairs.
+//! Structured key-value pairs.
 //!
-//! This module is unstable and breaking changes may be made
-//! at any time. See [the tracking issue](https://github.com/rust-lang-nursery/log/issues/328)
-//! for more details.
-//!
-//! Add the `kv_unstable` feature to your `Cargo.toml` to enable
+//! Add the `kv` feature to your `Cargo.toml` to enable
 //! this module:
 //!
 //! ```toml
 //! [dependencies.log]
-//! features = ["kv_unstable"]
+//! features = ["kv"]
 //! ```
+//!
+//! The [`Key`], [`Value`], [`ValueKind`], [`Source`], [`Visitor`], [`Visit`], [`Error`],
+//! and [`ErrorKind`] types are stable and follow the crate's semver guarantees. The `kv_std`,
+//! `kv_sval`, and `kv_serde` features enable support for `std`, `sval`, and `serde` respectively.
+//!
+//! [`Key`]: struct.Key.html
+//! [`Value`]: struct.Value.html
+//! [`ValueKind`]: enum.ValueKind.html
+//! [`Source`]: trait.Source.html
+//! [`Visitor`]: trait.Visitor.html
+//! [`Visit`]: trait.Visit.html
+//! [`Error`]: struct.Error.html
+//! [`ErrorKind`]: enum.ErrorKind.html
 
+mod bytes;
 mod error;
 mod key;
+#[cfg(feature = "kv_std")]
+mod owned;
 pub mod source;
+#[cfg(feature = "kv_std")]
+pub(crate) mod time;
 
 pub mod value;
 
-pub use self::error::Error;
+pub use self::error::{Error, ErrorKind};
 pub use self::key::{Key, ToKey};
+#[cfg(feature = "kv_std")]
+pub use self::owned::{OwnedKey, OwnedSource, OwnedValue};
 pub use self::source::{Source, Visitor};
 
 #[doc(inline)]
-pub use self::value::{ToValue, Value};
+pub use self::value::{RevealToken, Sensitive, ToValue, Value, ValueKind, Visit};
@@ -0,0 +1,1034 @@
+//! Owned keys, values, and sources.
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::iter::FromIterator;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use crate::kv::value::{Map, Seq, Visit, VisitMap, VisitSeq};
+use crate::kv::{Error, Key, Source, ToKey, ToValue, Value, Visitor};
+
+/// An owned key in a structured key-value pair.
+///
+/// An `OwnedKey` can be kept after the [`Key`] it was captured from goes away.
+///
+/// [`Key`]: struct.Key.html
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OwnedKey {
+    key: Box<str>,
+}
+
+impl OwnedKey {
+    /// Get an owned key from a string.
+    pub fn new(key: impl Into<String>) -> Self {
+        OwnedKey {
+            key: key.into().into_boxed_str(),
+        }
+    }
+
+    /// Get a borrowed string from this key.
+    pub fn as_str(&self) -> &str {
+        &self.key
+    }
+
+    /// Get a borrowed key from this key.
+    pub fn as_key(&self) -> Key<'_> {
+        Key::from_str(&self.key)
+    }
+}
+
+impl ToKey for OwnedKey {
+    fn to_key(&self) -> Key<'_> {
+        self.as_key()
+    }
+}
+
+impl<'k> From<Key<'k>> for OwnedKey {
+    fn from(key: Key<'k>) -> Self {
+        OwnedKey::new(key.as_str())
+    }
+}
+
+impl<'a> From<&'a str> for OwnedKey {
+    fn from(key: &'a str) -> Self {
+        OwnedKey::new(key)
+    }
+}
+
+impl From<String> for OwnedKey {
+    fn from(key: String) -> Self {
+        OwnedKey::new(key)
+    }
+}
+
+impl AsRef<str> for OwnedKey {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for OwnedKey {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Debug for OwnedKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.key, f)
+    }
+}
+
+impl fmt::Display for OwnedKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.key, f)
+    }
+}
+
+/// An owned value in a structured key-value pair.
+///
+/// An `OwnedValue` is captured from a [`Value`] using [`Value::to_owned`], and
+/// can be kept after the data it was captured from goes away. The owned value
+/// can be inspected like any other value by converting it back into a `Value`
+/// with [`ToValue`].
+///
+/// Capturing a value preserves:
+///
+/// - primitive numbers, booleans, and characters, with their type.
+/// - strings and bytes.
+/// - empty values, like `None`.
+/// - durations and timestamps.
+/// - errors, along with their chain of sources.
+/// - sequences and maps, along with their elements.
+///
+/// Any other value is captured as the string produced by its `fmt::Display`
+/// implementation.
+///
+/// When the `kv_serde` feature is enabled an `OwnedValue` can also be
+/// deserialized from any self-describing format, like JSON. Types the format
+/// doesn't have a native representation for, like durations or timestamps, are
+/// deserialized as whatever they were serialized as.
+///
+/// # Examples
+///
+/// ```
+/// use log::kv::{OwnedValue, ToValue};
+///
+/// let owned: OwnedValue = {
+///     let borrowed = String::from("a short-lived string");
+///     borrowed.to_value().to_owned()
+/// };
+///
+/// assert_eq!(Some("a short-lived string"), owned.to_value().to_borrowed_str());
+/// ```
+///
+/// [`Value`]: struct.Value.html
+/// [`Value::to_owned`]: struct.Value.html#method.to_owned
+/// [`ToValue`]: trait.ToValue.html
+#[derive(Clone, PartialEq)]
+pub struct OwnedValue {
+    inner: Inner,
+}
+
+#[derive(Clone, PartialEq)]
+enum Inner {
+    None,
+    U64(u64),
+    I64(i64),
+    U128(u128),
+    I128(i128),
+    F64(f64),
+    Bool(bool),
+    Char(char),
+    Str(Box<str>),
+    Bytes(Box<[u8]>),
+    Duration(Duration),
+    Timestamp(SystemTime),
+    Error(OwnedError),
+    Seq(OwnedSeq),
+    Map(OwnedMap),
+}
+
+impl<'v> Value<'v> {
+    /// Capture this value so it can be kept after the data it borrows goes away.
+    ///
+    /// See [`OwnedValue`](struct.OwnedValue.html) for details on what's preserved.
+    pub fn to_owned(&self) -> OwnedValue {
+        let mut capture = Capture(Inner::None);
+        let _ = self.visit(&mut capture);
+
+        OwnedValue { inner: capture.0 }
+    }
+}
+
+struct Capture(Inner);
+
+impl<'v> Visit<'v> for Capture {
+    fn visit_any(&mut self, value: Value) -> Result<(), Error> {
+        self.0 = if value.is_unit() {
+            Inner::None
+        } else {
+            Inner::Str(value.to_string().into())
+        };
+
+        Ok(())
+    }
+
+    fn visit_u64(&mut self, value: u64) -> Result<(), Error> {
+        self.0 = Inner::U64(value);
+        Ok(())
+    }
+
+    fn visit_i64(&mut self, value: i64) -> Result<(), Error> {
+        self.0 = Inner::I64(value);
+        Ok(())
+    }
+
+    fn visit_u128(&mut self, value: u128) -> Result<(), Error> {
+        self.0 = Inner::U128(value);
+        Ok(())
+    }
+
+    fn visit_i128(&mut self, value: i128) -> Result<(), Error> {
+        self.0 = Inner::I128(value);
+        Ok(())
+    }
+
+    fn visit_f64(&mut self, value: f64) -> Result<(), Error> {
+        self.0 = Inner::F64(value);
+        Ok(())
+    }
+
+    fn visit_bool(&mut self, value: bool) -> Result<(), Error> {
+        self.0 = Inner::Bool(value);
+        Ok(())
+    }
+
+    fn visit_str(&mut self, value: &str) -> Result<(), Error> {
+        self.0 = Inner::Str(value.into());
+        Ok(())
+    }
+
+    fn visit_char(&mut self, value: char) -> Result<(), Error> {
+        self.0 = Inner::Char(value);
+        Ok(())
+    }
+
+    fn visit_bytes(&mut self, value: &[u8]) -> Result<(), Error> {
+        self.0 = Inner::Bytes(value.into());
+        Ok(())
+    }
+
+    fn visit_duration(&mut self, value: Duration) -> Result<(), Error> {
+        self.0 = Inner::Duration(value);
+        Ok(())
+    }
+
+    fn visit_timestamp(&mut self, value: SystemTime) -> Result<(), Error> {
+        self.0 = Inner::Timestamp(value);
+        Ok(())
+    }
+
+    fn visit_error(&mut self, err: &(dyn std::error::Error + 'static)) -> Result<(), Error> {
+        self.0 = Inner::Error(OwnedError::capture(err));
+        Ok(())
+    }
+
+    fn visit_borrowed_error(
+        &mut self,
+        err: &'v (dyn std::error::Error + 'static),
+    ) -> Result<(), Error> {
+        self.visit_error(err)
+    }
+
+    fn visit_seq(&mut self, seq: &'v dyn Seq) -> Result<(), Error> {
+        struct Elements(Vec<OwnedValue>);
+
+        impl<'v> VisitSeq<'v> for Elements {
+            fn visit_element(&mut self, value: Value<'v>) -> Result<(), Error> {
+                self.0.push(value.to_owned());
+                Ok(())
+            }
+        }
+
+        let mut elements = Elements(Vec::new());
+        seq.visit_seq(&mut elements)?;
+
+        self.0 = Inner::Seq(OwnedSeq(elements.0));
+        Ok(())
+    }
+
+    fn visit_map(&mut self, map: &'v dyn Map) -> Result<(), Error> {
+        struct Entries(Vec<(OwnedValue, OwnedValue)>);
+
+        impl<'v> VisitMap<'v> for Entries {
+            fn visit_entry(&mut self, key: Value<'v>, value: Value<'v>) -> Result<(), Error> {
+                self.0.push((key.to_owned(), value.to_owned()));
+                Ok(())
+            }
+        }
+
+        let mut entries = Entries(Vec::new());
+        map.visit_map(&mut entries)?;
+
+        self.0 = Inner::Map(OwnedMap(entries.0));
+        Ok(())
+    }
+}
+
+impl ToValue for OwnedValue {
+    fn to_value(&self) -> Value<'_> {
+        match self.inner {
+            Inner::None => ().to_value(),
+            Inner::U64(value) => Value::from(value),
+            Inner::I64(value) => Value::from(value),
+            Inner::U128(ref value) => Value::from(value),
+            Inner::I128(ref value) => Value::from(value),
+            Inner::F64(value) => Value::from(value),
+            Inner::Bool(value) => Value::from(value),
+            Inner::Char(value) => Value::from(value),
+            Inner::Str(ref value) => Value::from(&**value),
+            Inner::Bytes(ref value) => Value::from_bytes(value),
+            Inner::Duration(value) => Value::from(value),
+            Inner::Timestamp(value) => Value::from(value),
+            Inner::Error(ref err) => Value::from_dyn_error(err),
+            Inner::Seq(ref seq) => Value::from_seq(seq),
+            Inner::Map(ref map) => Value::from_map(map),
+        }
+    }
+}
+
+impl fmt::Debug for OwnedValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_value(), f)
+    }
+}
+
+impl fmt::Display for OwnedValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.to_value(), f)
+    }
+}
+
+impl<'v> From<Value<'v>> for OwnedValue {
+    fn from(value: Value<'v>) -> Self {
+        value.to_owned()
+    }
+}
+
+impl<'a> From<&'a str> for OwnedValue {
+    fn from(value: &'a str) -> Self {
+        OwnedValue {
+            inner: Inner::Str(value.into()),
+        }
+    }
+}
+
+impl From<String> for OwnedValue {
+    fn from(value: String) -> Self {
+        OwnedValue {
+            inner: Inner::Str(value.into()),
+        }
+    }
+}
+
+macro_rules! impl_from_primitive {
+    ($($ty:ty => $variant:ident,)*) => {
+        $(
+            impl From<$ty> for OwnedValue {
+                fn from(value: $ty) -> Self {
+                    OwnedValue {
+                        inner: Inner::$variant(value.into()),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_from_primitive![
+    u8 => U64,
+    u16 => U64,
+    u32 => U64,
+    u64 => U64,
+    u128 => U128,
+    i8 => I64,
+    i16 => I64,
+    i32 => I64,
+    i64 => I64,
+    i128 => I128,
+    f32 => F64,
+    f64 => F64,
+    bool => Bool,
+    char => Char,
+];
+
+#[derive(Clone, PartialEq)]
+struct OwnedSeq(Vec<OwnedValue>);
+
+impl Seq for OwnedSeq {
+    fn visit_seq<'v>(&'v self, visitor: &mut dyn VisitSeq<'v>) -> Result<(), Error> {
+        self.0[..].visit_seq(visitor)
+    }
+}
+
+#[derive(Clone, PartialEq)]
+struct OwnedMap(Vec<(OwnedValue, OwnedValue)>);
+
+impl Map for OwnedMap {
+    fn visit_map<'v>(&'v self, visitor: &mut dyn VisitMap<'v>) -> Result<(), Error> {
+        for (key, value) in &self.0 {
+            visitor.visit_entry(key.to_value(), value.to_value())?;
+        }
+
+        Ok(())
+    }
+}
+
+// An error captured with its chain of sources.
+#[derive(Clone, Debug, PartialEq)]
+struct OwnedError {
+    message: String,
+    source: Option<Box<OwnedError>>,
+}
+
+impl OwnedError {
+    fn capture(err: &(dyn std::error::Error + 'static)) -> Self {
+        OwnedError {
+            message: err.to_string(),
+            source: err
+                .source()
+                .map(|source| Box::new(OwnedError::capture(source))),
+        }
+    }
+}
+
+impl fmt::Display for OwnedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for OwnedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_deref().map(|source| source as _)
+    }
+}
+
+/// An owned, ordered collection of key-value pairs.
+///
+/// Pairs are kept in the order they were first inserted. Inserting a key that's
+/// already present replaces its value in place.
+///
+/// # Examples
+///
+/// Capturing the key-values of a record:
+///
+/// ```
+/// use log::kv::{OwnedSource, Source};
+/// use log::Record;
+///
+/// # fn capture(record: &Record) -> OwnedSource {
+/// let owned = OwnedSource::from_source(record.key_values());
+/// # owned
+/// # }
+/// # let kvs: &[(&str, i32)] = &[("a", 1)];
+/// # let owned = capture(&Record::builder().key_values(&kvs).build());
+/// # assert_eq!(1, owned.count());
+/// ```
+#[derive(Clone, Default, PartialEq)]
+pub struct OwnedSource {
+    pairs: Vec<(OwnedKey, OwnedValue)>,
+}
+
+impl OwnedSource {
+    /// Create an empty source.
+    pub fn new() -> Self {
+        OwnedSource::default()
+    }
+
+    /// Capture the key-value pairs of a source.
+    ///
+    /// If the source contains the same key more than once, the last value wins.
+    pub fn from_source(source: &(impl Source + ?Sized)) -> Self {
+        struct Collect<'a>(&'a mut OwnedSource);
+
+        impl<'a, 'kvs> Visitor<'kvs> for Collect<'a> {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                self.0.insert(key, value.to_owned());
+                Ok(())
+            }
+        }
+
+        let mut owned = OwnedSource::new();
+        let _ = source.visit(&mut Collect(&mut owned));
+        owned
+    }
+
+    /// Insert a key-value pair.
+    ///
+    /// If the key is already present its value is replaced, and the previous value
+    /// is returned.
+    pub fn insert(
+        &mut self,
+        key: impl Into<OwnedKey>,
+        value: impl Into<OwnedValue>,
+    ) -> Option<OwnedValue> {
+        let key = key.into();
+        let value = value.into();
+
+        match self.pairs.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => Some(std::mem::replace(v, value)),
+            None => {
+                self.pairs.push((key, value));
+                None
+            }
+        }
+    }
+
+    /// Remove a key-value pair, returning its value if it was present.
+    pub fn remove(&mut self, key: &str) -> Option<OwnedValue> {
+        let i = self.pairs.iter().position(|(k, _)| k.as_str() == key)?;
+        Some(self.pairs.remove(i).1)
+    }
+
+    /// The number of key-value pairs.
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Whether there are no key-value pairs.
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Iterate over the key-value pairs in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&OwnedKey, &OwnedValue)> {
+        self.pairs.iter().map(|(k, v)| (k, v))
+    }
+}
+
+impl Source for OwnedSource {
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+        for (key, value) in &self.pairs {
+            visitor.visit_pair(key.as_key(), value.to_value())?;
+        }
+
+        Ok(())
+    }
+
+    fn get(&self, key: Key) -> Option<Value<'_>> {
+        self.pairs
+            .iter()
+            .find(|(k, _)| k.as_str() == key.as_str())
+            .map(|(_, v)| v.to_value())
+    }
+
+    fn count(&self) -> usize {
+        self.pairs.len()
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for OwnedSource
+where
+    K: Into<OwnedKey>,
+    V: Into<OwnedValue>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut owned = OwnedSource::new();
+        for (key, value) in iter {
+            owned.insert(key, value);
+        }
+
+        owned
+    }
+}
+
+impl fmt::Debug for OwnedSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(feature = "kv_serde")]
+mod serde_support {
+    use super::*;
+
+    use std::cmp;
+
+    use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess};
+    use serde::ser::{Serialize, SerializeMap, Serializer};
+
+    // Lengths come from the input, so they're capped to avoid huge allocations
+    // for payloads that claim more elements than they contain
+    fn cautious(hint: Option<usize>) -> usize {
+        cmp::min(hint.unwrap_or(0), 4096)
+    }
+
+    impl Serialize for OwnedKey {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.serialize_str(&self.key)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for OwnedKey {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            String::deserialize(deserializer).map(OwnedKey::new)
+        }
+    }
+
+    impl Serialize for OwnedValue {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            self.to_value().serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for OwnedValue {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            struct ValueVisitor;
+
+            impl<'de> de::Visitor<'de> for ValueVisitor {
+                type Value = OwnedValue;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("a structured value")
+                }
+
+                fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E> {
+                    Ok(OwnedValue::from(v))
+                }
+
+                fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> {
+                    Ok(OwnedValue::from(v))
+                }
+
+                fn visit_i128<E>(self, v: i128) -> Result<Self::Value, E> {
+                    Ok(OwnedValue::from(v))
+                }
+
+                fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
+                    Ok(OwnedValue::from(v))
+                }
+
+                fn visit_u128<E>(self, v: u128) -> Result<Self::Value, E> {
+                    Ok(OwnedValue::from(v))
+                }
+
+                fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E> {
+                    Ok(OwnedValue::from(v))
+                }
+
+                fn visit_char<E>(self, v: char) -> Result<Self::Value, E> {
+                    Ok(OwnedValue::from(v))
+                }
+
+                fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
+                    Ok(OwnedValue::from(v))
+                }
+
+                fn visit_string<E>(self, v: String) -> Result<Self::Value, E> {
+                    Ok(OwnedValue::from(v))
+                }
+
+                fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E> {
+                    Ok(OwnedValue {
+                        inner: Inner::Bytes(v.into()),
+                    })
+                }
+
+                fn visit_none<E>(self) -> Result<Self::Value, E> {
+                    Ok(OwnedValue { inner: Inner::None })
+                }
+
+                fn visit_unit<E>(self) -> Result<Self::Value, E> {
+                    Ok(OwnedValue { inner: Inner::None })
+                }
+
+                fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+                where
+                    D: Deserializer<'de>,
+                {
+                    OwnedValue::deserialize(deserializer)
+                }
+
+                fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+                where
+                    D: Deserializer<'de>,
+                {
+                    OwnedValue::deserialize(deserializer)
+                }
+
+                fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+                where
+                    A: SeqAccess<'de>,
+                {
+                    let mut elements = Vec::with_capacity(cautious(seq.size_hint()));
+                    while let Some(element) = seq.next_element()? {
+                        elements.push(element);
+                    }
+
+                    Ok(OwnedValue {
+                        inner: Inner::Seq(OwnedSeq(elements)),
+                    })
+                }
+
+                fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+                where
+                    A: MapAccess<'de>,
+                {
+                    let mut entries = Vec::with_capacity(cautious(map.size_hint()));
+                    while let Some(entry) = map.next_entry()? {
+                        entries.push(entry);
+                    }
+
+                    Ok(OwnedValue {
+                        inner: Inner::Map(OwnedMap(entries)),
+                    })
+                }
+            }
+
+            deserializer.deserialize_any(ValueVisitor)
+        }
+    }
+
+    impl Serialize for OwnedSource {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let mut map = serializer.serialize_map(Some(self.len()))?;
+            for (key, value) in self.iter() {
+                map.serialize_entry(key, value)?;
+            }
+
+            map.end()
+        }
+    }
+
+    // If a key appears more than once the last value wins, the same as
+    // `OwnedSource::from_source`.
+    impl<'de> Deserialize<'de> for OwnedSource {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            struct SourceVisitor;
+
+            impl<'de> de::Visitor<'de> for SourceVisitor {
+                type Value = OwnedSource;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("a map of key-value pairs")
+                }
+
+                fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+                where
+                    A: MapAccess<'de>,
+                {
+                    let mut source = OwnedSource::new();
+                    while let Some((key, value)) = map.next_entry::<OwnedKey, OwnedValue>()? {
+                        source.insert(key, value);
+                    }
+
+                    Ok(source)
+                }
+            }
+
+            deserializer.deserialize_map(SourceVisitor)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kv::value::tests::Token;
+
+    #[test]
+    fn owned_value_primitives() {
+        assert_eq!(
+            Token::U64(1),
+            Value::from(1u8).to_owned().to_value().to_token()
+        );
+        assert_eq!(
+            Token::I64(-1),
+            Value::from(-1i32).to_owned().to_value().to_token()
+        );
+        assert_eq!(
+            Token::F64(1.5),
+            Value::from(1.5f64).to_owned().to_value().to_token()
+        );
+        assert_eq!(
+            Token::Bool(true),
+            Value::from(true).to_owned().to_value().to_token()
+        );
+        assert_eq!(
+            Token::Char('a'),
+            Value::from('a').to_owned().to_value().to_token()
+        );
+        assert_eq!(
+            Some(u128::MAX),
+            Value::from(&u128::MAX).to_owned().to_value().to_u128()
+        );
+        assert_eq!(
+            Token::None,
+            None::<u8>.to_value().to_owned().to_value().to_token()
+        );
+    }
+
+    #[test]
+    fn owned_value_str() {
+        let owned = {
+            let short_lived = String::from("a string");
+            Value::from(&*short_lived).to_owned()
+        };
+
+        assert_eq!(Some("a string"), owned.to_value().to_borrowed_str());
+        assert_eq!("a string", owned.to_string());
+    }
+
+    #[test]
+    fn owned_value_bytes() {
+        let owned = {
+            let short_lived = vec![1u8, 2, 3];
+            Value::from_bytes(&short_lived).to_owned()
+        };
+
+        assert_eq!(Some(&[1u8, 2, 3][..]), owned.to_value().to_borrowed_bytes());
+        assert_eq!("010203", owned.to_string());
+    }
+
+    #[test]
+    fn owned_value_time() {
+        let duration = Duration::from_millis(1500);
+        assert_eq!(
+            Some(duration),
+            duration.to_value().to_owned().to_value().to_duration()
+        );
+
+        let now = SystemTime::now();
+        assert_eq!(
+            Some(now),
+            now.to_value().to_owned().to_value().to_timestamp()
+        );
+    }
+
+    #[test]
+    fn owned_value_fallback() {
+        let owned = Value::from_debug(&vec!["a"]).to_owned();
+
+        assert_eq!(Some("[\"a\"]"), owned.to_value().to_borrowed_str());
+    }
+
+    #[test]
+    fn owned_value_nested() {
+        let mut map = std::collections::BTreeMap::new();
+        map.insert("a", vec![1, 2]);
+        map.insert("b", vec![]);
+
+        let owned = map.to_value().to_owned();
+
+        assert_eq!("{a: [1, 2], b: []}", owned.to_string());
+        assert_eq!(owned, owned.to_value().to_owned());
+    }
+
+    #[test]
+    fn owned_value_error_chain() {
+        use std::io;
+
+        #[derive(Debug)]
+        struct Outer(io::Error);
+
+        impl fmt::Display for Outer {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("outer")
+            }
+        }
+
+        impl std::error::Error for Outer {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                Some(&self.0)
+            }
+        }
+
+        let owned = {
+            let err = Outer(io::Error::new(io::ErrorKind::Other, "inner"));
+            Value::from_dyn_error(&err).to_owned()
+        };
+
+        let value = owned.to_value();
+        let err = value.to_borrowed_error().unwrap();
+
+        assert_eq!("outer", err.to_string());
+        assert_eq!("inner", err.source().unwrap().to_string());
+        assert!(err.source().unwrap().source().is_none());
+    }
+
+    #[test]
+    fn owned_source_insert() {
+        let mut owned = OwnedSource::new();
+
+        assert_eq!(None, owned.insert("a", 1));
+        assert_eq!(None, owned.insert("b", "two"));
+        assert_eq!(Some(OwnedValue::from(1)), owned.insert("a", 3));
+
+        assert_eq!(2, owned.count());
+        assert_eq!(
+            vec!["a", "b"],
+            owned.iter().map(|(k, _)| k.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!(Some(3), owned.get(Key::from_str("a")).unwrap().to_i64());
+
+        assert_eq!(Some(OwnedValue::from(3)), owned.remove("a"));
+        assert_eq!(None, owned.remove("a"));
+        assert_eq!(1, owned.len());
+    }
+
+    #[test]
+    fn owned_source_from_source() {
+        let owned = {
+            let value = String::from("a string");
+            let kvs = vec![
+                ("a", value.to_value()),
+                ("b", Value::from(1)),
+                ("a", Value::from(2)),
+            ];
+
+            OwnedSource::from_source(&kvs)
+        };
+
+        assert_eq!(2, owned.count());
+        assert_eq!(Some(2), owned.get(Key::from_str("a")).unwrap().to_i64());
+        assert_eq!("{\"a\": 2, \"b\": 1}", format!("{:?}", owned));
+    }
+
+    #[test]
+    fn owned_source_from_iter() {
+        let owned: OwnedSource = vec![("a", 1), ("b", 2)].into_iter().collect();
+
+        assert_eq!(2, owned.len());
+        assert!(!owned.is_empty());
+    }
+
+    #[cfg(feature = "kv_serde")]
+    #[test]
+    fn owned_value_serde() {
+        use serde_test::{assert_de_tokens, assert_ser_tokens, Token as SerdeToken};
+
+        assert_ser_tokens(
+            &[1, 2].to_value().to_owned(),
+            &[
+                SerdeToken::Seq { len: None },
+                SerdeToken::I64(1),
+                SerdeToken::I64(2),
+                SerdeToken::SeqEnd,
+            ],
+        );
+
+        assert_de_tokens(&OwnedValue::from(1u64), &[SerdeToken::U64(1)]);
+        assert_de_tokens(&OwnedValue::from(-1i64), &[SerdeToken::I64(-1)]);
+        assert_de_tokens(&OwnedValue::from("a"), &[SerdeToken::Str("a")]);
+        assert_de_tokens(&OwnedValue::from(true), &[SerdeToken::Bool(true)]);
+        assert_de_tokens(
+            &OwnedValue::from(None::<u8>.to_value()),
+            &[SerdeToken::None],
+        );
+        assert_de_tokens(
+            &OwnedValue::from(Value::from_bytes(&[1, 2])),
+            &[SerdeToken::Bytes(&[1, 2])],
+        );
+        assert_de_tokens(
+            &OwnedValue::from("a"),
+            &[SerdeToken::Some, SerdeToken::Str("a")],
+        );
+        assert_de_tokens(
+            &[1u64, 2].to_value().to_owned(),
+            &[
+                SerdeToken::Seq { len: Some(2) },
+                SerdeToken::U64(1),
+                SerdeToken::U64(2),
+                SerdeToken::SeqEnd,
+            ],
+        );
+
+        let mut map = std::collections::BTreeMap::new();
+        map.insert("a", 1u64);
+        assert_de_tokens(
+            &map.to_value().to_owned(),
+            &[
+                SerdeToken::Map { len: Some(1) },
+                SerdeToken::Str("a"),
+                SerdeToken::U64(1),
+                SerdeToken::MapEnd,
+            ],
+        );
+
+        // Lengths that the input claims aren't trusted
+        assert_de_tokens(
+            &[1u64].to_value().to_owned(),
+            &[
+                SerdeToken::Seq {
+                    len: Some(usize::MAX),
+                },
+                SerdeToken::U64(1),
+                SerdeToken::SeqEnd,
+            ],
+        );
+        assert_de_tokens(
+            &map.to_value().to_owned(),
+            &[
+                SerdeToken::Map {
+                    len: Some(usize::MAX),
+                },
+                SerdeToken::Str("a"),
+                SerdeToken::U64(1),
+                SerdeToken::MapEnd,
+            ],
+        );
+    }
+
+    #[cfg(feature = "kv_serde")]
+    #[test]
+    fn owned_source_serde() {
+        use serde_test::{assert_de_tokens, assert_tokens, Token as SerdeToken};
+
+        let owned: OwnedSource = vec![("b", 1u64), ("a", 2u64)].into_iter().collect();
+        assert_tokens(
+            &owned,
+            &[
+                SerdeToken::Map { len: Some(2) },
+                SerdeToken::Str("b"),
+                SerdeToken::U64(1),
+                SerdeToken::Str("a"),
+                SerdeToken::U64(2),
+                SerdeToken::MapEnd,
+            ],
+        );
+
+        let owned: OwnedSource = vec![("a", 2u64)].into_iter().collect();
+        assert_de_tokens(
+            &owned,
+            &[
+                SerdeToken::Map { len: None },
+                SerdeToken::Str("a"),
+                SerdeToken::U64(1),
+                SerdeToken::Str("a"),
+                SerdeToken::U64(2),
+                SerdeToken::MapEnd,
+            ],
+        );
+    }
+}
@@ -15,10 +15,17 @@ pub trait Source {
     /// If the given visitor returns an error then the source may early-return with it,
     /// even if there are more key-value pairs.
     ///
+    /// A visitor that's done before it's seen every pair can return [`Error::stop`].
+    /// Check for it with [`Error::is_break`] to tell it apart from a real failure.
+    ///
     /// # Implementation notes
     ///
     /// A source should yield the same key-value pairs to a subsequent visitor unless
-    /// that visitor itself fails.
+    /// that visitor itself fails. Sources should return errors from their visitor
+    /// as-is, so callers can still recognize [`Error::stop`].
+    ///
+    /// [`Error::stop`]: struct.Error.html#method.stop
+    /// [`Error::is_break`]: struct.Error.html#method.is_break
     fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error>;
 
     /// Get the value for a given key.
@@ -46,6 +53,80 @@ pub trait Source {
     fn count(&self) -> usize {
         count_default(self)
     }
+
+    /// Chain this source with another.
+    ///
+    /// The key-value pairs in `self` are visited before the ones in `other`.
+    fn chain<U>(self, other: U) -> Chain<Self, U>
+    where
+        Self: Sized,
+        U: Source,
+    {
+        Chain {
+            first: self,
+            second: other,
+        }
+    }
+
+    /// Only visit the key-value pairs that match a predicate.
+    fn filter<F>(self, predicate: F) -> Filter<Self, F>
+    where
+        Self: Sized,
+        F: Fn(Key, &Value) -> bool,
+    {
+        Filter {
+            source: self,
+            predicate,
+        }
+    }
+
+    /// Transform the values in this source.
+    ///
+    /// The mapping can replace a value with any other that lives at least as long.
+    fn map_values<F>(self, map: F) -> MapValues<Self, F>
+    where
+        Self: Sized,
+        F: for<'k, 'v> Fn(Key<'k>, Value<'v>) -> Value<'v>,
+    {
+        MapValues { source: self, map }
+    }
+
+    /// Transform the keys in this source.
+    fn rename_keys<F>(self, rename: F) -> RenameKeys<Self, F>
+    where
+        Self: Sized,
+        F: for<'k> Fn(Key<'k>) -> Key<'k>,
+    {
+        RenameKeys {
+            source: self,
+            rename,
+        }
+    }
+
+    /// Only visit the last key-value pair for each key.
+    ///
+    /// This adapter doesn't allocate, so visiting it is quadratic in the number of
+    /// key-value pairs. It's intended for the small sources that are typical of
+    /// log records.
+    fn dedup_last_wins(self) -> DedupLastWins<Self>
+    where
+        Self: Sized,
+    {
+        DedupLastWins { source: self }
+    }
+
+    /// Replace the values for the given keys with `replacement`.
+    fn redact<'a, V>(self, keys: &'a [&'a str], replacement: V) -> Redact<'a, Self, V>
+    where
+        Self: Sized,
+        V: ToValue,
+    {
+        Redact {
+            source: self,
+            keys,
+            replacement,
+        }
+    }
 }
 
 /// The default implementation of `Source::get`
@@ -154,6 +235,23 @@ where
     }
 }
 
+impl<S, const N: usize> Source for [S; N]
+where
+    S: Source,
+{
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+        Source::visit(self as &[_], visitor)
+    }
+
+    fn get(&self, key: Key) -> Option<Value<'_>> {
+        Source::get(self as &[_], key)
+    }
+
+    fn count(&self) -> usize {
+        Source::count(self as &[_])
+    }
+}
+
 impl<S> Source for Option<S>
 where
     S: Source,
@@ -219,6 +317,631 @@ impl<'a, 'b: 'a, 'kvs> Visitor<'kvs> for fmt::DebugTuple<'a, 'b> {
     }
 }
 
+/// A source that visits two sources in order.
+///
+/// This type is returned by [`Source::chain`].
+#[derive(Debug, Clone)]
+pub struct Chain<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> Source for Chain<A, B>
+where
+    A: Source,
+    B: Source,
+{
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+        self.first.visit(visitor)?;
+        self.second.visit(visitor)
+    }
+
+    fn get(&self, key: Key) -> Option<Value<'_>> {
+        self.first.get(key.clone()).or_else(|| self.second.get(key))
+    }
+
+    fn count(&self) -> usize {
+        self.first.count() + self.second.count()
+    }
+}
+
+/// A source that only visits key-value pairs matching a predicate.
+///
+/// This type is returned by [`Source::filter`].
+#[derive(Clone)]
+pub struct Filter<S, F> {
+    source: S,
+    predicate: F,
+}
+
+impl<S, F> fmt::Debug for Filter<S, F>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Filter")
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+impl<S, F> Source for Filter<S, F>
+where
+    S: Source,
+    F: Fn(Key, &Value) -> bool,
+{
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+        struct FilterVisitor<'a, 'kvs, F> {
+            predicate: &'a F,
+            visitor: &'a mut dyn Visitor<'kvs>,
+        }
+
+        impl<'a, 'kvs, F> Visitor<'kvs> for FilterVisitor<'a, 'kvs, F>
+        where
+            F: Fn(Key, &Value) -> bool,
+        {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                if (self.predicate)(key.clone(), &value) {
+                    self.visitor.visit_pair(key, value)
+                } else {
+                    Ok(())
+                }
+            }
+        }
+
+        self.source.visit(&mut FilterVisitor {
+            predicate: &self.predicate,
+            visitor,
+        })
+    }
+
+    fn get(&self, key: Key) -> Option<Value<'_>> {
+        let value = self.source.get(key.clone())?;
+
+        if (self.predicate)(key.clone(), &value) {
+            Some(value)
+        } else {
+            // The pair the source picked was filtered out, but another
+            // pair with the same key might not be
+            get_default(self, key)
+        }
+    }
+}
+
+/// A source that transforms its values.
+///
+/// This type is returned by [`Source::map_values`].
+#[derive(Clone)]
+pub struct MapValues<S, F> {
+    source: S,
+    map: F,
+}
+
+impl<S, F> fmt::Debug for MapValues<S, F>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MapValues")
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+impl<S, F> Source for MapValues<S, F>
+where
+    S: Source,
+    F: for<'k, 'v> Fn(Key<'k>, Value<'v>) -> Value<'v>,
+{
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+        struct MapVisitor<'a, 'kvs, F> {
+            map: &'a F,
+            visitor: &'a mut dyn Visitor<'kvs>,
+        }
+
+        impl<'a, 'kvs, F> Visitor<'kvs> for MapVisitor<'a, 'kvs, F>
+        where
+            F: for<'k, 'v> Fn(Key<'k>, Value<'v>) -> Value<'v>,
+        {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                let value = (self.map)(key.clone(), value);
+                self.visitor.visit_pair(key, value)
+            }
+        }
+
+        self.source.visit(&mut MapVisitor {
+            map: &self.map,
+            visitor,
+        })
+    }
+
+    fn get(&self, key: Key) -> Option<Value<'_>> {
+        let value = self.source.get(key.clone())?;
+        Some((self.map)(key, value))
+    }
+
+    fn count(&self) -> usize {
+        self.source.count()
+    }
+}
+
+/// A source that transforms its keys.
+///
+/// This type is returned by [`Source::rename_keys`].
+#[derive(Clone)]
+pub struct RenameKeys<S, F> {
+    source: S,
+    rename: F,
+}
+
+impl<S, F> fmt::Debug for RenameKeys<S, F>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RenameKeys")
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+impl<S, F> Source for RenameKeys<S, F>
+where
+    S: Source,
+    F: for<'k> Fn(Key<'k>) -> Key<'k>,
+{
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+        struct RenameVisitor<'a, 'kvs, F> {
+            rename: &'a F,
+            visitor: &'a mut dyn Visitor<'kvs>,
+        }
+
+        impl<'a, 'kvs, F> Visitor<'kvs> for RenameVisitor<'a, 'kvs, F>
+        where
+            F: for<'k> Fn(Key<'k>) -> Key<'k>,
+        {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                self.visitor.visit_pair((self.rename)(key), value)
+            }
+        }
+
+        self.source.visit(&mut RenameVisitor {
+            rename: &self.rename,
+            visitor,
+        })
+    }
+
+    fn get(&self, key: Key) -> Option<Value<'_>> {
+        // Renaming can't be reversed, so look through the renamed keys
+        get_default(self, key)
+    }
+
+    fn count(&self) -> usize {
+        self.source.count()
+    }
+}
+
+/// A source that only visits the last key-value pair for each key.
+///
+/// This type is returned by [`Source::dedup_last_wins`].
+#[derive(Debug, Clone)]
+pub struct DedupLastWins<S> {
+    source: S,
+}
+
+impl<S> DedupLastWins<S>
+where
+    S: Source,
+{
+    // Visit each pair in the source along with whether it's the last one with its key
+    fn visit_last<'kvs>(
+        &'kvs self,
+        mut visit: impl FnMut(Key<'kvs>, Value<'kvs>) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        struct Outer<'kvs, F> {
+            source: &'kvs dyn Source,
+            index: usize,
+            visit: F,
+        }
+
+        impl<'kvs, F> Visitor<'kvs> for Outer<'kvs, F>
+        where
+            F: FnMut(Key<'kvs>, Value<'kvs>) -> Result<(), Error>,
+        {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                let mut later = Later {
+                    key: &key,
+                    index: 0,
+                    after: self.index,
+                    found: false,
+                };
+                let _ = self.source.visit(&mut later);
+
+                self.index += 1;
+
+                if later.found {
+                    Ok(())
+                } else {
+                    (self.visit)(key, value)
+                }
+            }
+        }
+
+        struct Later<'a, 'kvs> {
+            key: &'a Key<'kvs>,
+            index: usize,
+            after: usize,
+            found: bool,
+        }
+
+        impl<'a, 'kvs> Visitor<'kvs> for Later<'a, 'kvs> {
+            fn visit_pair(&mut self, key: Key<'kvs>, _: Value<'kvs>) -> Result<(), Error> {
+                if self.index > self.after && *self.key == key {
+                    self.found = true;
+
+                    // There's no need to look any further
+                    return Err(Error::stop());
+                }
+                self.index += 1;
+
+                Ok(())
+            }
+        }
+
+        self.source.visit(&mut Outer {
+            source: &self.source,
+            index: 0,
+            visit: &mut visit,
+        })
+    }
+}
+
+impl<S> Source for DedupLastWins<S>
+where
+    S: Source,
+{
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+        self.visit_last(|key, value| visitor.visit_pair(key, value))
+    }
+
+    fn get(&self, key: Key) -> Option<Value<'_>> {
+        // The default implementation keeps the last value it sees
+        get_default(&self.source, key)
+    }
+
+    fn count(&self) -> usize {
+        let mut count = 0;
+        let _ = self.visit_last(|_, _| {
+            count += 1;
+            Ok(())
+        });
+        count
+    }
+}
+
+/// A source that replaces the values for some keys.
+///
+/// This type is returned by [`Source::redact`].
+#[derive(Debug, Clone)]
+pub struct Redact<'a, S, V> {
+    source: S,
+    keys: &'a [&'a str],
+    replacement: V,
+}
+
+impl<'a, S, V> Redact<'a, S, V> {
+    fn is_redacted(&self, key: &Key) -> bool {
+        self.keys.iter().any(|k| *k == key.as_str())
+    }
+}
+
+impl<'a, S, V> Source for Redact<'a, S, V>
+where
+    S: Source,
+    V: ToValue,
+{
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+        struct RedactVisitor<'r, 'a, 'kvs, S, V> {
+            redact: &'kvs Redact<'a, S, V>,
+            visitor: &'r mut dyn Visitor<'kvs>,
+        }
+
+        impl<'r, 'a, 'kvs, S, V> Visitor<'kvs> for RedactVisitor<'r, 'a, 'kvs, S, V>
+        where
+            V: ToValue,
+        {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                if self.redact.is_redacted(&key) {
+                    self.visitor
+                        .visit_pair(key, self.redact.replacement.to_value())
+                } else {
+                    self.visitor.visit_pair(key, value)
+                }
+            }
+        }
+
+        self.source.visit(&mut RedactVisitor {
+            redact: self,
+            visitor,
+        })
+    }
+
+    fn get(&self, key: Key) -> Option<Value<'_>> {
+        if self.is_redacted(&key) {
+            self.source.get(key).map(|_| self.replacement.to_value())
+        } else {
+            self.source.get(key)
+        }
+    }
+
+    fn count(&self) -> usize {
+        self.source.count()
+    }
+}
+
+/// Visit a source as a map of its key-value pairs.
+///
+/// The returned value implements `fmt::Debug`, and can be serialized with
+/// `serde` or streamed with `sval` as a map when the `kv_serde` or `kv_sval`
+/// features are enabled. Keys aren't deduplicated, so a source with repeated
+/// keys will produce a map with repeated keys. Use [`Source::dedup_last_wins`]
+/// first to avoid them.
+///
+/// # Examples
+///
+/// ```
+/// use log::kv::source::as_map;
+///
+/// let source = [("a", 1), ("b", 2)];
+///
+/// assert_eq!(r#"{"a": 1, "b": 2}"#, format!("{:?}", as_map(source)));
+/// ```
+///
+/// [`Source::dedup_last_wins`]: trait.Source.html#method.dedup_last_wins
+pub fn as_map<S>(source: S) -> AsMap<S>
+where
+    S: Source,
+{
+    AsMap(source)
+}
+
+/// A source that's visited as a map of its key-value pairs.
+///
+/// This type is returned by [`as_map`](fn.as_map.html).
+#[derive(Clone)]
+pub struct AsMap<S>(S);
+
+impl<S> Source for AsMap<S>
+where
+    S: Source,
+{
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+        self.0.visit(visitor)
+    }
+
+    fn get(&self, key: Key) -> Option<Value<'_>> {
+        self.0.get(key)
+    }
+
+    fn count(&self) -> usize {
+        self.0.count()
+    }
+}
+
+impl<S> fmt::Debug for AsMap<S>
+where
+    S: Source,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut map = f.debug_map();
+        self.0
+            .visit(&mut FnVisitor(|key: Key, value| {
+                map.entry(&key.as_str(), &value);
+                Ok(())
+            }))
+            .map_err(|_| fmt::Error)?;
+        map.finish()
+    }
+}
+
+/// Visit a source as a list of its key-value pairs.
+///
+/// Each pair is a two-element tuple of its key and value. The returned value
+/// implements `fmt::Debug`, and can be serialized with `serde` or streamed with
+/// `sval` as a sequence when the `kv_serde` or `kv_sval` features are enabled.
+///
+/// # Examples
+///
+/// ```
+/// use log::kv::source::as_list;
+///
+/// let source = [("a", 1), ("b", 2)];
+///
+/// assert_eq!(r#"[("a", 1), ("b", 2)]"#, format!("{:?}", as_list(source)));
+/// ```
+pub fn as_list<S>(source: S) -> AsList<S>
+where
+    S: Source,
+{
+    AsList(source)
+}
+
+/// A source that's visited as a list of its key-value pairs.
+///
+/// This type is returned by [`as_list`](fn.as_list.html).
+#[derive(Clone)]
+pub struct AsList<S>(S);
+
+impl<S> Source for AsList<S>
+where
+    S: Source,
+{
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+        self.0.visit(visitor)
+    }
+
+    fn get(&self, key: Key) -> Option<Value<'_>> {
+        self.0.get(key)
+    }
+
+    fn count(&self) -> usize {
+        self.0.count()
+    }
+}
+
+impl<S> fmt::Debug for AsList<S>
+where
+    S: Source,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut list = f.debug_list();
+        self.0
+            .visit(&mut FnVisitor(|key: Key, value| {
+                list.entry(&(key.as_str(), value));
+                Ok(())
+            }))
+            .map_err(|_| fmt::Error)?;
+        list.finish()
+    }
+}
+
+// A visitor that calls a closure for each pair.
+struct FnVisitor<F>(F);
+
+impl<'kvs, F> Visitor<'kvs> for FnVisitor<F>
+where
+    F: FnMut(Key<'kvs>, Value<'kvs>) -> Result<(), Error>,
+{
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+        (self.0)(key, value)
+    }
+}
+
+#[cfg(feature = "kv_serde")]
+mod serde_support {
+    use super::*;
+    use crate::kv::ErrorKind;
+
+    use serde::ser::{Error as _, Serialize, SerializeMap, SerializeSeq, Serializer};
+
+    impl<S> Serialize for AsMap<S>
+    where
+        S: Source,
+    {
+        fn serialize<SE>(&self, serializer: SE) -> Result<SE::Ok, SE::Error>
+        where
+            SE: Serializer,
+        {
+            // Errors from the serializer are stashed while visiting
+            // so they can be returned as-is
+            let mut err = None;
+
+            let mut map = serializer.serialize_map(Some(self.count()))?;
+            let r = self.0.visit(&mut FnVisitor(|key, value| {
+                map.serialize_entry(&key, &value).map_err(|e| {
+                    err = Some(e);
+                    Error::with_kind(ErrorKind::Serde, "failed to serialize a key-value pair")
+                })
+            }));
+
+            match (r, err) {
+                (Ok(()), _) => map.end(),
+                (Err(_), Some(err)) => Err(err),
+                (Err(e), None) => Err(SE::Error::custom(e)),
+            }
+        }
+    }
+
+    impl<S> Serialize for AsList<S>
+    where
+        S: Source,
+    {
+        fn serialize<SE>(&self, serializer: SE) -> Result<SE::Ok, SE::Error>
+        where
+            SE: Serializer,
+        {
+            let mut err = None;
+
+            let mut seq = serializer.serialize_seq(Some(self.count()))?;
+            let r = self.0.visit(&mut FnVisitor(|key, value| {
+                seq.serialize_element(&(key, value)).map_err(|e| {
+                    err = Some(e);
+                    Error::with_kind(ErrorKind::Serde, "failed to serialize a key-value pair")
+                })
+            }));
+
+            match (r, err) {
+                (Ok(()), _) => seq.end(),
+                (Err(_), Some(err)) => Err(err),
+                (Err(e), None) => Err(SE::Error::custom(e)),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "kv_sval")]
+mod sval_support {
+    use super::*;
+    use crate::kv::ErrorKind;
+
+    impl<S> sval::Value for AsMap<S>
+    where
+        S: Source,
+    {
+        fn stream<'sval, SV: sval::Stream<'sval> + ?Sized>(
+            &'sval self,
+            stream: &mut SV,
+        ) -> sval::Result {
+            stream.map_begin(Some(self.count()))?;
+
+            self.0
+                .visit(&mut FnVisitor(|key, value| {
+                    (|| {
+                        stream.map_key_begin()?;
+                        stream.value_computed(&key)?;
+                        stream.map_key_end()?;
+                        stream.map_value_begin()?;
+                        stream.value_computed(&value)?;
+                        stream.map_value_end()
+                    })()
+                    .map_err(|_| {
+                        Error::with_kind(ErrorKind::Sval, "failed to stream a key-value pair")
+                    })
+                }))
+                .map_err(|_| sval::Error::new())?;
+
+            stream.map_end()
+        }
+    }
+
+    impl<S> sval::Value for AsList<S>
+    where
+        S: Source,
+    {
+        fn stream<'sval, SV: sval::Stream<'sval> + ?Sized>(
+            &'sval self,
+            stream: &mut SV,
+        ) -> sval::Result {
+            stream.seq_begin(Some(self.count()))?;
+
+            self.0
+                .visit(&mut FnVisitor(|key, value| {
+                    (|| {
+                        stream.seq_value_begin()?;
+                        stream.value_computed(&(key, value))?;
+                        stream.seq_value_end()
+                    })()
+                    .map_err(|_| {
+                        Error::with_kind(ErrorKind::Sval, "failed to stream a key-value pair")
+                    })
+                }))
+                .map_err(|_| sval::Error::new())?;
+
+            stream.seq_end()
+        }
+    }
+}
+
 #[cfg(feature = "std")]
 mod std_support {
     use super::*;
@@ -453,4 +1176,267 @@ mod tests {
         let source = None::<(&str, i32)>;
         assert!(Source::get(&source, Key::from_str("a")).is_none());
     }
+
+    fn collect<'kvs>(source: &'kvs impl Source) -> [Option<(Key<'kvs>, Token)>; 4] {
+        struct Collect<'kvs> {
+            pairs: [Option<(Key<'kvs>, Token)>; 4],
+            len: usize,
+        }
+
+        impl<'kvs> Visitor<'kvs> for Collect<'kvs> {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                self.pairs[self.len] = Some((key, value.to_token()));
+                self.len += 1;
+                Ok(())
+            }
+        }
+
+        let mut collect = Collect {
+            pairs: [None, None, None, None],
+            len: 0,
+        };
+        source.visit(&mut collect).unwrap();
+        collect.pairs
+    }
+
+    fn pair(key: &'static str, value: Token) -> Option<(Key<'static>, Token)> {
+        Some((Key::from_str(key), value))
+    }
+
+    #[test]
+    fn chain() {
+        let source = ("a", 1).chain([("b", 2), ("a", 3)]);
+
+        assert_eq!(3, source.count());
+        assert_eq!(
+            [
+                pair("a", Token::I64(1)),
+                pair("b", Token::I64(2)),
+                pair("a", Token::I64(3)),
+                None
+            ],
+            collect(&source)
+        );
+        assert_eq!(
+            Token::I64(1),
+            source.get(Key::from_str("a")).unwrap().to_token()
+        );
+        assert_eq!(
+            Token::I64(2),
+            source.get(Key::from_str("b")).unwrap().to_token()
+        );
+        assert!(source.get(Key::from_str("c")).is_none());
+    }
+
+    #[test]
+    fn filter() {
+        let pairs = [("a", 1), ("b", 2), ("a", 3)];
+        let source = (&pairs as &[_]).filter(|_, v| v.to_i64() != Some(1));
+
+        assert_eq!(2, source.count());
+        assert_eq!(
+            [
+                pair("b", Token::I64(2)),
+                pair("a", Token::I64(3)),
+                None,
+                None
+            ],
+            collect(&source)
+        );
+        assert_eq!(
+            Token::I64(3),
+            source.get(Key::from_str("a")).unwrap().to_token()
+        );
+
+        let source = (&pairs as &[_]).filter(|k, _| k.as_str() != "a");
+        assert_eq!(1, source.count());
+        assert!(source.get(Key::from_str("a")).is_none());
+    }
+
+    #[test]
+    fn map_values() {
+        let source = [("a", 1), ("b", 2)].map_values(|k, v| {
+            if k.as_str() == "a" {
+                Value::from("x")
+            } else {
+                v
+            }
+        });
+
+        assert_eq!(2, source.count());
+        assert_eq!(
+            [
+                pair("a", Token::Str("x".into())),
+                pair("b", Token::I64(2)),
+                None,
+                None
+            ],
+            collect(&source)
+        );
+        assert_eq!(
+            Token::Str("x".into()),
+            source.get(Key::from_str("a")).unwrap().to_token()
+        );
+    }
+
+    #[test]
+    fn rename_keys() {
+        let source = [("a", 1), ("b", 2)].rename_keys(|k| {
+            if k.as_str() == "a" {
+                Key::from_str("c")
+            } else {
+                k
+            }
+        });
+
+        assert_eq!(2, source.count());
+        assert_eq!(
+            [
+                pair("c", Token::I64(1)),
+                pair("b", Token::I64(2)),
+                None,
+                None
+            ],
+            collect(&source)
+        );
+        assert_eq!(
+            Token::I64(1),
+            source.get(Key::from_str("c")).unwrap().to_token()
+        );
+        assert!(source.get(Key::from_str("a")).is_none());
+    }
+
+    #[test]
+    fn visit_stop() {
+        struct First(usize);
+
+        impl<'kvs> Visitor<'kvs> for First {
+            fn visit_pair(&mut self, _: Key<'kvs>, _: Value<'kvs>) -> Result<(), Error> {
+                self.0 += 1;
+                Err(Error::stop())
+            }
+        }
+
+        let mut first = First(0);
+        let err = [("a", 1), ("b", 2)].visit(&mut first).unwrap_err();
+
+        assert!(err.is_break());
+        assert_eq!(1, first.0);
+
+        let mut first = First(0);
+        let err = ("a", 1).chain(("b", 2)).visit(&mut first).unwrap_err();
+
+        assert!(err.is_break());
+        assert_eq!(1, first.0);
+    }
+
+    #[test]
+    fn dedup_last_wins() {
+        let source = [("a", 1), ("b", 2), ("a", 3), ("c", 4)].dedup_last_wins();
+
+        assert_eq!(3, source.count());
+        assert_eq!(
+            [
+                pair("b", Token::I64(2)),
+                pair("a", Token::I64(3)),
+                pair("c", Token::I64(4)),
+                None
+            ],
+            collect(&source)
+        );
+        assert_eq!(
+            Token::I64(3),
+            source.get(Key::from_str("a")).unwrap().to_token()
+        );
+
+        let context = [("request", 1), ("user", 2)];
+        let source = (&context as &[_]).chain(("user", 3)).dedup_last_wins();
+        assert_eq!(2, source.count());
+        assert_eq!(
+            Token::I64(3),
+            source.get(Key::from_str("user")).unwrap().to_token()
+        );
+    }
+
+    #[test]
+    fn redact() {
+        let source = [("user", "a"), ("password", "b")].redact(&["password"], "***");
+
+        assert_eq!(2, source.count());
+        assert_eq!(
+            [
+                pair("user", Token::Str("a".into())),
+                pair("password", Token::Str("***".into())),
+                None,
+                None
+            ],
+            collect(&source)
+        );
+        assert_eq!(
+            Token::Str("***".into()),
+            source.get(Key::from_str("password")).unwrap().to_token()
+        );
+        assert!(source.get(Key::from_str("token")).is_none());
+    }
+
+    #[test]
+    fn as_map_as_list_debug() {
+        let source = [("a", 1), ("b", 2)];
+
+        assert_eq!(r#"{"a": 1, "b": 2}"#, format!("{:?}", as_map(source)));
+        assert_eq!(r#"[("a", 1), ("b", 2)]"#, format!("{:?}", as_list(source)));
+        assert_eq!("{}", format!("{:?}", as_map(None::<(&str, i32)>)));
+    }
+
+    #[cfg(feature = "kv_serde")]
+    #[test]
+    fn as_map_as_list_serde() {
+        use serde_test::{assert_ser_tokens, Token};
+
+        let source = [("a", 1), ("b", 2)];
+
+        assert_ser_tokens(
+            &as_map(source),
+            &[
+                Token::Map { len: Some(2) },
+                Token::Str("a"),
+                Token::I64(1),
+                Token::Str("b"),
+                Token::I64(2),
+                Token::MapEnd,
+            ],
+        );
+
+        assert_ser_tokens(
+            &as_list(source),
+            &[
+                Token::Seq { len: Some(2) },
+                Token::Tuple { len: 2 },
+                Token::Str("a"),
+                Token::I64(1),
+                Token::TupleEnd,
+                Token::Tuple { len: 2 },
+                Token::Str("b"),
+                Token::I64(2),
+                Token::TupleEnd,
+                Token::SeqEnd,
+            ],
+        );
+    }
+
+    #[cfg(feature = "kv_sval")]
+    #[test]
+    fn as_map_as_list_sval() {
+        let source = [("a", 1), ("b", 2)];
+
+        // Formatting an `sval::Value` streams it
+        let map = as_map(source);
+        assert_eq!("{ \"a\": 1, \"b\": 2 }", Value::from_sval(&map).to_string());
+
+        let list = as_list(source);
+        assert_eq!(
+            "[(\"a\", 1), (\"b\", 2)]",
+            Value::from_sval(&list).to_string()
+        );
+    }
 }
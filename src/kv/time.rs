@@ -0,0 +1,115 @@
+//! Formatting for timestamps.
+
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Formats a `SystemTime` as an RFC 3339 timestamp in UTC.
+///
+/// Fractional seconds are written with millisecond, microsecond, or nanosecond
+/// precision, whichever is the shortest that doesn't lose any information.
+pub(crate) struct Rfc3339(pub(crate) SystemTime);
+
+impl fmt::Display for Rfc3339 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (secs, nanos) = match self.0.duration_since(UNIX_EPOCH) {
+            Ok(since) => (since.as_secs() as i64, since.subsec_nanos()),
+            Err(err) => {
+                let before = err.duration();
+                match before.subsec_nanos() {
+                    0 => (-(before.as_secs() as i64), 0),
+                    nanos => (-(before.as_secs() as i64) - 1, 1_000_000_000 - nanos),
+                }
+            }
+        };
+
+        let days = secs.div_euclid(86_400);
+        let secs_of_day = secs.rem_euclid(86_400);
+
+        let (year, month, day) = civil_from_days(days);
+
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            year,
+            month,
+            day,
+            secs_of_day / 3_600,
+            secs_of_day % 3_600 / 60,
+            secs_of_day % 60,
+        )?;
+
+        if nanos == 0 {
+            // No fractional seconds
+        } else if nanos % 1_000_000 == 0 {
+            write!(f, ".{:03}", nanos / 1_000_000)?;
+        } else if nanos % 1_000 == 0 {
+            write!(f, ".{:06}", nanos / 1_000)?;
+        } else {
+            write!(f, ".{:09}", nanos)?;
+        }
+
+        f.write_str("Z")
+    }
+}
+
+// Convert a number of days since the Unix epoch into a proleptic Gregorian date.
+//
+// See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn rfc3339(secs: i64, nanos: u32) -> String {
+        let ts = if secs >= 0 {
+            UNIX_EPOCH + Duration::new(secs as u64, nanos)
+        } else {
+            UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs())
+                + Duration::from_nanos(nanos as u64)
+        };
+
+        Rfc3339(ts).to_string()
+    }
+
+    #[test]
+    fn epoch() {
+        assert_eq!("1970-01-01T00:00:00Z", rfc3339(0, 0));
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!("2000-02-29T12:34:56Z", rfc3339(951_827_696, 0));
+        assert_eq!("2023-12-31T23:59:59Z", rfc3339(1_704_067_199, 0));
+        assert_eq!("2024-01-01T00:00:00Z", rfc3339(1_704_067_200, 0));
+        assert_eq!("2100-03-01T00:00:00Z", rfc3339(4_107_542_400, 0));
+    }
+
+    #[test]
+    fn before_epoch() {
+        assert_eq!("1969-12-31T23:59:59Z", rfc3339(-1, 0));
+        assert_eq!("1969-12-31T23:59:59.500Z", rfc3339(-1, 500_000_000));
+        assert_eq!("1900-01-01T00:00:00Z", rfc3339(-2_208_988_800, 0));
+    }
+
+    #[test]
+    fn fractional_seconds() {
+        assert_eq!("1970-01-01T00:00:01.500Z", rfc3339(1, 500_000_000));
+        assert_eq!("1970-01-01T00:00:01.000250Z", rfc3339(1, 250_000));
+        assert_eq!("1970-01-01T00:00:01.000000001Z", rfc3339(1, 1));
+    }
+}
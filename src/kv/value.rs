@@ -2,7 +2,9 @@
 
 use std::fmt;
 
+pub use crate::kv::bytes::{BytesFormat, FormatBytes};
 pub use crate::kv::Error;
+use crate::kv::ErrorKind;
 
 use value_bag::ValueBag;
 
@@ -10,6 +12,20 @@ use value_bag::ValueBag;
 pub trait ToValue {
     /// Perform the conversion.
     fn to_value(&self) -> Value;
+
+    // Convert a slice of this type into a value.
+    //
+    // This lets slices of `u8` be captured as bytes while
+    // slices of anything else are captured as sequences.
+    // The slice is passed both as a sequence and as itself
+    // because an unsized slice can't be made into a `dyn Seq`
+    #[doc(hidden)]
+    fn __slice_to_value<'v>(seq: &'v dyn Seq, _: &'v [Self]) -> Value<'v>
+    where
+        Self: Sized,
+    {
+        Value::from_dyn_seq(seq)
+    }
 }
 
 impl<'a, T> ToValue for &'a T
@@ -46,7 +62,7 @@ macro_rules! as_display {
 }
 
 /// Get a value from an error.
-#[cfg(feature = "kv_unstable_std")]
+#[cfg(feature = "kv_std")]
 #[macro_export]
 macro_rules! as_error {
     ($capture:expr) => {
@@ -54,7 +70,17 @@ macro_rules! as_error {
     };
 }
 
-#[cfg(feature = "kv_unstable_serde")]
+/// Get a sensitive value that's redacted when it's rendered.
+///
+/// See [`Sensitive`](kv/value/struct.Sensitive.html) for details.
+#[macro_export]
+macro_rules! as_sensitive {
+    ($capture:expr) => {
+        $crate::kv::Value::from_sensitive(&$capture)
+    };
+}
+
+#[cfg(feature = "kv_serde")]
 /// Get a value from a type implementing `serde::Serialize`.
 #[macro_export]
 macro_rules! as_serde {
@@ -64,7 +90,7 @@ macro_rules! as_serde {
 }
 
 /// Get a value from a type implementing `sval::Value`.
-#[cfg(feature = "kv_unstable_sval")]
+#[cfg(feature = "kv_sval")]
 #[macro_export]
 macro_rules! as_sval {
     ($capture:expr) => {
@@ -133,7 +159,70 @@ macro_rules! as_sval {
 /// assert_eq!(Some(42), value.to_i64());
 /// ```
 pub struct Value<'v> {
-    inner: ValueBag<'v>,
+    inner: Inner<'v>,
+}
+
+#[derive(Clone)]
+enum Inner<'v> {
+    // The kind is what visiting the bag fails with, since `value_bag`'s own
+    // errors don't say whether they came from `serde` or `sval`
+    Bag(ValueBag<'v>, ErrorKind),
+    Unit,
+    Seq(&'v dyn Seq),
+    Map(&'v dyn Map),
+    Sensitive(&'v dyn ToValue),
+    Bytes(&'v [u8]),
+    Duration(core::time::Duration),
+    #[cfg(feature = "kv_std")]
+    Timestamp(std::time::SystemTime),
+}
+
+/// The kind of a [`Value`].
+///
+/// Formatters can use [`Value::kind`] to dispatch on the type of a value once,
+/// instead of trying each of the `Value::to_*` conversions in turn.
+///
+/// [`Value`]: struct.Value.html
+/// [`Value::kind`]: struct.Value.html#method.kind
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ValueKind {
+    /// `()` or `None`.
+    Unit,
+    /// A boolean.
+    Bool,
+    /// A Unicode character.
+    Char,
+    /// An unsigned integer that fits in a `u64`.
+    U64,
+    /// A signed integer that fits in an `i64`.
+    I64,
+    /// A big unsigned integer.
+    U128,
+    /// A big signed integer.
+    I128,
+    /// A floating point.
+    F64,
+    /// A string.
+    Str,
+    /// An error.
+    Error,
+    /// A slice of bytes.
+    Bytes,
+    /// A `core::time::Duration`.
+    Duration,
+    /// A `std::time::SystemTime`.
+    Timestamp,
+    /// A sequence.
+    Seq,
+    /// A map.
+    Map,
+    /// A sensitive value.
+    ///
+    /// The kind of the underlying value isn't exposed.
+    Sensitive,
+    /// Any other value, like one captured through its `fmt::Debug` implementation.
+    Other,
 }
 
 impl<'v> Value<'v> {
@@ -150,9 +239,7 @@ impl<'v> Value<'v> {
     where
         T: fmt::Debug + 'static,
     {
-        Value {
-            inner: ValueBag::capture_debug(value),
-        }
+        Value::from_value_bag(ValueBag::capture_debug(value))
     }
 
     /// Get a value from a type implementing `std::fmt::Display`.
@@ -160,42 +247,34 @@ impl<'v> Value<'v> {
     where
         T: fmt::Display + 'static,
     {
-        Value {
-            inner: ValueBag::capture_display(value),
-        }
+        Value::from_value_bag(ValueBag::capture_display(value))
     }
 
     /// Get a value from an error.
-    #[cfg(feature = "kv_unstable_std")]
+    #[cfg(feature = "kv_std")]
     pub fn capture_error<T>(err: &'v T) -> Self
     where
         T: std::error::Error + 'static,
     {
-        Value {
-            inner: ValueBag::capture_error(err),
-        }
+        Value::from_value_bag(ValueBag::capture_error(err))
     }
 
-    #[cfg(feature = "kv_unstable_serde")]
+    #[cfg(feature = "kv_serde")]
     /// Get a value from a type implementing `serde::Serialize`.
     pub fn capture_serde<T>(value: &'v T) -> Self
     where
         T: serde::Serialize + 'static,
     {
-        Value {
-            inner: ValueBag::capture_serde1(value),
-        }
+        Value::from_value_bag_with(ValueBag::capture_serde1(value), ErrorKind::Serde)
     }
 
     /// Get a value from a type implementing `sval::Value`.
-    #[cfg(feature = "kv_unstable_sval")]
+    #[cfg(feature = "kv_sval")]
     pub fn capture_sval<T>(value: &'v T) -> Self
     where
         T: sval::Value + 'static,
     {
-        Value {
-            inner: ValueBag::capture_sval2(value),
-        }
+        Value::from_value_bag_with(ValueBag::capture_sval2(value), ErrorKind::Sval)
     }
 
     /// Get a value from a type implementing `std::fmt::Debug`.
@@ -203,9 +282,7 @@ impl<'v> Value<'v> {
     where
         T: fmt::Debug,
     {
-        Value {
-            inner: ValueBag::from_debug(value),
-        }
+        Value::from_value_bag(ValueBag::from_debug(value))
     }
 
     /// Get a value from a type implementing `std::fmt::Display`.
@@ -213,184 +290,626 @@ impl<'v> Value<'v> {
     where
         T: fmt::Display,
     {
-        Value {
-            inner: ValueBag::from_display(value),
-        }
+        Value::from_value_bag(ValueBag::from_display(value))
     }
 
     /// Get a value from a type implementing `serde::Serialize`.
-    #[cfg(feature = "kv_unstable_serde")]
+    #[cfg(feature = "kv_serde")]
     pub fn from_serde<T>(value: &'v T) -> Self
     where
         T: serde::Serialize,
     {
-        Value {
-            inner: ValueBag::from_serde1(value),
-        }
+        Value::from_value_bag_with(ValueBag::from_serde1(value), ErrorKind::Serde)
     }
 
     /// Get a value from a type implementing `sval::Value`.
-    #[cfg(feature = "kv_unstable_sval")]
+    #[cfg(feature = "kv_sval")]
     pub fn from_sval<T>(value: &'v T) -> Self
     where
         T: sval::Value,
     {
-        Value {
-            inner: ValueBag::from_sval2(value),
-        }
+        Value::from_value_bag_with(ValueBag::from_sval2(value), ErrorKind::Sval)
     }
 
     /// Get a value from a dynamic `std::fmt::Debug`.
     pub fn from_dyn_debug(value: &'v dyn fmt::Debug) -> Self {
-        Value {
-            inner: ValueBag::from_dyn_debug(value),
-        }
+        Value::from_value_bag(ValueBag::from_dyn_debug(value))
     }
 
     /// Get a value from a dynamic `std::fmt::Display`.
     pub fn from_dyn_display(value: &'v dyn fmt::Display) -> Self {
-        Value {
-            inner: ValueBag::from_dyn_display(value),
-        }
+        Value::from_value_bag(ValueBag::from_dyn_display(value))
     }
 
     /// Get a value from a dynamic error.
-    #[cfg(feature = "kv_unstable_std")]
+    #[cfg(feature = "kv_std")]
     pub fn from_dyn_error(err: &'v (dyn std::error::Error + 'static)) -> Self {
+        Value::from_value_bag(ValueBag::from_dyn_error(err))
+    }
+
+    /// Get a value from a sequence.
+    pub fn from_seq<T>(value: &'v T) -> Self
+    where
+        T: Seq,
+    {
+        Value::from_dyn_seq(value)
+    }
+
+    /// Get a value from a map.
+    pub fn from_map<T>(value: &'v T) -> Self
+    where
+        T: Map,
+    {
+        Value::from_dyn_map(value)
+    }
+
+    /// Get a value from a slice of bytes.
+    pub fn from_bytes(value: &'v [u8]) -> Self {
+        Value {
+            inner: Inner::Bytes(value),
+        }
+    }
+
+    /// Get a value from a dynamic sequence.
+    pub fn from_dyn_seq(value: &'v dyn Seq) -> Self {
+        Value {
+            inner: Inner::Seq(value),
+        }
+    }
+
+    /// Get a value from a dynamic map.
+    pub fn from_dyn_map(value: &'v dyn Map) -> Self {
+        Value {
+            inner: Inner::Map(value),
+        }
+    }
+
+    /// Get a sensitive value.
+    ///
+    /// The value is rendered as `[REDACTED]` unless it's explicitly revealed.
+    /// See [`Sensitive`](struct.Sensitive.html) for details.
+    pub fn from_sensitive<T>(value: &'v T) -> Self
+    where
+        T: ToValue,
+    {
+        Value::from_dyn_sensitive(value)
+    }
+
+    /// Get a sensitive value from a dynamic `ToValue`.
+    pub fn from_dyn_sensitive(value: &'v dyn ToValue) -> Self {
         Value {
-            inner: ValueBag::from_dyn_error(err),
+            inner: Inner::Sensitive(value),
+        }
+    }
+
+    /// Check whether this value is sensitive.
+    ///
+    /// Sensitive values are rendered as `[REDACTED]`.
+    pub fn is_sensitive(&self) -> bool {
+        matches!(self.inner, Inner::Sensitive(_))
+    }
+
+    /// Get the underlying value of a sensitive value.
+    ///
+    /// Values that aren't sensitive are returned as-is. Sinks that can be trusted
+    /// with sensitive data create a [`RevealToken`] and pass it to this method to
+    /// opt in to seeing them.
+    ///
+    /// [`RevealToken`]: struct.RevealToken.html
+    pub fn reveal_with(&self, _token: &RevealToken) -> Value<'v> {
+        match self.inner {
+            Inner::Sensitive(value) => value.to_value(),
+            ref inner => Value {
+                inner: inner.clone(),
+            },
         }
     }
 
     /// Get a value from an internal primitive.
     fn from_value_bag<T>(value: T) -> Self
+    where
+        T: Into<ValueBag<'v>>,
+    {
+        Value::from_value_bag_with(value, ErrorKind::Custom)
+    }
+
+    /// Get a value from an internal primitive that fails with `kind` when visited.
+    fn from_value_bag_with<T>(value: T, kind: ErrorKind) -> Self
     where
         T: Into<ValueBag<'v>>,
     {
         Value {
-            inner: value.into(),
+            inner: Inner::Bag(value.into(), kind),
+        }
+    }
+
+    fn bag(&self) -> Option<&ValueBag<'v>> {
+        match self.inner {
+            Inner::Bag(ref value, _) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the kind of this value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use log::kv::{Value, ValueKind};
+    ///
+    /// assert_eq!(ValueKind::U64, Value::from(1u8).kind());
+    /// assert_eq!(ValueKind::Str, Value::from("a").kind());
+    /// assert_eq!(ValueKind::Unit, Value::from_any(&()).kind());
+    /// assert_eq!(ValueKind::Other, Value::from_debug(&"a").kind());
+    /// ```
+    pub fn kind(&self) -> ValueKind {
+        struct Kind(ValueKind);
+
+        impl<'v> value_bag::visit::Visit<'v> for Kind {
+            fn visit_any(&mut self, _: ValueBag) -> Result<(), value_bag::Error> {
+                self.0 = ValueKind::Other;
+                Ok(())
+            }
+
+            fn visit_u64(&mut self, _: u64) -> Result<(), value_bag::Error> {
+                self.0 = ValueKind::U64;
+                Ok(())
+            }
+
+            fn visit_i64(&mut self, _: i64) -> Result<(), value_bag::Error> {
+                self.0 = ValueKind::I64;
+                Ok(())
+            }
+
+            fn visit_u128(&mut self, _: u128) -> Result<(), value_bag::Error> {
+                self.0 = ValueKind::U128;
+                Ok(())
+            }
+
+            fn visit_i128(&mut self, _: i128) -> Result<(), value_bag::Error> {
+                self.0 = ValueKind::I128;
+                Ok(())
+            }
+
+            fn visit_f64(&mut self, _: f64) -> Result<(), value_bag::Error> {
+                self.0 = ValueKind::F64;
+                Ok(())
+            }
+
+            fn visit_bool(&mut self, _: bool) -> Result<(), value_bag::Error> {
+                self.0 = ValueKind::Bool;
+                Ok(())
+            }
+
+            fn visit_str(&mut self, _: &str) -> Result<(), value_bag::Error> {
+                self.0 = ValueKind::Str;
+                Ok(())
+            }
+
+            fn visit_char(&mut self, _: char) -> Result<(), value_bag::Error> {
+                self.0 = ValueKind::Char;
+                Ok(())
+            }
+
+            #[cfg(feature = "kv_std")]
+            fn visit_error(
+                &mut self,
+                _: &(dyn std::error::Error + 'static),
+            ) -> Result<(), value_bag::Error> {
+                self.0 = ValueKind::Error;
+                Ok(())
+            }
+
+            #[cfg(feature = "kv_std")]
+            fn visit_borrowed_error(
+                &mut self,
+                _: &'v (dyn std::error::Error + 'static),
+            ) -> Result<(), value_bag::Error> {
+                self.0 = ValueKind::Error;
+                Ok(())
+            }
+        }
+
+        match self.inner {
+            Inner::Bag(ref value, _) => {
+                if value.is::<core::time::Duration>() {
+                    return ValueKind::Duration;
+                }
+
+                #[cfg(feature = "kv_std")]
+                {
+                    if value.is::<std::time::SystemTime>() {
+                        return ValueKind::Timestamp;
+                    }
+                }
+
+                let mut kind = Kind(ValueKind::Other);
+                let _ = value.visit(&mut kind);
+                kind.0
+            }
+            Inner::Unit => ValueKind::Unit,
+            Inner::Seq(_) => ValueKind::Seq,
+            Inner::Map(_) => ValueKind::Map,
+            Inner::Sensitive(_) => ValueKind::Sensitive,
+            Inner::Bytes(_) => ValueKind::Bytes,
+            Inner::Duration(_) => ValueKind::Duration,
+            #[cfg(feature = "kv_std")]
+            Inner::Timestamp(_) => ValueKind::Timestamp,
         }
     }
 
+    /// Check whether this value is `()` or `None`.
+    pub fn is_unit(&self) -> bool {
+        matches!(self.inner, Inner::Unit)
+    }
+
     /// Check whether this value can be downcast to `T`.
     pub fn is<T: 'static>(&self) -> bool {
-        self.inner.is::<T>()
+        self.bag().map_or(false, |value| value.is::<T>())
     }
 
     /// Try downcast this value to `T`.
     pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
-        self.inner.downcast_ref::<T>()
+        self.bag()?.downcast_ref::<T>()
     }
 
     /// Inspect this value using a simple visitor.
-    pub fn visit(&self, visitor: impl Visit<'v>) -> Result<(), Error> {
-        struct Visitor<V>(V);
+    ///
+    /// Sequences and maps are passed to [`Visit::visit_seq`] and [`Visit::visit_map`].
+    ///
+    /// [`Visit::visit_seq`]: trait.Visit.html#method.visit_seq
+    /// [`Visit::visit_map`]: trait.Visit.html#method.visit_map
+    pub fn visit(&self, mut visitor: impl Visit<'v>) -> Result<(), Error> {
+        // Errors from the visitor are kept here so they don't lose their kind
+        // when they're passed back through `value_bag`
+        struct Visitor<V> {
+            visit: V,
+            err: Option<Error>,
+        }
+
+        impl<V> Visitor<V> {
+            fn map(&mut self, r: Result<(), Error>) -> Result<(), value_bag::Error> {
+                r.map_err(|err| {
+                    self.err = Some(err);
+                    value_bag::Error::msg("error inspecting a value")
+                })
+            }
+        }
 
         impl<'v, V> value_bag::visit::Visit<'v> for Visitor<V>
         where
             V: Visit<'v>,
         {
             fn visit_any(&mut self, value: ValueBag) -> Result<(), value_bag::Error> {
-                self.0
-                    .visit_any(Value { inner: value })
-                    .map_err(Error::into_value)
+                let r = self.visit.visit_any(Value::from_value_bag(value));
+                self.map(r)
             }
 
             fn visit_u64(&mut self, value: u64) -> Result<(), value_bag::Error> {
-                self.0.visit_u64(value).map_err(Error::into_value)
+                let r = self.visit.visit_u64(value);
+                self.map(r)
             }
 
             fn visit_i64(&mut self, value: i64) -> Result<(), value_bag::Error> {
-                self.0.visit_i64(value).map_err(Error::into_value)
+                let r = self.visit.visit_i64(value);
+                self.map(r)
             }
 
             fn visit_u128(&mut self, value: u128) -> Result<(), value_bag::Error> {
-                self.0.visit_u128(value).map_err(Error::into_value)
+                let r = self.visit.visit_u128(value);
+                self.map(r)
             }
 
             fn visit_i128(&mut self, value: i128) -> Result<(), value_bag::Error> {
-                self.0.visit_i128(value).map_err(Error::into_value)
+                let r = self.visit.visit_i128(value);
+                self.map(r)
             }
 
             fn visit_f64(&mut self, value: f64) -> Result<(), value_bag::Error> {
-                self.0.visit_f64(value).map_err(Error::into_value)
+                let r = self.visit.visit_f64(value);
+                self.map(r)
             }
 
             fn visit_bool(&mut self, value: bool) -> Result<(), value_bag::Error> {
-                self.0.visit_bool(value).map_err(Error::into_value)
+                let r = self.visit.visit_bool(value);
+                self.map(r)
             }
 
             fn visit_str(&mut self, value: &str) -> Result<(), value_bag::Error> {
-                self.0.visit_str(value).map_err(Error::into_value)
+                let r = self.visit.visit_str(value);
+                self.map(r)
             }
 
             fn visit_borrowed_str(&mut self, value: &'v str) -> Result<(), value_bag::Error> {
-                self.0.visit_borrowed_str(value).map_err(Error::into_value)
+                let r = self.visit.visit_borrowed_str(value);
+                self.map(r)
             }
 
             fn visit_char(&mut self, value: char) -> Result<(), value_bag::Error> {
-                self.0.visit_char(value).map_err(Error::into_value)
+                let r = self.visit.visit_char(value);
+                self.map(r)
             }
 
-            #[cfg(feature = "kv_unstable_std")]
+            #[cfg(feature = "kv_std")]
             fn visit_error(
                 &mut self,
                 err: &(dyn std::error::Error + 'static),
             ) -> Result<(), value_bag::Error> {
-                self.0.visit_error(err).map_err(Error::into_value)
+                let r = self.visit.visit_error(err);
+                self.map(r)
             }
 
-            #[cfg(feature = "kv_unstable_std")]
+            #[cfg(feature = "kv_std")]
             fn visit_borrowed_error(
                 &mut self,
                 err: &'v (dyn std::error::Error + 'static),
             ) -> Result<(), value_bag::Error> {
-                self.0.visit_borrowed_error(err).map_err(Error::into_value)
+                let r = self.visit.visit_borrowed_error(err);
+                self.map(r)
             }
         }
 
-        self.inner
-            .visit(&mut Visitor(visitor))
-            .map_err(Error::from_value)
+        match self.inner {
+            Inner::Bag(ref value, kind) => {
+                let mut visitor = Visitor {
+                    visit: visitor,
+                    err: None,
+                };
+
+                value.visit(&mut visitor).map_err(|err| {
+                    visitor
+                        .err
+                        .take()
+                        .unwrap_or_else(|| Error::from_value(kind, err))
+                })
+            }
+            Inner::Unit => visitor.visit_any(self.to_value()),
+            Inner::Seq(seq) => visitor.visit_seq(seq),
+            Inner::Map(map) => visitor.visit_map(map),
+            Inner::Sensitive(_) => visitor.visit_any(self.to_value()),
+            Inner::Bytes(bytes) => visitor.visit_borrowed_bytes(bytes),
+            Inner::Duration(value) => visitor.visit_duration(value),
+            #[cfg(feature = "kv_std")]
+            Inner::Timestamp(value) => visitor.visit_timestamp(value),
+        }
     }
 }
 
 impl<'v> fmt::Debug for Value<'v> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt::Debug::fmt(&self.inner, f)
+        match self.inner {
+            Inner::Bag(ref value, _) => fmt::Debug::fmt(value, f),
+            Inner::Unit => f.write_str(NONE),
+            Inner::Seq(seq) => fmt_seq(seq, f, |value| value),
+            Inner::Map(map) => fmt_map(map, f, |value| value),
+            Inner::Sensitive(_) => f.write_str(REDACTED),
+            Inner::Bytes(bytes) => fmt::Display::fmt(&FormatBytes::new(bytes), f),
+            Inner::Duration(ref value) => fmt::Debug::fmt(value, f),
+            #[cfg(feature = "kv_std")]
+            Inner::Timestamp(value) => fmt::Display::fmt(&crate::kv::time::Rfc3339(value), f),
+        }
     }
 }
 
 impl<'v> fmt::Display for Value<'v> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt::Display::fmt(&self.inner, f)
+        match self.inner {
+            Inner::Bag(ref value, _) => fmt::Display::fmt(value, f),
+            Inner::Unit => f.write_str(NONE),
+            Inner::Seq(seq) => fmt_seq(seq, f, DisplayAsDebug),
+            Inner::Map(map) => fmt_map(map, f, DisplayAsDebug),
+            Inner::Sensitive(_) => f.write_str(REDACTED),
+            Inner::Bytes(bytes) => fmt::Display::fmt(&FormatBytes::new(bytes), f),
+            Inner::Duration(ref value) => fmt::Debug::fmt(value, f),
+            #[cfg(feature = "kv_std")]
+            Inner::Timestamp(value) => fmt::Display::fmt(&crate::kv::time::Rfc3339(value), f),
+        }
+    }
+}
+
+// Format a sequence like `[a, b]`, formatting its elements
+// through the `fmt::Debug` implementation of `adapt(element)`.
+fn fmt_seq<'v, T>(
+    seq: &'v dyn Seq,
+    f: &mut fmt::Formatter,
+    adapt: impl Fn(Value<'v>) -> T,
+) -> fmt::Result
+where
+    T: fmt::Debug,
+{
+    let mut list = f.debug_list();
+    seq.visit_seq(&mut FnVisitSeq(|value| {
+        list.entry(&adapt(value));
+        Ok(())
+    }))
+    .map_err(|_| fmt::Error)?;
+    list.finish()
+}
+
+// Format a map like `{k: v}`, formatting its entries
+// through the `fmt::Debug` implementation of `adapt(key)` and `adapt(value)`.
+fn fmt_map<'v, T>(
+    map: &'v dyn Map,
+    f: &mut fmt::Formatter,
+    adapt: impl Fn(Value<'v>) -> T,
+) -> fmt::Result
+where
+    T: fmt::Debug,
+{
+    let mut entries = f.debug_map();
+    map.visit_map(&mut FnVisitMap(|key, value| {
+        entries.entry(&adapt(key), &adapt(value));
+        Ok(())
+    }))
+    .map_err(|_| fmt::Error)?;
+    entries.finish()
+}
+
+struct DisplayAsDebug<'v>(Value<'v>);
+
+impl<'v> fmt::Debug for DisplayAsDebug<'v> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
     }
 }
 
-#[cfg(feature = "kv_unstable_serde")]
+#[cfg(feature = "kv_serde")]
 impl<'v> serde::Serialize for Value<'v> {
     fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        self.inner.serialize(s)
+        use serde::ser::{Error as _, SerializeMap, SerializeSeq};
+
+        // Errors from the serializer are stashed while visiting
+        // so they can be returned as-is
+        let mut err = None;
+
+        let r = match self.inner {
+            Inner::Bag(ref value, _) => return value.serialize(s),
+            Inner::Unit => return s.serialize_none(),
+            Inner::Sensitive(_) => return s.serialize_str(REDACTED),
+            Inner::Bytes(bytes) => return s.serialize_bytes(bytes),
+            Inner::Duration(value) => return s.serialize_f64(value.as_secs_f64()),
+            Inner::Timestamp(value) => {
+                return s.collect_str(&crate::kv::time::Rfc3339(value));
+            }
+            Inner::Seq(seq) => {
+                let mut s = s.serialize_seq(None)?;
+                seq.visit_seq(&mut FnVisitSeq(|value| {
+                    s.serialize_element(&value).map_err(|e| {
+                        err = Some(e);
+                        Error::with_kind(ErrorKind::Serde, "failed to serialize a sequence element")
+                    })
+                }))
+                .map(|_| s)
+                .and_then(|s| {
+                    s.end().map_err(|e| {
+                        err = Some(e);
+                        Error::with_kind(ErrorKind::Serde, "failed to serialize a sequence")
+                    })
+                })
+            }
+            Inner::Map(map) => {
+                let mut s = s.serialize_map(None)?;
+                map.visit_map(&mut FnVisitMap(|key, value| {
+                    s.serialize_entry(&key, &value).map_err(|e| {
+                        err = Some(e);
+                        Error::with_kind(ErrorKind::Serde, "failed to serialize a map entry")
+                    })
+                }))
+                .map(|_| s)
+                .and_then(|s| {
+                    s.end().map_err(|e| {
+                        err = Some(e);
+                        Error::with_kind(ErrorKind::Serde, "failed to serialize a map")
+                    })
+                })
+            }
+        };
+
+        match (r, err) {
+            (Ok(ok), _) => Ok(ok),
+            (Err(_), Some(err)) => Err(err),
+            (Err(e), None) => Err(S::Error::custom(e)),
+        }
     }
 }
 
-#[cfg(feature = "kv_unstable_sval")]
+#[cfg(feature = "kv_sval")]
 impl<'v> sval::Value for Value<'v> {
     fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(&'sval self, stream: &mut S) -> sval::Result {
-        sval::Value::stream(&self.inner, stream)
+        match self.inner {
+            Inner::Bag(ref value, _) => sval::Value::stream(value, stream),
+            Inner::Unit => stream.null(),
+            Inner::Seq(seq) => stream_seq(seq, stream),
+            Inner::Map(map) => stream_map(map, stream),
+            Inner::Sensitive(_) => stream.value(REDACTED),
+            Inner::Bytes(bytes) => stream.value(sval::BinarySlice::new(bytes)),
+            Inner::Duration(value) => stream.f64(value.as_secs_f64()),
+            #[cfg(feature = "kv_std")]
+            Inner::Timestamp(value) => stream_timestamp(value, stream),
+        }
     }
 }
 
-#[cfg(feature = "kv_unstable_sval")]
+#[cfg(feature = "kv_sval")]
 impl<'v> sval_ref::ValueRef<'v> for Value<'v> {
     fn stream_ref<S: sval::Stream<'v> + ?Sized>(&self, stream: &mut S) -> sval::Result {
-        sval_ref::ValueRef::stream_ref(&self.inner, stream)
+        match self.inner {
+            Inner::Bag(ref value, _) => sval_ref::ValueRef::stream_ref(value, stream),
+            Inner::Unit => stream.null(),
+            Inner::Seq(seq) => stream_seq(seq, stream),
+            Inner::Map(map) => stream_map(map, stream),
+            Inner::Sensitive(_) => stream.value(REDACTED),
+            Inner::Bytes(bytes) => stream.value(sval::BinarySlice::new(bytes)),
+            Inner::Duration(value) => stream.f64(value.as_secs_f64()),
+            #[cfg(feature = "kv_std")]
+            Inner::Timestamp(value) => stream_timestamp(value, stream),
+        }
     }
 }
 
+#[cfg(all(feature = "kv_sval", feature = "kv_std"))]
+fn stream_timestamp<'sval, S: sval::Stream<'sval> + ?Sized>(
+    value: std::time::SystemTime,
+    stream: &mut S,
+) -> sval::Result {
+    use std::fmt::Write as _;
+
+    // RFC 3339 timestamps are at most 35 bytes long, but years
+    // outside of 0-9999 can make them longer so they're buffered
+    let mut buf = String::new();
+    write!(buf, "{}", crate::kv::time::Rfc3339(value)).map_err(|_| sval::Error::new())?;
+
+    stream.text_begin(Some(buf.len()))?;
+    stream.text_fragment_computed(&buf)?;
+    stream.text_end()
+}
+
+#[cfg(feature = "kv_sval")]
+fn stream_seq<'sval, S: sval::Stream<'sval> + ?Sized>(
+    seq: &dyn Seq,
+    stream: &mut S,
+) -> sval::Result {
+    stream.seq_begin(None)?;
+
+    seq.visit_seq(&mut FnVisitSeq(|value| {
+        (|| {
+            stream.seq_value_begin()?;
+            stream.value_computed(&value)?;
+            stream.seq_value_end()
+        })()
+        .map_err(|_| Error::with_kind(ErrorKind::Sval, "failed to stream a sequence element"))
+    }))
+    .map_err(|_| sval::Error::new())?;
+
+    stream.seq_end()
+}
+
+#[cfg(feature = "kv_sval")]
+fn stream_map<'sval, S: sval::Stream<'sval> + ?Sized>(
+    map: &dyn Map,
+    stream: &mut S,
+) -> sval::Result {
+    stream.map_begin(None)?;
+
+    map.visit_map(&mut FnVisitMap(|key, value| {
+        (|| {
+            stream.map_key_begin()?;
+            stream.value_computed(&key)?;
+            stream.map_key_end()?;
+            stream.map_value_begin()?;
+            stream.value_computed(&value)?;
+            stream.map_value_end()
+        })()
+        .map_err(|_| Error::with_kind(ErrorKind::Sval, "failed to stream a map entry"))
+    }))
+    .map_err(|_| sval::Error::new())?;
+
+    stream.map_end()
+}
+
 impl ToValue for str {
     fn to_value(&self) -> Value {
         Value::from(self)
@@ -453,9 +972,45 @@ impl<'v> From<&'v std::num::NonZeroI128> for Value<'v> {
     }
 }
 
+impl ToValue for u8 {
+    fn to_value(&self) -> Value<'_> {
+        Value::from(*self)
+    }
+
+    fn __slice_to_value<'v>(_: &'v dyn Seq, values: &'v [Self]) -> Value<'v> {
+        Value::from_bytes(values)
+    }
+}
+
+impl<'v> From<u8> for Value<'v> {
+    fn from(value: u8) -> Self {
+        Value::from_value_bag(value)
+    }
+}
+
+impl<'v> From<&'v [u8]> for Value<'v> {
+    fn from(value: &'v [u8]) -> Self {
+        Value::from_bytes(value)
+    }
+}
+
+impl ToValue for core::time::Duration {
+    fn to_value(&self) -> Value<'_> {
+        Value::from(*self)
+    }
+}
+
+impl<'v> From<core::time::Duration> for Value<'v> {
+    fn from(value: core::time::Duration) -> Self {
+        Value {
+            inner: Inner::Duration(value),
+        }
+    }
+}
+
 impl ToValue for () {
     fn to_value(&self) -> Value {
-        Value::from_value_bag(())
+        Value { inner: Inner::Unit }
     }
 }
 
@@ -466,7 +1021,7 @@ where
     fn to_value(&self) -> Value {
         match *self {
             Some(ref value) => value.to_value(),
-            None => Value::from_value_bag(()),
+            None => Value { inner: Inner::Unit },
         }
     }
 }
@@ -513,14 +1068,14 @@ macro_rules! impl_value_to_primitive {
             $(
                 #[doc = $doc]
                 pub fn $into_name(&self) -> Option<$into_ty> {
-                    self.inner.$into_name()
+                    self.bag()?.$into_name()
                 }
             )*
         }
     }
 }
 
-impl_to_value_primitive![usize, u8, u16, u32, u64, isize, i8, i16, i32, i64, f32, f64, char, bool,];
+impl_to_value_primitive![usize, u16, u32, u64, isize, i8, i16, i32, i64, f32, f64, char, bool,];
 
 #[rustfmt::skip]
 impl_to_value_nonzero_primitive![
@@ -547,20 +1102,46 @@ impl_value_to_primitive![
 
 impl<'v> Value<'v> {
     /// Try convert this value into an error.
-    #[cfg(feature = "kv_unstable_std")]
+    #[cfg(feature = "kv_std")]
     pub fn to_borrowed_error(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        self.inner.to_borrowed_error()
+        self.bag()?.to_borrowed_error()
     }
 
     /// Try convert this value into a borrowed string.
     pub fn to_borrowed_str(&self) -> Option<&str> {
-        self.inner.to_borrowed_str()
+        self.bag()?.to_borrowed_str()
+    }
+
+    /// Try convert this value into a borrowed slice of bytes.
+    pub fn to_borrowed_bytes(&self) -> Option<&[u8]> {
+        match self.inner {
+            Inner::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// Try convert this value into a `Duration`.
+    pub fn to_duration(&self) -> Option<core::time::Duration> {
+        match self.inner {
+            Inner::Duration(value) => Some(value),
+            _ => self.downcast_ref::<core::time::Duration>().copied(),
+        }
+    }
+
+    /// Try convert this value into a `SystemTime`.
+    #[cfg(feature = "kv_std")]
+    pub fn to_timestamp(&self) -> Option<std::time::SystemTime> {
+        match self.inner {
+            Inner::Timestamp(value) => Some(value),
+            _ => self.downcast_ref::<std::time::SystemTime>().copied(),
+        }
     }
 }
 
-#[cfg(feature = "kv_unstable_std")]
+#[cfg(feature = "kv_std")]
 mod std_support {
     use std::borrow::Cow;
+    use std::collections::{BTreeMap, HashMap};
     use std::rc::Rc;
     use std::sync::Arc;
 
@@ -593,6 +1174,20 @@ mod std_support {
         }
     }
 
+    impl ToValue for std::time::SystemTime {
+        fn to_value(&self) -> Value<'_> {
+            Value::from(*self)
+        }
+    }
+
+    impl<'v> From<std::time::SystemTime> for Value<'v> {
+        fn from(value: std::time::SystemTime) -> Self {
+            Value {
+                inner: Inner::Timestamp(value),
+            }
+        }
+    }
+
     impl ToValue for String {
         fn to_value(&self) -> Value {
             Value::from(&**self)
@@ -608,7 +1203,7 @@ mod std_support {
     impl<'v> Value<'v> {
         /// Try convert this value into a string.
         pub fn to_str(&self) -> Option<Cow<'v, str>> {
-            self.inner.to_str()
+            self.bag()?.to_str()
         }
     }
 
@@ -617,44 +1212,350 @@ mod std_support {
             Value::from(&**v)
         }
     }
-}
-
-/// A visitor for a `Value`.
-pub trait Visit<'v> {
-    /// Visit a `Value`.
-    ///
-    /// This is the only required method on `Visit` and acts as a fallback for any
-    /// more specific methods that aren't overridden.
-    /// The `Value` may be formatted using its `fmt::Debug` or `fmt::Display` implementation,
-    /// or serialized using its `sval::Value` or `serde::Serialize` implementation.
-    fn visit_any(&mut self, value: Value) -> Result<(), Error>;
 
-    /// Visit an unsigned integer.
-    fn visit_u64(&mut self, value: u64) -> Result<(), Error> {
-        self.visit_any(value.into())
+    impl<T> Seq for Vec<T>
+    where
+        T: ToValue,
+    {
+        fn visit_seq<'v>(&'v self, visitor: &mut dyn VisitSeq<'v>) -> Result<(), Error> {
+            self[..].visit_seq(visitor)
+        }
     }
 
-    /// Visit a signed integer.
-    fn visit_i64(&mut self, value: i64) -> Result<(), Error> {
-        self.visit_any(value.into())
+    impl<T> ToValue for Vec<T>
+    where
+        T: ToValue,
+    {
+        fn to_value(&self) -> Value<'_> {
+            T::__slice_to_value(self, self)
+        }
     }
 
-    /// Visit a big unsigned integer.
-    fn visit_u128(&mut self, value: u128) -> Result<(), Error> {
-        self.visit_any((&value).into())
+    impl<K, V> Map for BTreeMap<K, V>
+    where
+        K: ToValue,
+        V: ToValue,
+    {
+        fn visit_map<'v>(&'v self, visitor: &mut dyn VisitMap<'v>) -> Result<(), Error> {
+            for (key, value) in self {
+                visitor.visit_entry(key.to_value(), value.to_value())?;
+            }
+
+            Ok(())
+        }
     }
 
-    /// Visit a big signed integer.
-    fn visit_i128(&mut self, value: i128) -> Result<(), Error> {
-        self.visit_any((&value).into())
+    impl<K, V> ToValue for BTreeMap<K, V>
+    where
+        K: ToValue,
+        V: ToValue,
+    {
+        fn to_value(&self) -> Value<'_> {
+            Value::from_map(self)
+        }
     }
 
-    /// Visit a floating point.
-    fn visit_f64(&mut self, value: f64) -> Result<(), Error> {
-        self.visit_any(value.into())
+    impl<K, V, S> Map for HashMap<K, V, S>
+    where
+        K: ToValue,
+        V: ToValue,
+    {
+        fn visit_map<'v>(&'v self, visitor: &mut dyn VisitMap<'v>) -> Result<(), Error> {
+            for (key, value) in self {
+                visitor.visit_entry(key.to_value(), value.to_value())?;
+            }
+
+            Ok(())
+        }
     }
 
-    /// Visit a boolean.
+    impl<K, V, S> ToValue for HashMap<K, V, S>
+    where
+        K: ToValue,
+        V: ToValue,
+    {
+        fn to_value(&self) -> Value<'_> {
+            Value::from_map(self)
+        }
+    }
+}
+
+// How sensitive values are rendered
+const REDACTED: &str = "[REDACTED]";
+
+// How `()` and `None` are rendered
+const NONE: &str = "None";
+
+/// Permission to reveal sensitive values.
+///
+/// A sink that can be trusted with sensitive data creates a token and passes it
+/// to [`Value::reveal_with`] at each place it reads a value. Revealing is never
+/// implied, so code that doesn't hold a token only ever sees `[REDACTED]`.
+///
+/// [`Value::reveal_with`]: struct.Value.html#method.reveal_with
+#[derive(Clone, Copy, Debug)]
+pub struct RevealToken(());
+
+// A `Default` token would let revealing happen implicitly
+#[allow(clippy::new_without_default)]
+impl RevealToken {
+    /// Create a token that reveals sensitive values.
+    pub const fn new() -> Self {
+        RevealToken(())
+    }
+}
+
+/// A value that must not appear in logs.
+///
+/// A `Sensitive` is rendered as `[REDACTED]` through its `Debug` and `Display`
+/// implementations, and through the [`Value`] it's converted into. That value
+/// is also redacted when it's visited, serialized with `serde`, or streamed with
+/// `sval`. Visitors see it through [`Visit::visit_any`].
+///
+/// Sinks that can be trusted with sensitive data can use [`Value::reveal_with`]
+/// and a [`RevealToken`] to get the underlying value.
+///
+/// # Examples
+///
+/// ```
+/// use log::kv::{RevealToken, Sensitive, ToValue};
+///
+/// let password = Sensitive::new("hunter2");
+///
+/// assert_eq!("[REDACTED]", password.to_value().to_string());
+///
+/// let token = RevealToken::new();
+/// assert_eq!("hunter2", password.to_value().reveal_with(&token).to_string());
+/// ```
+///
+/// [`Value`]: struct.Value.html
+/// [`Visit::visit_any`]: trait.Visit.html#tymethod.visit_any
+/// [`Value::reveal_with`]: struct.Value.html#method.reveal_with
+/// [`RevealToken`]: struct.RevealToken.html
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Sensitive<T>(T);
+
+impl<T> Sensitive<T> {
+    /// Mark a value as sensitive.
+    pub const fn new(value: T) -> Self {
+        Sensitive(value)
+    }
+
+    /// Get a reference to the underlying value.
+    pub fn get(&self) -> &T {
+        &self.0
+    }
+
+    /// Get the underlying value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Sensitive<T> {
+    fn from(value: T) -> Self {
+        Sensitive(value)
+    }
+}
+
+impl<T> fmt::Debug for Sensitive<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T> fmt::Display for Sensitive<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T> ToValue for Sensitive<T>
+where
+    T: ToValue,
+{
+    fn to_value(&self) -> Value<'_> {
+        Value::from_sensitive(&self.0)
+    }
+}
+
+/// A sequence of values.
+///
+/// A `Seq` can be captured as a [`Value`] using [`Value::from_seq`]. It's
+/// implemented for arrays, slices, and `Vec`s of values, so sequences can be
+/// inspected with [`Visit::visit_seq`] without requiring `serde` or `sval`.
+///
+/// # Examples
+///
+/// ```
+/// use log::kv::value::{Error, Seq, ToValue, VisitSeq};
+///
+/// struct Pair(u64, u64);
+///
+/// impl Seq for Pair {
+///     fn visit_seq<'v>(&'v self, visitor: &mut dyn VisitSeq<'v>) -> Result<(), Error> {
+///         visitor.visit_element(self.0.to_value())?;
+///         visitor.visit_element(self.1.to_value())
+///     }
+/// }
+///
+/// let pair = Pair(1, 2);
+///
+/// assert_eq!("[1, 2]", log::kv::Value::from_seq(&pair).to_string());
+/// ```
+///
+/// [`Value`]: struct.Value.html
+/// [`Value::from_seq`]: struct.Value.html#method.from_seq
+/// [`Visit::visit_seq`]: trait.Visit.html#method.visit_seq
+pub trait Seq {
+    /// Visit each element of the sequence, in order.
+    fn visit_seq<'v>(&'v self, visitor: &mut dyn VisitSeq<'v>) -> Result<(), Error>;
+}
+
+/// A visitor for the elements of a [`Seq`](trait.Seq.html).
+pub trait VisitSeq<'v> {
+    /// Visit an element of the sequence.
+    fn visit_element(&mut self, value: Value<'v>) -> Result<(), Error>;
+}
+
+/// A map of keys to values.
+///
+/// A `Map` can be captured as a [`Value`] using [`Value::from_map`]. It's
+/// implemented for `BTreeMap`s and `HashMap`s of values, so maps can be
+/// inspected with [`Visit::visit_map`] without requiring `serde` or `sval`.
+///
+/// [`Value`]: struct.Value.html
+/// [`Value::from_map`]: struct.Value.html#method.from_map
+/// [`Visit::visit_map`]: trait.Visit.html#method.visit_map
+pub trait Map {
+    /// Visit each entry of the map.
+    fn visit_map<'v>(&'v self, visitor: &mut dyn VisitMap<'v>) -> Result<(), Error>;
+}
+
+/// A visitor for the entries of a [`Map`](trait.Map.html).
+pub trait VisitMap<'v> {
+    /// Visit an entry of the map.
+    fn visit_entry(&mut self, key: Value<'v>, value: Value<'v>) -> Result<(), Error>;
+}
+
+impl<T> Seq for &T
+where
+    T: Seq + ?Sized,
+{
+    fn visit_seq<'v>(&'v self, visitor: &mut dyn VisitSeq<'v>) -> Result<(), Error> {
+        (**self).visit_seq(visitor)
+    }
+}
+
+impl<T> Map for &T
+where
+    T: Map + ?Sized,
+{
+    fn visit_map<'v>(&'v self, visitor: &mut dyn VisitMap<'v>) -> Result<(), Error> {
+        (**self).visit_map(visitor)
+    }
+}
+
+impl<T> Seq for [T]
+where
+    T: ToValue,
+{
+    fn visit_seq<'v>(&'v self, visitor: &mut dyn VisitSeq<'v>) -> Result<(), Error> {
+        for value in self {
+            visitor.visit_element(value.to_value())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T, const N: usize> Seq for [T; N]
+where
+    T: ToValue,
+{
+    fn visit_seq<'v>(&'v self, visitor: &mut dyn VisitSeq<'v>) -> Result<(), Error> {
+        self[..].visit_seq(visitor)
+    }
+}
+
+impl<T, const N: usize> ToValue for [T; N]
+where
+    T: ToValue,
+{
+    fn to_value(&self) -> Value<'_> {
+        T::__slice_to_value(self, self)
+    }
+}
+
+// A slice can't be captured directly because it's unsized,
+// but a reference to one can
+impl<T> ToValue for &[T]
+where
+    T: ToValue,
+{
+    fn to_value(&self) -> Value<'_> {
+        T::__slice_to_value(self, self)
+    }
+}
+
+struct FnVisitSeq<F>(F);
+
+impl<'v, F> VisitSeq<'v> for FnVisitSeq<F>
+where
+    F: FnMut(Value<'v>) -> Result<(), Error>,
+{
+    fn visit_element(&mut self, value: Value<'v>) -> Result<(), Error> {
+        (self.0)(value)
+    }
+}
+
+struct FnVisitMap<F>(F);
+
+impl<'v, F> VisitMap<'v> for FnVisitMap<F>
+where
+    F: FnMut(Value<'v>, Value<'v>) -> Result<(), Error>,
+{
+    fn visit_entry(&mut self, key: Value<'v>, value: Value<'v>) -> Result<(), Error> {
+        (self.0)(key, value)
+    }
+}
+
+/// A visitor for a `Value`.
+pub trait Visit<'v> {
+    /// Visit a `Value`.
+    ///
+    /// This is the only required method on `Visit` and acts as a fallback for any
+    /// more specific methods that aren't overridden.
+    /// The `Value` may be formatted using its `fmt::Debug` or `fmt::Display` implementation,
+    /// or serialized using its `sval::Value` or `serde::Serialize` implementation.
+    fn visit_any(&mut self, value: Value) -> Result<(), Error>;
+
+    /// Visit an unsigned integer.
+    fn visit_u64(&mut self, value: u64) -> Result<(), Error> {
+        self.visit_any(value.into())
+    }
+
+    /// Visit a signed integer.
+    fn visit_i64(&mut self, value: i64) -> Result<(), Error> {
+        self.visit_any(value.into())
+    }
+
+    /// Visit a big unsigned integer.
+    fn visit_u128(&mut self, value: u128) -> Result<(), Error> {
+        self.visit_any((&value).into())
+    }
+
+    /// Visit a big signed integer.
+    fn visit_i128(&mut self, value: i128) -> Result<(), Error> {
+        self.visit_any((&value).into())
+    }
+
+    /// Visit a floating point.
+    fn visit_f64(&mut self, value: f64) -> Result<(), Error> {
+        self.visit_any(value.into())
+    }
+
+    /// Visit a boolean.
     fn visit_bool(&mut self, value: bool) -> Result<(), Error> {
         self.visit_any(value.into())
     }
@@ -675,20 +1576,59 @@ pub trait Visit<'v> {
         self.visit_str(&*value.encode_utf8(&mut b))
     }
 
+    /// Visit a slice of bytes.
+    fn visit_bytes(&mut self, value: &[u8]) -> Result<(), Error> {
+        self.visit_any(Value::from_bytes(value))
+    }
+
+    /// Visit a slice of bytes.
+    fn visit_borrowed_bytes(&mut self, value: &'v [u8]) -> Result<(), Error> {
+        self.visit_bytes(value)
+    }
+
+    /// Visit a duration.
+    fn visit_duration(&mut self, value: core::time::Duration) -> Result<(), Error> {
+        self.visit_any(value.into())
+    }
+
+    /// Visit a point in time.
+    #[cfg(feature = "kv_std")]
+    fn visit_timestamp(&mut self, value: std::time::SystemTime) -> Result<(), Error> {
+        self.visit_any(value.into())
+    }
+
     /// Visit an error.
-    #[cfg(feature = "kv_unstable_std")]
+    #[cfg(feature = "kv_std")]
     fn visit_error(&mut self, err: &(dyn std::error::Error + 'static)) -> Result<(), Error> {
         self.visit_any(Value::from_dyn_error(err))
     }
 
     /// Visit an error.
-    #[cfg(feature = "kv_unstable_std")]
+    #[cfg(feature = "kv_std")]
     fn visit_borrowed_error(
         &mut self,
         err: &'v (dyn std::error::Error + 'static),
     ) -> Result<(), Error> {
         self.visit_any(Value::from_dyn_error(err))
     }
+
+    /// Visit a sequence.
+    ///
+    /// The elements of the sequence can be visited with [`Seq::visit_seq`].
+    ///
+    /// [`Seq::visit_seq`]: trait.Seq.html#tymethod.visit_seq
+    fn visit_seq(&mut self, seq: &'v dyn Seq) -> Result<(), Error> {
+        self.visit_any(Value::from_dyn_seq(seq))
+    }
+
+    /// Visit a map.
+    ///
+    /// The entries of the map can be visited with [`Map::visit_map`].
+    ///
+    /// [`Map::visit_map`]: trait.Map.html#tymethod.visit_map
+    fn visit_map(&mut self, map: &'v dyn Map) -> Result<(), Error> {
+        self.visit_any(Value::from_dyn_map(map))
+    }
 }
 
 impl<'a, 'v, T: ?Sized> Visit<'v> for &'a mut T
@@ -735,18 +1675,43 @@ where
         (**self).visit_char(value)
     }
 
-    #[cfg(feature = "kv_unstable_std")]
+    fn visit_bytes(&mut self, value: &[u8]) -> Result<(), Error> {
+        (**self).visit_bytes(value)
+    }
+
+    fn visit_borrowed_bytes(&mut self, value: &'v [u8]) -> Result<(), Error> {
+        (**self).visit_borrowed_bytes(value)
+    }
+
+    fn visit_duration(&mut self, value: core::time::Duration) -> Result<(), Error> {
+        (**self).visit_duration(value)
+    }
+
+    #[cfg(feature = "kv_std")]
+    fn visit_timestamp(&mut self, value: std::time::SystemTime) -> Result<(), Error> {
+        (**self).visit_timestamp(value)
+    }
+
+    #[cfg(feature = "kv_std")]
     fn visit_error(&mut self, err: &(dyn std::error::Error + 'static)) -> Result<(), Error> {
         (**self).visit_error(err)
     }
 
-    #[cfg(feature = "kv_unstable_std")]
+    #[cfg(feature = "kv_std")]
     fn visit_borrowed_error(
         &mut self,
         err: &'v (dyn std::error::Error + 'static),
     ) -> Result<(), Error> {
         (**self).visit_borrowed_error(err)
     }
+
+    fn visit_seq(&mut self, seq: &'v dyn Seq) -> Result<(), Error> {
+        (**self).visit_seq(seq)
+    }
+
+    fn visit_map(&mut self, map: &'v dyn Map) -> Result<(), Error> {
+        (**self).visit_map(map)
+    }
 }
 
 #[cfg(test)]
@@ -756,7 +1721,13 @@ pub(crate) mod tests {
 
     impl<'v> Value<'v> {
         pub(crate) fn to_token(&self) -> Token {
-            self.inner.to_test_token()
+            match self.inner {
+                Inner::Unit => Token::None,
+                _ => self
+                    .bag()
+                    .expect("sequences and maps have no test token")
+                    .to_test_token(),
+            }
         }
     }
 
@@ -817,7 +1788,7 @@ pub(crate) mod tests {
         assert!(Value::from_debug(&42).to_u64().is_none());
     }
 
-    #[cfg(feature = "kv_unstable_std")]
+    #[cfg(feature = "kv_std")]
     #[test]
     fn test_capture_error() {
         let err = std::io::Error::from(std::io::ErrorKind::Other);
@@ -826,7 +1797,7 @@ pub(crate) mod tests {
         assert!(Value::from_dyn_error(&err).to_borrowed_error().is_some());
     }
 
-    #[cfg(feature = "kv_unstable_serde")]
+    #[cfg(feature = "kv_serde")]
     #[test]
     fn test_capture_serde() {
         assert_eq!(Some(42u64), Value::capture_serde(&42).to_u64());
@@ -834,7 +1805,7 @@ pub(crate) mod tests {
         assert_eq!(Some(42u64), Value::from_serde(&42).to_u64());
     }
 
-    #[cfg(feature = "kv_unstable_sval")]
+    #[cfg(feature = "kv_sval")]
     #[test]
     fn test_capture_sval() {
         assert_eq!(Some(42u64), Value::capture_sval(&42).to_u64());
@@ -898,7 +1869,7 @@ pub(crate) mod tests {
         for v in str() {
             assert!(v.to_borrowed_str().is_some());
 
-            #[cfg(feature = "kv_unstable_std")]
+            #[cfg(feature = "kv_std")]
             assert!(v.to_str().is_some());
         }
 
@@ -907,13 +1878,13 @@ pub(crate) mod tests {
 
         assert!(v.to_borrowed_str().is_some());
 
-        #[cfg(feature = "kv_unstable_std")]
+        #[cfg(feature = "kv_std")]
         assert!(v.to_str().is_some());
 
         for v in unsigned().chain(signed()).chain(float()).chain(bool()) {
             assert!(v.to_borrowed_str().is_none());
 
-            #[cfg(feature = "kv_unstable_std")]
+            #[cfg(feature = "kv_std")]
             assert!(v.to_str().is_none());
         }
     }
@@ -950,6 +1921,241 @@ pub(crate) mod tests {
         }
     }
 
+    #[test]
+    fn test_visit_error_kind() {
+        struct Stop;
+
+        impl<'v> Visit<'v> for Stop {
+            fn visit_any(&mut self, _: Value) -> Result<(), Error> {
+                Err(Error::stop())
+            }
+        }
+
+        for v in unsigned().chain(signed()).chain(str()).chain(bool()) {
+            let err = v.visit(Stop).unwrap_err();
+
+            assert!(err.is_break());
+        }
+    }
+
+    #[test]
+    fn test_kind() {
+        for v in unsigned() {
+            assert_eq!(ValueKind::U64, v.kind());
+        }
+
+        for v in signed() {
+            assert_eq!(ValueKind::I64, v.kind());
+        }
+
+        for v in float() {
+            assert_eq!(ValueKind::F64, v.kind());
+        }
+
+        for v in bool() {
+            assert_eq!(ValueKind::Bool, v.kind());
+        }
+
+        for v in str() {
+            assert_eq!(ValueKind::Str, v.kind());
+        }
+
+        for v in char() {
+            assert_eq!(ValueKind::Char, v.kind());
+        }
+
+        assert_eq!(ValueKind::U128, Value::from(&u128::MAX).kind());
+        assert_eq!(ValueKind::I128, Value::from(&i128::MIN).kind());
+        assert_eq!(ValueKind::Unit, ().to_value().kind());
+        assert_eq!(ValueKind::Unit, None::<i32>.to_value().kind());
+        assert_eq!(ValueKind::Bytes, Value::from_bytes(&[1, 2]).kind());
+        assert_eq!(ValueKind::Seq, [1, 2].to_value().kind());
+        assert_eq!(ValueKind::Sensitive, Value::from_sensitive(&1).kind());
+        assert_eq!(ValueKind::Other, Value::from_debug(&1).kind());
+        assert_eq!(
+            ValueKind::Duration,
+            Value::capture_debug(&std::time::Duration::from_secs(1)).kind()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "kv_std")]
+    fn test_kind_std() {
+        use std::collections::BTreeMap;
+
+        let map = BTreeMap::from([(1, true)]);
+        let err = std::io::Error::from(std::io::ErrorKind::Other);
+        let now = std::time::SystemTime::now();
+
+        assert_eq!(ValueKind::Map, map.to_value().kind());
+        assert_eq!(ValueKind::Error, Value::from_dyn_error(&err).kind());
+        assert_eq!(ValueKind::Timestamp, Value::capture_debug(&now).kind());
+    }
+
+    #[test]
+    fn test_is_unit() {
+        assert!(().to_value().is_unit());
+        assert!(None::<i32>.to_value().is_unit());
+
+        assert!(!Some(1).to_value().is_unit());
+        assert!(!Value::from("").is_unit());
+    }
+
+    #[test]
+    fn test_to_bytes() {
+        let value = Value::from_bytes(&[1, 2, 3]);
+
+        assert_eq!(Some(&[1u8, 2, 3][..]), value.to_borrowed_bytes());
+        assert_eq!("010203", format!("{:?}", value));
+        assert_eq!("010203", value.to_string());
+
+        assert!(Value::from("a").to_borrowed_bytes().is_none());
+        assert!([1u16, 2, 3].to_value().to_borrowed_bytes().is_none());
+    }
+
+    #[test]
+    fn test_bytes_to_value() {
+        let bytes = [1u8, 2, 3];
+
+        assert_eq!(ValueKind::Bytes, bytes.to_value().kind());
+        assert_eq!(ValueKind::Bytes, (&bytes as &[u8]).to_value().kind());
+        assert_eq!(ValueKind::Bytes, Value::from(&bytes as &[u8]).kind());
+        assert_eq!(Some(&bytes[..]), bytes.to_value().to_borrowed_bytes());
+
+        // Slices of anything else are still sequences
+        assert_eq!(ValueKind::Seq, [1u16, 2, 3].to_value().kind());
+        assert_eq!(ValueKind::Seq, (&[1i8, 2, 3] as &[i8]).to_value().kind());
+    }
+
+    #[test]
+    #[cfg(feature = "kv_std")]
+    fn test_vec_bytes_to_value() {
+        let bytes = vec![1u8, 2, 3];
+
+        assert_eq!(ValueKind::Bytes, bytes.to_value().kind());
+        assert_eq!(ValueKind::Seq, vec![1u16].to_value().kind());
+    }
+
+    #[test]
+    fn test_visit_bytes() {
+        struct Extract<'v>(Option<&'v [u8]>);
+
+        impl<'v> Visit<'v> for Extract<'v> {
+            fn visit_any(&mut self, value: Value) -> Result<(), Error> {
+                unimplemented!("unexpected value: {value:?}")
+            }
+
+            fn visit_borrowed_bytes(&mut self, value: &'v [u8]) -> Result<(), Error> {
+                self.0 = Some(value);
+                Ok(())
+            }
+        }
+
+        let bytes = [1u8, 2, 3];
+
+        let mut extract = Extract(None);
+        bytes.to_value().visit(&mut extract).unwrap();
+        assert_eq!(Some(&bytes[..]), extract.0);
+    }
+
+    #[test]
+    fn test_to_duration_timestamp() {
+        let duration = std::time::Duration::from_millis(1500);
+
+        assert_eq!(
+            Some(duration),
+            Value::capture_debug(&duration).to_duration()
+        );
+        assert!(Value::from(1500u64).to_duration().is_none());
+
+        #[cfg(feature = "kv_std")]
+        {
+            let now = std::time::SystemTime::now();
+
+            assert_eq!(Some(now), Value::capture_debug(&now).to_timestamp());
+            assert!(Value::capture_debug(&duration).to_timestamp().is_none());
+        }
+    }
+
+    #[test]
+    fn test_duration() {
+        struct Extract(Option<std::time::Duration>);
+
+        impl<'v> Visit<'v> for Extract {
+            fn visit_any(&mut self, value: Value) -> Result<(), Error> {
+                unimplemented!("unexpected value: {value:?}")
+            }
+
+            fn visit_duration(&mut self, value: std::time::Duration) -> Result<(), Error> {
+                self.0 = Some(value);
+                Ok(())
+            }
+        }
+
+        let duration = std::time::Duration::from_millis(1500);
+        let value = duration.to_value();
+
+        assert_eq!(ValueKind::Duration, value.kind());
+        assert_eq!(Some(duration), value.to_duration());
+        assert_eq!("1.5s", value.to_string());
+        assert_eq!("1.5s", format!("{:?}", value));
+
+        let mut extract = Extract(None);
+        value.visit(&mut extract).unwrap();
+        assert_eq!(Some(duration), extract.0);
+    }
+
+    #[test]
+    #[cfg(feature = "kv_std")]
+    fn test_timestamp() {
+        use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+        struct Extract(Option<SystemTime>);
+
+        impl<'v> Visit<'v> for Extract {
+            fn visit_any(&mut self, value: Value) -> Result<(), Error> {
+                unimplemented!("unexpected value: {value:?}")
+            }
+
+            fn visit_timestamp(&mut self, value: SystemTime) -> Result<(), Error> {
+                self.0 = Some(value);
+                Ok(())
+            }
+        }
+
+        let ts = UNIX_EPOCH + Duration::new(1_704_067_200, 250_000_000);
+        let value = ts.to_value();
+
+        assert_eq!(ValueKind::Timestamp, value.kind());
+        assert_eq!(Some(ts), value.to_timestamp());
+        assert_eq!("2024-01-01T00:00:00.250Z", value.to_string());
+        assert_eq!("2024-01-01T00:00:00.250Z", format!("{:?}", value));
+
+        let mut extract = Extract(None);
+        value.visit(&mut extract).unwrap();
+        assert_eq!(Some(ts), extract.0);
+    }
+
+    #[test]
+    fn test_visit_duration_default() {
+        struct Collect(Option<String>);
+
+        impl<'v> Visit<'v> for Collect {
+            fn visit_any(&mut self, value: Value) -> Result<(), Error> {
+                self.0 = Some(value.to_string());
+                Ok(())
+            }
+        }
+
+        let mut collect = Collect(None);
+        std::time::Duration::from_secs(2)
+            .to_value()
+            .visit(&mut collect)
+            .unwrap();
+
+        assert_eq!(Some("2s"), collect.0.as_deref());
+    }
+
     #[test]
     fn test_downcast_ref() {
         #[derive(Debug)]
@@ -983,6 +2189,284 @@ pub(crate) mod tests {
         assert_eq!(Some(42), extract.0);
     }
 
+    #[test]
+    fn test_seq_fmt() {
+        let array = [1, 2];
+        let slice = &["a", "b"][..];
+        let nested = [[1], [2]];
+
+        assert_eq!("[1, 2]", array.to_value().to_string());
+        assert_eq!("[a, b]", slice.to_value().to_string());
+        assert_eq!("[\"a\", \"b\"]", format!("{:?}", slice.to_value()));
+        assert_eq!("[[1], [2]]", nested.to_value().to_string());
+        assert_eq!("[]", Value::from_seq(&[0u8; 0]).to_string());
+    }
+
+    #[cfg(feature = "kv_std")]
+    #[test]
+    fn test_std_seq_map_fmt() {
+        use std::collections::{BTreeMap, HashMap};
+
+        let vec = vec!["a", "b"];
+
+        let mut btree = BTreeMap::new();
+        btree.insert("a", vec![1]);
+        btree.insert("b", vec![2, 3]);
+
+        let mut hash = HashMap::new();
+        hash.insert(1, true);
+
+        assert_eq!("[a, b]", vec.to_value().to_string());
+        assert_eq!("{a: [1], b: [2, 3]}", btree.to_value().to_string());
+        assert_eq!(
+            "{\"a\": [1], \"b\": [2, 3]}",
+            format!("{:?}", btree.to_value())
+        );
+        assert_eq!("{1: true}", hash.to_value().to_string());
+    }
+
+    #[test]
+    fn test_seq_map_to_primitive() {
+        let value = [1].to_value();
+
+        assert!(value.to_u64().is_none());
+        assert!(value.to_borrowed_str().is_none());
+        assert!(!value.is::<[i32; 1]>());
+    }
+
+    #[test]
+    fn test_visit_seq_map() {
+        struct Map;
+
+        impl super::Map for Map {
+            fn visit_map<'v>(&'v self, visitor: &mut dyn VisitMap<'v>) -> Result<(), Error> {
+                visitor.visit_entry(Value::from("a"), [1, 2].to_value())?;
+                visitor.visit_entry(Value::from("b"), Value::from(3))
+            }
+        }
+
+        // Sums every number in a value, however deeply it's nested
+        struct Sum(u64);
+
+        impl<'v> Visit<'v> for Sum {
+            fn visit_any(&mut self, value: Value) -> Result<(), Error> {
+                unimplemented!("unexpected value: {:?}", value)
+            }
+
+            fn visit_u64(&mut self, value: u64) -> Result<(), Error> {
+                self.0 += value;
+                Ok(())
+            }
+
+            fn visit_i64(&mut self, value: i64) -> Result<(), Error> {
+                self.visit_u64(value as u64)
+            }
+
+            fn visit_str(&mut self, _: &str) -> Result<(), Error> {
+                Ok(())
+            }
+
+            fn visit_seq(&mut self, seq: &'v dyn Seq) -> Result<(), Error> {
+                seq.visit_seq(&mut FnVisitSeq(|value: Value<'v>| value.visit(&mut *self)))
+            }
+
+            fn visit_map(&mut self, map: &'v dyn super::Map) -> Result<(), Error> {
+                map.visit_map(&mut FnVisitMap(|key: Value<'v>, value: Value<'v>| {
+                    key.visit(&mut *self)?;
+                    value.visit(&mut *self)
+                }))
+            }
+        }
+
+        let mut sum = Sum(0);
+        Value::from_map(&Map).visit(&mut sum).unwrap();
+
+        assert_eq!(6, sum.0);
+    }
+
+    #[test]
+    fn test_visit_seq_default() {
+        struct Fmt(String);
+
+        impl<'v> Visit<'v> for Fmt {
+            fn visit_any(&mut self, value: Value) -> Result<(), Error> {
+                self.0 = value.to_string();
+                Ok(())
+            }
+        }
+
+        let mut fmt = Fmt(String::new());
+        [1, 2].to_value().visit(&mut fmt).unwrap();
+
+        assert_eq!("[1, 2]", fmt.0);
+    }
+
+    #[cfg(feature = "kv_serde")]
+    #[test]
+    fn test_seq_map_serde() {
+        use serde_test::{assert_ser_tokens, Token};
+
+        assert_ser_tokens(
+            &[1, 2].to_value(),
+            &[
+                Token::Seq { len: None },
+                Token::I64(1),
+                Token::I64(2),
+                Token::SeqEnd,
+            ],
+        );
+
+        let mut map = std::collections::BTreeMap::new();
+        map.insert("a", [true]);
+
+        assert_ser_tokens(
+            &map.to_value(),
+            &[
+                Token::Map { len: None },
+                Token::Str("a"),
+                Token::Seq { len: None },
+                Token::Bool(true),
+                Token::SeqEnd,
+                Token::MapEnd,
+            ],
+        );
+    }
+
+    #[cfg(feature = "kv_sval")]
+    #[test]
+    fn test_seq_map_sval() {
+        let seq = [[1, 2], [3, 4]];
+
+        // Formatting an `sval::Value` streams it
+        assert_eq!(
+            "[[1, 2], [3, 4]]",
+            Value::from_sval(&seq.to_value()).to_string()
+        );
+    }
+
+    #[test]
+    fn test_sensitive_fmt() {
+        let password = Sensitive::new("hunter2");
+
+        assert_eq!("[REDACTED]", format!("{:?}", password));
+        assert_eq!("[REDACTED]", format!("{}", password));
+        assert_eq!("[REDACTED]", format!("{:?}", password.to_value()));
+        assert_eq!("[REDACTED]", format!("{}", password.to_value()));
+        assert_eq!("[REDACTED]", as_sensitive!(42).to_string());
+        assert_eq!(
+            "[[REDACTED], 2]",
+            [as_sensitive!(1), 2.to_value()].to_value().to_string()
+        );
+
+        assert!(password.to_value().is_sensitive());
+        assert!(password.to_value().to_borrowed_str().is_none());
+        assert!(!"hunter2".to_value().is_sensitive());
+    }
+
+    #[test]
+    fn test_sensitive_visit() {
+        struct Extract(Option<String>);
+
+        impl<'v> Visit<'v> for Extract {
+            fn visit_any(&mut self, value: Value) -> Result<(), Error> {
+                assert!(value.is_sensitive());
+                self.0 = Some(value.to_string());
+                Ok(())
+            }
+
+            fn visit_borrowed_str(&mut self, value: &'v str) -> Result<(), Error> {
+                unimplemented!("unexpected value: {:?}", value)
+            }
+        }
+
+        let mut extract = Extract(None);
+        Sensitive::new("hunter2")
+            .to_value()
+            .visit(&mut extract)
+            .unwrap();
+
+        assert_eq!(Some("[REDACTED]"), extract.0.as_deref());
+    }
+
+    #[test]
+    fn test_sensitive_reveal() {
+        let password = Sensitive::new("hunter2");
+        let value = password.to_value();
+
+        let token = RevealToken::new();
+        assert_eq!("42", 42.to_value().reveal_with(&token).to_string());
+
+        let revealed = value.reveal_with(&token);
+
+        assert!(value.is_sensitive());
+        assert_eq!("[REDACTED]", value.to_string());
+        assert!(!revealed.is_sensitive());
+        assert_eq!(Some("hunter2"), revealed.to_borrowed_str());
+    }
+
+    #[cfg(feature = "kv_serde")]
+    #[test]
+    fn test_sensitive_serde() {
+        use serde_test::{assert_ser_tokens, Token};
+
+        assert_ser_tokens(
+            &Sensitive::new("hunter2").to_value(),
+            &[Token::Str("[REDACTED]")],
+        );
+    }
+
+    #[cfg(feature = "kv_sval")]
+    #[test]
+    fn test_sensitive_sval() {
+        let password = Sensitive::new("hunter2");
+
+        // Formatting an `sval::Value` streams it
+        assert_eq!(
+            "\"[REDACTED]\"",
+            Value::from_sval(&password.to_value()).to_string()
+        );
+    }
+
+    #[cfg(feature = "kv_serde")]
+    #[test]
+    fn test_time_serde() {
+        use serde_test::{assert_ser_tokens, Token};
+        use std::time::{Duration, UNIX_EPOCH};
+
+        assert_ser_tokens(&Duration::from_millis(1500).to_value(), &[Token::F64(1.5)]);
+        assert_ser_tokens(
+            &(UNIX_EPOCH + Duration::from_secs(1_704_067_200)).to_value(),
+            &[Token::Str("2024-01-01T00:00:00Z")],
+        );
+    }
+
+    #[cfg(feature = "kv_sval")]
+    #[test]
+    fn test_time_sval() {
+        use std::time::Duration;
+
+        // Formatting an `sval::Value` streams it
+        let duration = Duration::from_millis(1500);
+        assert_eq!("1.5", Value::from_sval(&duration.to_value()).to_string());
+
+        #[cfg(feature = "kv_std")]
+        {
+            let ts = std::time::UNIX_EPOCH + Duration::from_secs(1_704_067_200);
+            assert_eq!(
+                "\"2024-01-01T00:00:00Z\"",
+                Value::from_sval(&ts.to_value()).to_string()
+            );
+        }
+    }
+
+    #[cfg(feature = "kv_serde")]
+    #[test]
+    fn test_bytes_serde() {
+        use serde_test::{assert_ser_tokens, Token};
+
+        assert_ser_tokens(&Value::from_bytes(&[1, 2]), &[Token::Bytes(&[1, 2])]);
+    }
+
     #[test]
     fn test_visit_borrowed_str() {
         struct Extract<'v>(Option<&'v str>);
@@ -1006,4 +2490,69 @@ pub(crate) mod tests {
 
         assert_eq!(Some("A short-lived string"), extract.0);
     }
+
+    #[cfg(feature = "kv_serde")]
+    #[test]
+    fn test_serde_error_kind() {
+        struct Fail;
+
+        impl serde::Serialize for Fail {
+            fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+                Err(serde::ser::Error::custom("failed"))
+            }
+        }
+
+        struct Ignore;
+
+        impl<'v> Visit<'v> for Ignore {
+            fn visit_any(&mut self, _: Value) -> Result<(), Error> {
+                Ok(())
+            }
+        }
+
+        for value in [Value::from_serde(&Fail), Value::capture_serde(&Fail)] {
+            let err = serde_json::to_string(&value).unwrap_err();
+            assert!(err.to_string().contains("failed"));
+
+            // Newer versions of `value_bag` visit the value's `Debug` output instead of failing
+            if let Err(err) = value.visit(Ignore) {
+                assert_eq!(ErrorKind::Serde, err.kind());
+            }
+        }
+    }
+
+    #[cfg(feature = "kv_sval")]
+    #[test]
+    fn test_sval_error_kind() {
+        use std::fmt::Write as _;
+
+        struct Fail;
+
+        impl sval::Value for Fail {
+            fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(
+                &'sval self,
+                _: &mut S,
+            ) -> sval::Result {
+                Err(sval::Error::new())
+            }
+        }
+
+        struct Ignore;
+
+        impl<'v> Visit<'v> for Ignore {
+            fn visit_any(&mut self, _: Value) -> Result<(), Error> {
+                Ok(())
+            }
+        }
+
+        for value in [Value::from_sval(&Fail), Value::capture_sval(&Fail)] {
+            // Formatting an `sval::Value` streams it
+            assert!(write!(String::new(), "{}", value).is_err());
+
+            // Newer versions of `value_bag` visit the value's `Debug` output instead of failing
+            if let Err(err) = value.visit(Ignore) {
+                assert_eq!(ErrorKind::Sval, err.kind());
+            }
+        }
+    }
 }
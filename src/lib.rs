@@ -88,7 +88,7 @@
 //!
 //! ## Structured logging
 //!
-//! If you enable the `kv_unstable` feature you can associate structured values
+//! If you enable the `kv` feature you can associate structured values
 //! with your log records. If we take the example from before, we can include
 //! some additional context besides what's in the formatted message:
 //!
@@ -97,7 +97,7 @@
 //! # #[derive(Debug, Serialize)] pub struct Yak(String);
 //! # impl Yak { fn shave(&mut self, _: u32) {} }
 //! # fn find_a_razor() -> Result<u32, std::io::Error> { Ok(1) }
-//! # #[cfg(feature = "kv_unstable_serde")]
+//! # #[cfg(feature = "kv_serde")]
 //! # fn main() {
 //! use log::{info, warn, as_serde, as_error};
 //!
@@ -118,10 +118,56 @@
 //!     }
 //! }
 //! # }
-//! # #[cfg(not(feature = "kv_unstable_serde"))]
+//! # #[cfg(not(feature = "kv_serde"))]
 //! # fn main() {}
 //! ```
 //!
+//! Values are captured through their [`ToValue`](kv/trait.ToValue.html) implementation
+//! by default. A modifier after the key captures the value through another trait instead:
+//!
+//! - `key:? = value` uses `fmt::Debug`.
+//! - `key:% = value` uses `fmt::Display`.
+//! - `key:err = value` uses `std::error::Error` (requires `kv_std`).
+//! - `key:serde = value` uses `serde::Serialize` (requires `kv_serde`).
+//! - `key:sval = value` uses `sval::Value` (requires `kv_sval`).
+//!
+//! A key without a value captures the variable with the same name:
+//!
+//! ```edition2018
+//! # #[cfg(feature = "kv")]
+//! # fn main() {
+//! use log::info;
+//! use std::path::PathBuf;
+//!
+//! let user_id = 42;
+//! let path = PathBuf::from("/var/lib/yak");
+//!
+//! info!(user_id, path:? = path; "Opened the yak store");
+//! # }
+//! # #[cfg(not(feature = "kv"))]
+//! # fn main() {}
+//! ```
+//!
+//! Values with identifier keys can be interpolated into the message by name,
+//! so they only need to be written once. Each value is evaluated once, before
+//! the message is formatted:
+//!
+//! ```edition2018
+//! # #[cfg(feature = "kv")]
+//! # fn main() {
+//! use log::info;
+//!
+//! # let yak = "Fernando";
+//! info!(yak = yak.trim(); "Shaving {yak}");
+//! # }
+//! # #[cfg(not(feature = "kv"))]
+//! # fn main() {}
+//! ```
+//!
+//! Keys are only visible to the format string itself. They shadow variables with
+//! the same name that it refers to, like `{yak}`, but not variables used by the
+//! format arguments that follow it.
+//!
 //! # Available logging implementations
 //!
 //! In order to produce log output executables have to use
@@ -276,9 +322,34 @@
 //! The following crate feature flags are available in addition to the filters. They are
 //! configured in your `Cargo.toml`.
 //!
-//! * `std` allows use of `std` crate instead of the default `core`. Enables using `std::error` and
-//! `set_boxed_logger` functionality.
-//! * `serde` enables support for serialization and deserialization of `Level` and `LevelFilter`.
+//! * `std` allows use of `std` crate instead of the default `core`. Enables using `std::error`,
+//!   `set_boxed_logger` functionality, the [`panic`] hook, the [`writer`] adapter, the
+//!   [`format`] trait for rendering records, and the [`batch`] wrapper for batching sinks.
+//! * `serde` enables support for serialization and deserialization of `Level` and `LevelFilter`,
+//!   with alternative representations in the [`serde`](serde/index.html) module, and
+//!   serialization of `Metadata` and `Record`.
+//! * `panic_backtrace` enables `kv_std`, and attaches a backtrace to records logged by the
+//!   [`panic`] hook. It needs Rust 1.65 or newer, unlike the rest of the crate.
+//! * `deferred` enables the [`log_deferred!`] macro and the [`deferred`] module, which move
+//!   message formatting off the logging thread.
+//! * `binary` enables the [`binary`] module, a compact binary encoding for records.
+//! * `net` enables the [`net`](net/index.html) module, a sink that writes records to a TCP or
+//!   Unix socket.
+//! * `kv` enables the [`kv`] module and structured key-values in the logging macros.
+//! * `kv_std` enables `kv` with support for `std` types like `String` and `std::error::Error`.
+//! * `kv_sval` enables `kv` with support for capturing values with `sval`.
+//! * `kv_serde` enables `kv_std` with support for capturing values with `serde`.
+//! * `otel` enables `kv_std` and the [`otel`](otel/index.html) module, which maps records into
+//!   the OpenTelemetry log data model and exports them as OTLP/JSON.
+//! * `gelf` enables `kv_std` and the [`gelf`](gelf/index.html) module, which formats records as
+//!   GELF messages for Graylog and sends them over UDP or TCP.
+//! * `ecs` enables `kv_std` and the [`ecs`](ecs/index.html) module, which formats records as
+//!   Elastic Common Schema documents.
+//! * `journald` enables `kv_std` and, on Linux, the [`journald`](journald/index.html) module,
+//!   which sends records to the systemd journal using its native protocol.
+//!
+//! The `kv_unstable`, `kv_unstable_std`, `kv_unstable_sval`, and `kv_unstable_serde` features
+//! are deprecated aliases of the `kv` features above.
 //!
 //! ```toml
 //! [dependencies]
@@ -297,6 +368,14 @@
 //! [filter_link]: enum.LevelFilter.html
 //! [`set_logger`]: fn.set_logger.html
 //! [`set_max_level`]: fn.set_max_level.html
+//! [`panic`]: panic/index.html
+//! [`writer`]: writer/index.html
+//! [`format`]: format/index.html
+//! [`batch`]: batch/index.html
+//! [`deferred`]: deferred/index.html
+//! [`binary`]: binary/index.html
+//! [`kv`]: kv/index.html
+//! [`log_deferred!`]: macro.log_deferred.html
 //! [`try_set_logger_raw`]: fn.try_set_logger_raw.html
 //! [`shutdown_logger_raw`]: fn.shutdown_logger_raw.html
 //! [env_logger]: https://docs.rs/env_logger/*/env_logger/
@@ -341,11 +420,55 @@ use std::{cmp, fmt, mem};
 
 #[macro_use]
 mod macros;
-mod serde;
+pub mod serde;
 
-#[cfg(feature = "kv_unstable")]
+#[cfg(feature = "kv")]
 pub mod kv;
 
+#[cfg(feature = "std")]
+mod owned;
+
+#[cfg(feature = "std")]
+pub mod panic;
+
+pub mod ring;
+
+#[cfg(feature = "deferred")]
+pub mod deferred;
+
+#[cfg(feature = "binary")]
+pub mod binary;
+
+#[cfg(feature = "std")]
+pub mod writer;
+
+#[cfg(feature = "std")]
+pub mod format;
+
+#[cfg(feature = "net")]
+pub mod net;
+
+#[cfg(feature = "std")]
+pub mod batch;
+
+#[cfg(any(feature = "otel", feature = "gelf", feature = "ecs"))]
+mod json;
+
+#[cfg(feature = "gelf")]
+pub mod gelf;
+
+#[cfg(feature = "ecs")]
+pub mod ecs;
+
+#[cfg(feature = "otel")]
+pub mod otel;
+
+#[cfg(all(target_os = "linux", feature = "journald"))]
+pub mod journald;
+
+#[cfg(feature = "std")]
+pub use self::owned::OwnedRecord;
+
 #[cfg(target_has_atomic = "ptr")]
 use std::sync::atomic::{AtomicUsize, Ordering};
 
@@ -505,6 +628,39 @@ impl Level {
         }
     }
 
+    /// Returns the OpenTelemetry severity number of the `Level`.
+    ///
+    /// Levels map to the first number in each OpenTelemetry severity range:
+    ///
+    /// | Level   | Severity number |
+    /// | ------- | --------------- |
+    /// | `Trace` | `1`             |
+    /// | `Debug` | `5`             |
+    /// | `Info`  | `9`             |
+    /// | `Warn`  | `13`            |
+    /// | `Error` | `17`            |
+    pub fn otel_severity_number(&self) -> u8 {
+        match *self {
+            Level::Trace => 1,
+            Level::Debug => 5,
+            Level::Info => 9,
+            Level::Warn => 13,
+            Level::Error => 17,
+        }
+    }
+
+    // The syslog severity for this level. There's no syslog severity below
+    // debug, so trace records share it
+    #[cfg(any(feature = "gelf", all(target_os = "linux", feature = "journald")))]
+    pub(crate) fn syslog_severity(&self) -> u8 {
+        match *self {
+            Level::Error => 3,
+            Level::Warn => 4,
+            Level::Info => 6,
+            Level::Debug | Level::Trace => 7,
+        }
+    }
+
     /// Returns the most verbose logging level.
     #[inline]
     pub fn max() -> Level {
@@ -722,19 +878,21 @@ pub struct Record<'a> {
     module_path: Option<MaybeStaticStr<'a>>,
     file: Option<MaybeStaticStr<'a>>,
     line: Option<u32>,
-    #[cfg(feature = "kv_unstable")]
+    #[cfg(feature = "kv")]
     key_values: KeyValues<'a>,
+    #[cfg(feature = "deferred")]
+    deferred: Option<&'a deferred::DeferredArgs>,
 }
 
 // This wrapper type is only needed so we can
 // `#[derive(Debug)]` on `Record`. It also
 // provides a useful `Debug` implementation for
 // the underlying `Source`.
-#[cfg(feature = "kv_unstable")]
+#[cfg(feature = "kv")]
 #[derive(Clone)]
 struct KeyValues<'a>(&'a dyn kv::Source);
 
-#[cfg(feature = "kv_unstable")]
+#[cfg(feature = "kv")]
 impl<'a> fmt::Debug for KeyValues<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut visitor = f.debug_map();
@@ -811,14 +969,23 @@ impl<'a> Record<'a> {
     }
 
     /// The structured key-value pairs associated with the message.
-    #[cfg(feature = "kv_unstable")]
+    #[cfg(feature = "kv")]
     #[inline]
     pub fn key_values(&self) -> &dyn kv::Source {
         self.key_values.0
     }
 
+    /// The deferred arguments of the message, if it was logged with [`log_deferred!`].
+    ///
+    /// [`log_deferred!`]: macro.log_deferred.html
+    #[cfg(feature = "deferred")]
+    #[inline]
+    pub fn deferred_args(&self) -> Option<&'a deferred::DeferredArgs> {
+        self.deferred
+    }
+
     /// Create a new [`RecordBuilder`](struct.RecordBuilder.html) based on this record.
-    #[cfg(feature = "kv_unstable")]
+    #[cfg(feature = "kv")]
     #[inline]
     pub fn to_builder(&self) -> RecordBuilder {
         RecordBuilder {
@@ -832,6 +999,8 @@ impl<'a> Record<'a> {
                 file: self.file,
                 line: self.line,
                 key_values: self.key_values.clone(),
+                #[cfg(feature = "deferred")]
+                deferred: self.deferred,
             },
         }
     }
@@ -903,8 +1072,10 @@ impl<'a> RecordBuilder<'a> {
                 module_path: None,
                 file: None,
                 line: None,
-                #[cfg(feature = "kv_unstable")]
+                #[cfg(feature = "kv")]
                 key_values: KeyValues(&None::<(kv::Key, kv::Value)>),
+                #[cfg(feature = "deferred")]
+                deferred: None,
             },
         }
     }
@@ -973,13 +1144,24 @@ impl<'a> RecordBuilder<'a> {
     }
 
     /// Set [`key_values`](struct.Record.html#method.key_values)
-    #[cfg(feature = "kv_unstable")]
+    #[cfg(feature = "kv")]
     #[inline]
     pub fn key_values(&mut self, kvs: &'a dyn kv::Source) -> &mut RecordBuilder<'a> {
         self.record.key_values = KeyValues(kvs);
         self
     }
 
+    /// Set [`deferred_args`](struct.Record.html#method.deferred_args)
+    #[cfg(feature = "deferred")]
+    #[inline]
+    pub fn deferred_args(
+        &mut self,
+        args: Option<&'a deferred::DeferredArgs>,
+    ) -> &mut RecordBuilder<'a> {
+        self.record.deferred = args;
+        self
+    }
+
     /// Invoke the builder and return a `Record`
     #[inline]
     pub fn build(&self) -> Record<'a> {
@@ -1741,7 +1923,7 @@ mod tests {
     }
 
     #[test]
-    #[cfg(feature = "kv_unstable")]
+    #[cfg(feature = "kv")]
     fn test_record_key_values_builder() {
         use super::Record;
         use crate::kv::{self, Visitor};
@@ -1772,7 +1954,7 @@ mod tests {
     }
 
     #[test]
-    #[cfg(feature = "kv_unstable")]
+    #[cfg(feature = "kv")]
     fn test_record_key_values_get_coerce() {
         use super::Record;
 
@@ -29,16 +29,17 @@
 /// ```
 #[macro_export]
 macro_rules! log {
-    // log!(target: "my_target", Level::Info, key1 = 42, key2 = true; "a {} event", "log");
-    (target: $target:expr, $lvl:expr, $($key:tt = $value:expr),+; $($arg:tt)+) => ({
+    // log!(target: "my_target", Level::Info, key1 = 42, key2:? = true, key3; "a {} event", "log");
+    (target: $target:expr, $lvl:expr, $($key:tt $(:$capture:tt)? $(= $value:expr)?),+; $($arg:tt)+) => ({
         let lvl = $lvl;
         if lvl <= $crate::STATIC_MAX_LEVEL && lvl <= $crate::max_level() {
-            $crate::__private_api::log::<&_>(
-                $crate::__private_api::format_args!($($arg)+),
-                lvl,
-                &($target, $crate::__private_api::module_path!(), $crate::__private_api::file!()),
-                $crate::__private_api::line!(),
-                &[$(($crate::__log_key!($key), &$value)),+]
+            // The target is evaluated before any keys are bound
+            // so they can't shadow variables it uses
+            let target: &str = $target;
+            $crate::__log_kvs!(
+                [] [] [] []
+                ($($key $(:$capture)* $(= $value)*),+)
+                (lvl, target, ($($arg)+))
             );
         }
     });
@@ -238,3 +239,189 @@ macro_rules! __log_key {
         $($args)*
     };
 }
+
+// Bind the values of key-value pairs and log them.
+//
+// Values and format arguments are bound to `value` and `arg` variables that
+// are unique to each step of the recursion, and are all evaluated before any
+// keys are bound. Ident keys are then bound to their values in a scope that
+// only the format string sees, so they can be interpolated into the message
+// without shadowing variables used by the format arguments. Keys that aren't
+// interpolated are unused, and repeated keys shadow the earlier ones.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_kvs {
+    // key1 = 42
+    ([$($pat:tt)*] [$($val:tt)*] [$($kv:tt)*] [$($bind:tt)*] ($key:ident = $value:expr $(, $($rest:tt)*)?) $ctx:tt) => {
+        $crate::__log_kvs!(
+            [$($pat)* value,] [$($val)* &$value,] [$($kv)* ($crate::__log_key!($key), value),] [$($bind)* ($key value)]
+            ($($($rest)*)?) $ctx
+        )
+    };
+    // key1:? = 42
+    ([$($pat:tt)*] [$($val:tt)*] [$($kv:tt)*] [$($bind:tt)*] ($key:ident :$capture:tt = $value:expr $(, $($rest:tt)*)?) $ctx:tt) => {
+        $crate::__log_kvs!(
+            [$($pat)* value,] [$($val)* &$value,] [$($kv)* ($crate::__log_key!($key), $crate::__log_value!((value):$capture)),] [$($bind)* ($key value)]
+            ($($($rest)*)?) $ctx
+        )
+    };
+    // key1
+    ([$($pat:tt)*] [$($val:tt)*] [$($kv:tt)*] [$($bind:tt)*] ($key:ident $(, $($rest:tt)*)?) $ctx:tt) => {
+        $crate::__log_kvs!(
+            [$($pat)* value,] [$($val)* &$key,] [$($kv)* ($crate::__log_key!($key), value),] [$($bind)*]
+            ($($($rest)*)?) $ctx
+        )
+    };
+    // key1:?
+    ([$($pat:tt)*] [$($val:tt)*] [$($kv:tt)*] [$($bind:tt)*] ($key:ident :$capture:tt $(, $($rest:tt)*)?) $ctx:tt) => {
+        $crate::__log_kvs!(
+            [$($pat)* value,] [$($val)* &$key,] [$($kv)* ($crate::__log_key!($key), $crate::__log_value!((value):$capture)),] [$($bind)*]
+            ($($($rest)*)?) $ctx
+        )
+    };
+    // "key1" = 42
+    ([$($pat:tt)*] [$($val:tt)*] [$($kv:tt)*] [$($bind:tt)*] ($key:tt = $value:expr $(, $($rest:tt)*)?) $ctx:tt) => {
+        $crate::__log_kvs!(
+            [$($pat)* value,] [$($val)* &$value,] [$($kv)* ($crate::__log_key!($key), value),] [$($bind)*]
+            ($($($rest)*)?) $ctx
+        )
+    };
+    // "key1":? = 42
+    ([$($pat:tt)*] [$($val:tt)*] [$($kv:tt)*] [$($bind:tt)*] ($key:tt :$capture:tt = $value:expr $(, $($rest:tt)*)?) $ctx:tt) => {
+        $crate::__log_kvs!(
+            [$($pat)* value,] [$($val)* &$value,] [$($kv)* ($crate::__log_key!($key), $crate::__log_value!((value):$capture)),] [$($bind)*]
+            ($($($rest)*)?) $ctx
+        )
+    };
+    // All values are collected, so collect the format arguments
+    ([$($pat:tt)*] [$($val:tt)*] [$($kv:tt)*] [$($bind:tt)*] () ($lvl:expr, $target:expr, ($fmt:expr $(, $($args:tt)*)?))) => {
+        $crate::__log_kvs!(
+            @args [$($pat)*] [$($val)*] [$($kv)*] [$($bind)*] []
+            ($($($args)*)?) ($lvl, $target, $fmt)
+        )
+    };
+    // name = "world"
+    (@args [$($pat:tt)*] [$($val:tt)*] [$($kv:tt)*] [$($bind:tt)*] [$($fmt_args:tt)*] ($name:ident = $arg:expr $(, $($rest:tt)*)?) $ctx:tt) => {
+        $crate::__log_kvs!(
+            @args [$($pat)* arg,] [$($val)* &$arg,] [$($kv)*] [$($bind)*] [$($fmt_args)* $name = *arg,]
+            ($($($rest)*)?) $ctx
+        )
+    };
+    // "world"
+    (@args [$($pat:tt)*] [$($val:tt)*] [$($kv:tt)*] [$($bind:tt)*] [$($fmt_args:tt)*] ($arg:expr $(, $($rest:tt)*)?) $ctx:tt) => {
+        $crate::__log_kvs!(
+            @args [$($pat)* arg,] [$($val)* &$arg,] [$($kv)*] [$($bind)*] [$($fmt_args)* *arg,]
+            ($($($rest)*)?) $ctx
+        )
+    };
+    // All values and format arguments are bound
+    (@args [$($pat:tt)*] [$($val:tt)*] [$($kv:tt)*] [$(($key:ident $value:ident))*] [$($fmt_args:tt)*] () ($lvl:expr, $target:expr, $fmt:expr)) => {{
+        let ($($pat)*) = ($($val)*);
+        $(
+            #[allow(unused_variables)]
+            let $key = $value;
+        )*
+        $crate::__private_api::log::<&_>(
+            $crate::__private_api::format_args!($fmt, $($fmt_args)*),
+            $lvl,
+            &($target, $crate::__private_api::module_path!(), $crate::__private_api::file!()),
+            $crate::__private_api::line!(),
+            &[$($kv)*]
+        );
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "kv")]
+macro_rules! __log_value {
+    // Debug
+    (($value:expr):?) => {
+        &$crate::__private_api::capture_debug($value)
+    };
+    // Display
+    (($value:expr):%) => {
+        &$crate::__private_api::capture_display($value)
+    };
+    // Error
+    (($value:expr):err) => {
+        $crate::__log_value_error!($value)
+    };
+    // serde::Serialize
+    (($value:expr):serde) => {
+        $crate::__log_value_serde!($value)
+    };
+    // sval::Value
+    (($value:expr):sval) => {
+        $crate::__log_value_sval!($value)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(feature = "kv"))]
+macro_rules! __log_value {
+    (($value:expr):$capture:tt) => {
+        $crate::__private_api::compile_error!("capture modifiers require the `kv` feature of `log`")
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "kv_std")]
+macro_rules! __log_value_error {
+    ($value:expr) => {
+        &$crate::__private_api::capture_error($value)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(feature = "kv_std"))]
+macro_rules! __log_value_error {
+    ($value:expr) => {
+        $crate::__private_api::compile_error!(
+            "capturing values as `std::error::Error` requires the `kv_std` feature of `log`"
+        )
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "kv_serde")]
+macro_rules! __log_value_serde {
+    ($value:expr) => {
+        &$crate::__private_api::capture_serde($value)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(feature = "kv_serde"))]
+macro_rules! __log_value_serde {
+    ($value:expr) => {
+        $crate::__private_api::compile_error!(
+            "capturing values as `serde::Serialize` requires the `kv_serde` feature of `log`"
+        )
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "kv_sval")]
+macro_rules! __log_value_sval {
+    ($value:expr) => {
+        &$crate::__private_api::capture_sval($value)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(feature = "kv_sval"))]
+macro_rules! __log_value_sval {
+    ($value:expr) => {
+        $crate::__private_api::compile_error!(
+            "capturing values as `sval::Value` requires the `kv_sval` feature of `log`"
+        )
+    };
+}
@@ -0,0 +1,614 @@
+//! Sending records over a socket.
+//!
+//! This module forwards records to a local agent or remote collector with
+//! [`NetSink`], which writes framed records to a TCP or Unix socket. Records
+//! are rendered by a [`Format`], like [`Text`] or the formats in other
+//! modules of this crate.
+//!
+//! Requires the `net` feature.
+//!
+//! # Examples
+//!
+//! Forwarding records as lines of text to a local agent:
+//!
+//! ```edition2018,no_run
+//! use log::format::Text;
+//! use log::net::NetSink;
+//!
+//! # fn main() -> std::io::Result<()> {
+//! let sink = NetSink::tcp("localhost:5170", Text::new())?;
+//!
+//! log::set_boxed_logger(Box::new(sink)).unwrap();
+//! log::set_max_level(log::LevelFilter::Info);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`NetSink`]: struct.NetSink.html
+//! [`Format`]: ../format/trait.Format.html
+//! [`Text`]: ../format/struct.Text.html
+
+use std::cmp;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::format::Format;
+use crate::{Log, Metadata, Record};
+
+/// How records are delimited on the socket.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Framing {
+    /// Each record is followed by a `\n`.
+    ///
+    /// Line breaks within a record are escaped as `\n` and `\r`, so each
+    /// record stays on its own line.
+    Newline,
+    /// Each record is preceded by its length in bytes, as a 4 byte big-endian
+    /// integer.
+    LengthDelimited,
+}
+
+/// A [`Log`] that writes records to a TCP or Unix socket.
+///
+/// Each record is rendered by the sink's [`Format`] and framed according to
+/// its [`Framing`].
+///
+/// The connection is opened when the first record is logged. If it can't be
+/// opened, or writing to it fails, records are buffered and the connection is
+/// retried when later records are logged, waiting longer after each failure.
+/// The wait starts at 100 milliseconds and doubles up to 30 seconds by
+/// default; see [`NetSink::backoff`]. Records that fail partway through being
+/// written are sent again in full on the new connection.
+///
+/// Up to 1 MiB of records are buffered by default; see
+/// [`NetSink::buffer_limit`]. Records logged while the buffer is full are
+/// dropped, and counted by [`NetSink::dropped`].
+///
+/// [`Log::flush`] retries the connection straight away, without waiting for
+/// the backoff, and writes any buffered records.
+///
+/// All records are enabled; use [`set_max_level`] to filter them.
+///
+/// [`Log`]: ../trait.Log.html
+/// [`Format`]: ../format/trait.Format.html
+/// [`Framing`]: enum.Framing.html
+/// [`NetSink::backoff`]: #method.backoff
+/// [`NetSink::buffer_limit`]: #method.buffer_limit
+/// [`NetSink::dropped`]: #method.dropped
+/// [`Log::flush`]: ../trait.Log.html#tymethod.flush
+/// [`set_max_level`]: ../fn.set_max_level.html
+#[derive(Debug)]
+pub struct NetSink<F> {
+    format: F,
+    endpoint: Endpoint,
+    framing: Framing,
+    buffer_limit: usize,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    timeout: Duration,
+    dropped: AtomicU64,
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+enum Endpoint {
+    Tcp(Vec<SocketAddr>),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+#[derive(Debug)]
+enum Connection {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match *self {
+            Connection::Tcp(ref mut stream) => stream.write(buf),
+            #[cfg(unix)]
+            Connection::Unix(ref mut stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match *self {
+            Connection::Tcp(ref mut stream) => stream.flush(),
+            #[cfg(unix)]
+            Connection::Unix(ref mut stream) => stream.flush(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    connection: Option<Connection>,
+    buffer: VecDeque<Vec<u8>>,
+    buffered: usize,
+    backoff: Duration,
+    retry_at: Option<Instant>,
+}
+
+impl<F> NetSink<F>
+where
+    F: Format,
+{
+    /// Create a sink that writes records to a TCP socket at `addr`.
+    ///
+    /// The address is resolved immediately, but the connection isn't opened
+    /// until the first record is logged.
+    pub fn tcp(addr: impl ToSocketAddrs, format: F) -> io::Result<Self> {
+        let addrs: Vec<_> = addr.to_socket_addrs()?.collect();
+        if addrs.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "the address didn't resolve to any socket addresses",
+            ));
+        }
+
+        Ok(NetSink::new(Endpoint::Tcp(addrs), format))
+    }
+
+    /// Create a sink that writes records to a Unix socket at `path`.
+    ///
+    /// The connection isn't opened until the first record is logged.
+    #[cfg(unix)]
+    pub fn unix(path: impl Into<PathBuf>, format: F) -> Self {
+        NetSink::new(Endpoint::Unix(path.into()), format)
+    }
+
+    fn new(endpoint: Endpoint, format: F) -> Self {
+        let initial_backoff = Duration::from_millis(100);
+
+        NetSink {
+            format,
+            endpoint,
+            framing: Framing::Newline,
+            buffer_limit: 1024 * 1024,
+            initial_backoff,
+            max_backoff: Duration::from_secs(30),
+            timeout: Duration::from_secs(10),
+            dropped: AtomicU64::new(0),
+            state: Mutex::new(State {
+                connection: None,
+                buffer: VecDeque::new(),
+                buffered: 0,
+                backoff: initial_backoff,
+                retry_at: None,
+            }),
+        }
+    }
+
+    /// Set how records are delimited.
+    ///
+    /// The default is [`Framing::Newline`](enum.Framing.html#variant.Newline).
+    pub fn framing(mut self, framing: Framing) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// Set the most bytes of framed records to buffer while disconnected.
+    ///
+    /// The default is 1 MiB. A limit of `0` drops records whenever the sink is
+    /// disconnected.
+    pub fn buffer_limit(mut self, bytes: usize) -> Self {
+        self.buffer_limit = bytes;
+        self
+    }
+
+    /// Set how long to wait before retrying the connection.
+    ///
+    /// The sink waits `initial` after the first failure, and doubles the wait
+    /// after each failure that follows, up to `max`. The wait is reset once a
+    /// connection is opened.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = cmp::max(initial, max);
+        self.state_mut().backoff = initial;
+        self
+    }
+
+    /// Set the timeout for connecting and writing.
+    ///
+    /// The default is 10 seconds. A write that times out is treated like any
+    /// other failure, and the record is buffered to be sent again.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The format used for records.
+    pub fn format(&self) -> &F {
+        &self.format
+    }
+
+    /// The number of records dropped because the buffer was full, or because
+    /// they couldn't be formatted.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// The number of bytes of framed records waiting to be written.
+    pub fn buffered(&self) -> usize {
+        self.lock().buffered
+    }
+
+    fn frame(&self, record: &Record) -> io::Result<Vec<u8>> {
+        let mut frame = Vec::new();
+
+        match self.framing {
+            Framing::Newline => {
+                self.format.format(record, &mut frame)?;
+
+                if frame.iter().any(|&b| b == b'\n' || b == b'\r') {
+                    frame = escape_line_breaks(&frame);
+                }
+                frame.push(b'\n');
+            }
+            Framing::LengthDelimited => {
+                frame.extend_from_slice(&[0; 4]);
+                self.format.format(record, &mut frame)?;
+
+                let len = u32::try_from(frame.len() - 4).map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "the record is too large for a length-delimited frame",
+                    )
+                })?;
+                frame[..4].copy_from_slice(&len.to_be_bytes());
+            }
+        }
+
+        Ok(frame)
+    }
+
+    fn connect(&self) -> io::Result<Connection> {
+        match self.endpoint {
+            Endpoint::Tcp(ref addrs) => {
+                let mut last_err = None;
+                for addr in addrs {
+                    match TcpStream::connect_timeout(addr, self.timeout) {
+                        Ok(stream) => {
+                            stream.set_write_timeout(Some(self.timeout))?;
+                            return Ok(Connection::Tcp(stream));
+                        }
+                        Err(err) => last_err = Some(err),
+                    }
+                }
+
+                Err(last_err.expect("addresses are never empty"))
+            }
+            #[cfg(unix)]
+            Endpoint::Unix(ref path) => {
+                let stream = UnixStream::connect(path)?;
+                stream.set_write_timeout(Some(self.timeout))?;
+                Ok(Connection::Unix(stream))
+            }
+        }
+    }
+
+    // Write buffered records, connecting first if needed. Unless `now` is set,
+    // a connection isn't attempted until the backoff has passed
+    fn drain(&self, state: &mut State, now: bool) {
+        if state.connection.is_none() {
+            if let Some(retry_at) = state.retry_at {
+                if !now && Instant::now() < retry_at {
+                    return;
+                }
+            }
+
+            match self.connect() {
+                Ok(connection) => {
+                    state.connection = Some(connection);
+                    state.backoff = self.initial_backoff;
+                    state.retry_at = None;
+                }
+                Err(_) => {
+                    self.disconnected(state);
+                    return;
+                }
+            }
+        }
+
+        while let Some(frame) = state.buffer.front() {
+            let written = match state.connection {
+                Some(ref mut connection) => connection.write_all(frame),
+                None => return,
+            };
+
+            if written.is_err() {
+                self.disconnected(state);
+                return;
+            }
+
+            state.buffered -= frame.len();
+            state.buffer.pop_front();
+        }
+    }
+
+    fn disconnected(&self, state: &mut State) {
+        state.connection = None;
+        state.retry_at = Some(Instant::now() + state.backoff);
+        state.backoff = cmp::min(state.backoff * 2, self.max_backoff);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn state_mut(&mut self) -> &mut State {
+        self.state.get_mut().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+fn escape_line_breaks(formatted: &[u8]) -> Vec<u8> {
+    let mut escaped = Vec::with_capacity(formatted.len() + 8);
+
+    for &b in formatted {
+        match b {
+            b'\n' => escaped.extend_from_slice(b"\\n"),
+            b'\r' => escaped.extend_from_slice(b"\\r"),
+            b => escaped.push(b),
+        }
+    }
+
+    escaped
+}
+
+impl<F> Log for NetSink<F>
+where
+    F: Format + Send + Sync,
+{
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        let frame = match self.frame(record) {
+            Ok(frame) => frame,
+            Err(_) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        };
+
+        let mut state = self.lock();
+
+        // Records that don't fit in the buffer are still written if the
+        // sink is connected and nothing is waiting ahead of them
+        let fits = state.buffered + frame.len() <= self.buffer_limit;
+        let writable = state.buffer.is_empty() && state.connection.is_some();
+        if !fits && !writable {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        state.buffered += frame.len();
+        state.buffer.push_back(frame);
+
+        self.drain(&mut state, false);
+
+        // If writing a record that doesn't fit failed then it isn't kept
+        if !fits && !state.buffer.is_empty() {
+            let frame = state.buffer.pop_back().expect("the buffer isn't empty");
+            state.buffered -= frame.len();
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn flush(&self) {
+        let mut state = self.lock();
+        self.drain(&mut state, true);
+
+        let flushed = match state.connection {
+            Some(ref mut connection) => connection.flush(),
+            None => return,
+        };
+
+        if flushed.is_err() {
+            self.disconnected(&mut state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Text;
+    use crate::Level;
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    fn log(sink: &impl Log, message: &str) {
+        sink.log(
+            &Record::builder()
+                .level(Level::Info)
+                .target("app")
+                .args(format_args!("{}", message))
+                .build(),
+        );
+    }
+
+    #[test]
+    fn tcp_newline() {
+        let agent = TcpListener::bind("127.0.0.1:0").unwrap();
+        let sink = NetSink::tcp(agent.local_addr().unwrap(), Text::new()).unwrap();
+
+        log(&sink, "first");
+        log(&sink, "second");
+        sink.flush();
+
+        let (mut stream, _) = agent.accept().unwrap();
+        drop(sink);
+
+        let mut received = String::new();
+        stream.read_to_string(&mut received).unwrap();
+
+        assert_eq!("INFO app: first\nINFO app: second\n", received);
+    }
+
+    #[test]
+    fn tcp_newline_multiline() {
+        let agent = TcpListener::bind("127.0.0.1:0").unwrap();
+        let sink = NetSink::tcp(agent.local_addr().unwrap(), Text::new()).unwrap();
+
+        log(&sink, "first\nline\r\n");
+        log(&sink, "second");
+        sink.flush();
+
+        let (mut stream, _) = agent.accept().unwrap();
+        drop(sink);
+
+        let mut received = String::new();
+        stream.read_to_string(&mut received).unwrap();
+
+        assert_eq!("INFO app: first\\nline\\r\\n\nINFO app: second\n", received);
+    }
+
+    #[test]
+    fn tcp_length_delimited() {
+        let agent = TcpListener::bind("127.0.0.1:0").unwrap();
+        let sink = NetSink::tcp(agent.local_addr().unwrap(), Text::new())
+            .unwrap()
+            .framing(Framing::LengthDelimited);
+
+        log(&sink, "first");
+        log(&sink, "second");
+
+        let (mut stream, _) = agent.accept().unwrap();
+        drop(sink);
+
+        let mut received = Vec::new();
+        stream.read_to_end(&mut received).unwrap();
+
+        let mut frames = Vec::new();
+        let mut rest = &received[..];
+        while !rest.is_empty() {
+            let mut len = [0; 4];
+            len.copy_from_slice(&rest[..4]);
+            let len = u32::from_be_bytes(len) as usize;
+
+            frames.push(std::str::from_utf8(&rest[4..4 + len]).unwrap());
+            rest = &rest[4 + len..];
+        }
+
+        assert_eq!(vec!["INFO app: first", "INFO app: second"], frames);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix() {
+        use std::os::unix::net::UnixListener;
+
+        let path = std::env::temp_dir().join(format!("log-net-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let agent = UnixListener::bind(&path).unwrap();
+        let sink = NetSink::unix(&path, Text::new());
+
+        log(&sink, "over unix");
+
+        let (mut stream, _) = agent.accept().unwrap();
+        drop(sink);
+
+        let mut received = String::new();
+        stream.read_to_string(&mut received).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!("INFO app: over unix\n", received);
+    }
+
+    #[test]
+    fn reconnect_drains_on_flush() {
+        let agent = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = agent.local_addr().unwrap();
+        drop(agent);
+
+        let hour = Duration::from_secs(60 * 60);
+        let sink = NetSink::tcp(addr, Text::new()).unwrap().backoff(hour, hour);
+
+        log(&sink, "first");
+        log(&sink, "second");
+        assert_eq!(33, sink.buffered());
+
+        // The agent is back, but the sink waits for the backoff before retrying
+        let agent = TcpListener::bind(addr).unwrap();
+        log(&sink, "third");
+        assert_eq!(49, sink.buffered());
+
+        // Flushing retries straight away
+        sink.flush();
+        assert_eq!(0, sink.buffered());
+        assert_eq!(0, sink.dropped());
+
+        let (mut stream, _) = agent.accept().unwrap();
+        drop(sink);
+
+        let mut received = String::new();
+        stream.read_to_string(&mut received).unwrap();
+
+        assert_eq!(
+            "INFO app: first\nINFO app: second\nINFO app: third\n",
+            received
+        );
+    }
+
+    #[test]
+    fn buffer_limit() {
+        let agent = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = agent.local_addr().unwrap();
+        drop(agent);
+
+        let hour = Duration::from_secs(60 * 60);
+        let sink = NetSink::tcp(addr, Text::new())
+            .unwrap()
+            .backoff(hour, hour)
+            .buffer_limit(40);
+
+        for i in 0..5 {
+            log(&sink, &format!("record {}", i));
+        }
+
+        // Each framed record is 19 bytes, so only 2 fit
+        assert_eq!(38, sink.buffered());
+        assert_eq!(3, sink.dropped());
+    }
+
+    #[test]
+    fn backoff() {
+        let agent = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = agent.local_addr().unwrap();
+        drop(agent);
+
+        let sink = NetSink::tcp(addr, Text::new())
+            .unwrap()
+            .backoff(Duration::from_millis(10), Duration::from_millis(30));
+
+        let backoffs: Vec<_> = (0..4)
+            .map(|_| {
+                sink.flush();
+                sink.lock().backoff
+            })
+            .collect();
+
+        assert_eq!(
+            vec![
+                Duration::from_millis(20),
+                Duration::from_millis(30),
+                Duration::from_millis(30),
+                Duration::from_millis(30),
+            ],
+            backoffs
+        );
+    }
+}
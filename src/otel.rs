@@ -0,0 +1,972 @@
+//! OpenTelemetry log records.
+//!
+//! This module maps a [`Record`] into the OpenTelemetry [log data model], and
+//! encodes batches of the resulting [`LogRecord`]s as OTLP/JSON, ready to post
+//! to a collector's `/v1/logs` endpoint. [`HttpExporter`] does that posting
+//! over plain HTTP.
+//!
+//! Records map to log records as follows:
+//!
+//! | Record              | Log record                     |
+//! | ------------------- | ------------------------------ |
+//! | `level`             | `SeverityNumber`, `SeverityText` |
+//! | `args`              | `Body`                         |
+//! | `key_values`        | `Attributes`                   |
+//! | `file`              | `code.filepath` attribute      |
+//! | `line`              | `code.lineno` attribute        |
+//! | `module_path`       | `code.namespace` attribute     |
+//! | `target`            | `InstrumentationScope` name    |
+//!
+//! Records don't carry a timestamp, so the time a record is mapped is used as
+//! its `ObservedTimestamp`.
+//!
+//! Requires the `otel` feature.
+//!
+//! # Examples
+//!
+//! Exporting each record to a collector:
+//!
+//! ```edition2018,no_run
+//! use log::otel::{HttpExporter, LogRecord};
+//! use log::{Log, Metadata, Record};
+//!
+//! struct OtlpLogger(HttpExporter);
+//!
+//! impl Log for OtlpLogger {
+//!     fn enabled(&self, _: &Metadata) -> bool {
+//!         true
+//!     }
+//!
+//!     fn log(&self, record: &Record) {
+//!         let _ = self.0.export(&[LogRecord::from_record(record)]);
+//!     }
+//!
+//!     fn flush(&self) {}
+//! }
+//!
+//! # fn main() -> std::io::Result<()> {
+//! let exporter = HttpExporter::new("http://localhost:4318")?
+//!     .resource_attribute("service.name", "checkout");
+//!
+//! log::set_boxed_logger(Box::new(OtlpLogger(exporter))).unwrap();
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Record`]: ../struct.Record.html
+//! [log data model]: https://opentelemetry.io/docs/specs/otel/logs/data-model/
+//! [`LogRecord`]: struct.LogRecord.html
+//! [`HttpExporter`]: struct.HttpExporter.html
+
+use std::fmt::{self, Write as _};
+use std::io::{self, BufRead, BufReader, Write as _};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::json;
+use crate::kv::value::{BytesFormat, FormatBytes, Map, Seq, Visit, VisitMap, VisitSeq};
+use crate::kv::{self, Error, Key, Value, Visitor};
+use crate::Record;
+
+/// An OpenTelemetry log record.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogRecord {
+    observed_time_unix_nano: u64,
+    severity_number: u8,
+    severity_text: &'static str,
+    body: AnyValue,
+    attributes: Vec<(String, AnyValue)>,
+    scope_name: String,
+}
+
+impl LogRecord {
+    /// Map a record into an OpenTelemetry log record.
+    ///
+    /// The record's key-values become attributes, followed by its source
+    /// location. Key-values with empty values, like `None`, are skipped.
+    pub fn from_record(record: &Record) -> Self {
+        let mut attributes = Vec::new();
+
+        let _ = record
+            .key_values()
+            .visit(&mut CollectAttributes(&mut attributes));
+
+        if let Some(file) = record.file() {
+            attributes.push(("code.filepath".into(), AnyValue::String(file.into())));
+        }
+        if let Some(line) = record.line() {
+            attributes.push(("code.lineno".into(), AnyValue::Int(line.into())));
+        }
+        if let Some(module_path) = record.module_path() {
+            attributes.push((
+                "code.namespace".into(),
+                AnyValue::String(module_path.into()),
+            ));
+        }
+
+        LogRecord {
+            observed_time_unix_nano: unix_nanos(SystemTime::now()),
+            severity_number: record.level().otel_severity_number(),
+            severity_text: record.level().as_str(),
+            body: AnyValue::String(match record.args().as_str() {
+                Some(msg) => msg.into(),
+                None => record.args().to_string(),
+            }),
+            attributes,
+            scope_name: record.target().into(),
+        }
+    }
+
+    /// Use the given time as the time the record was observed.
+    pub fn with_observed_time(mut self, time: SystemTime) -> Self {
+        self.observed_time_unix_nano = unix_nanos(time);
+        self
+    }
+
+    /// The time the record was observed, in nanoseconds since the Unix epoch.
+    pub fn observed_time_unix_nano(&self) -> u64 {
+        self.observed_time_unix_nano
+    }
+
+    /// The OpenTelemetry severity number of the record.
+    ///
+    /// See [`Level::otel_severity_number`] for how levels map to severity
+    /// numbers.
+    ///
+    /// [`Level::otel_severity_number`]: ../enum.Level.html#method.otel_severity_number
+    pub fn severity_number(&self) -> u8 {
+        self.severity_number
+    }
+
+    /// The severity text of the record, which is the name of its level.
+    pub fn severity_text(&self) -> &str {
+        self.severity_text
+    }
+
+    /// The body of the record, which is its formatted message.
+    pub fn body(&self) -> &AnyValue {
+        &self.body
+    }
+
+    /// The attributes of the record.
+    pub fn attributes(&self) -> &[(String, AnyValue)] {
+        &self.attributes
+    }
+
+    /// Get the value of an attribute.
+    pub fn attribute(&self, key: &str) -> Option<&AnyValue> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    /// The name of the instrumentation scope of the record, which is its target.
+    pub fn scope_name(&self) -> &str {
+        &self.scope_name
+    }
+}
+
+impl<'a> From<&'a Record<'a>> for LogRecord {
+    fn from(record: &'a Record<'a>) -> Self {
+        LogRecord::from_record(record)
+    }
+}
+
+fn unix_nanos(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|since| since.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// An OpenTelemetry attribute or body value.
+///
+/// Structured values are converted as follows:
+///
+/// - strings, characters, and errors become `String`s.
+/// - integers become `Int`s. Integers that don't fit in an `i64` become `String`s.
+/// - floating point numbers and durations, in seconds, become `Double`s.
+/// - timestamps become RFC 3339 `String`s.
+/// - sequences become `Array`s and maps become `KvList`s.
+/// - empty values, like `None`, become `Empty`.
+///
+/// Any other value becomes the `String` produced by its `fmt::Display`
+/// implementation.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum AnyValue {
+    /// No value.
+    Empty,
+    /// A string.
+    String(String),
+    /// A boolean.
+    Bool(bool),
+    /// A signed integer.
+    Int(i64),
+    /// A floating point number.
+    Double(f64),
+    /// A sequence of bytes.
+    Bytes(Vec<u8>),
+    /// A list of values.
+    Array(Vec<AnyValue>),
+    /// A list of key-value pairs.
+    KvList(Vec<(String, AnyValue)>),
+}
+
+impl<'v> From<Value<'v>> for AnyValue {
+    fn from(value: Value<'v>) -> Self {
+        let mut convert = Convert(AnyValue::Empty);
+        let _ = value.visit(&mut convert);
+        convert.0
+    }
+}
+
+impl<'a> From<&'a str> for AnyValue {
+    fn from(value: &'a str) -> Self {
+        AnyValue::String(value.into())
+    }
+}
+
+impl From<String> for AnyValue {
+    fn from(value: String) -> Self {
+        AnyValue::String(value)
+    }
+}
+
+impl From<bool> for AnyValue {
+    fn from(value: bool) -> Self {
+        AnyValue::Bool(value)
+    }
+}
+
+impl From<i64> for AnyValue {
+    fn from(value: i64) -> Self {
+        AnyValue::Int(value)
+    }
+}
+
+impl From<f64> for AnyValue {
+    fn from(value: f64) -> Self {
+        AnyValue::Double(value)
+    }
+}
+
+struct CollectAttributes<'a>(&'a mut Vec<(String, AnyValue)>);
+
+impl<'a, 'kvs> Visitor<'kvs> for CollectAttributes<'a> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+        match AnyValue::from(value) {
+            AnyValue::Empty => (),
+            value => self.0.push((key.as_str().into(), value)),
+        }
+
+        Ok(())
+    }
+}
+
+struct Convert(AnyValue);
+
+impl Convert {
+    fn int<T>(&mut self, value: T) -> Result<(), Error>
+    where
+        T: TryInto<i64> + fmt::Display + Copy,
+    {
+        self.0 = match value.try_into() {
+            Ok(value) => AnyValue::Int(value),
+            Err(_) => AnyValue::String(value.to_string()),
+        };
+        Ok(())
+    }
+}
+
+impl<'v> Visit<'v> for Convert {
+    fn visit_any(&mut self, value: Value) -> Result<(), Error> {
+        self.0 = if value.is_unit() {
+            AnyValue::Empty
+        } else {
+            AnyValue::String(value.to_string())
+        };
+        Ok(())
+    }
+
+    fn visit_u64(&mut self, value: u64) -> Result<(), Error> {
+        self.int(value)
+    }
+
+    fn visit_i64(&mut self, value: i64) -> Result<(), Error> {
+        self.int(value)
+    }
+
+    fn visit_u128(&mut self, value: u128) -> Result<(), Error> {
+        self.int(value)
+    }
+
+    fn visit_i128(&mut self, value: i128) -> Result<(), Error> {
+        self.int(value)
+    }
+
+    fn visit_f64(&mut self, value: f64) -> Result<(), Error> {
+        self.0 = AnyValue::Double(value);
+        Ok(())
+    }
+
+    fn visit_bool(&mut self, value: bool) -> Result<(), Error> {
+        self.0 = AnyValue::Bool(value);
+        Ok(())
+    }
+
+    fn visit_str(&mut self, value: &str) -> Result<(), Error> {
+        self.0 = AnyValue::String(value.into());
+        Ok(())
+    }
+
+    fn visit_char(&mut self, value: char) -> Result<(), Error> {
+        self.0 = AnyValue::String(value.into());
+        Ok(())
+    }
+
+    fn visit_bytes(&mut self, value: &[u8]) -> Result<(), Error> {
+        self.0 = AnyValue::Bytes(value.into());
+        Ok(())
+    }
+
+    fn visit_duration(&mut self, value: Duration) -> Result<(), Error> {
+        self.0 = AnyValue::Double(value.as_secs_f64());
+        Ok(())
+    }
+
+    fn visit_timestamp(&mut self, value: SystemTime) -> Result<(), Error> {
+        self.0 = AnyValue::String(kv::time::Rfc3339(value).to_string());
+        Ok(())
+    }
+
+    fn visit_seq(&mut self, seq: &'v dyn Seq) -> Result<(), Error> {
+        struct Elements(Vec<AnyValue>);
+
+        impl<'v> VisitSeq<'v> for Elements {
+            fn visit_element(&mut self, value: Value<'v>) -> Result<(), Error> {
+                self.0.push(value.into());
+                Ok(())
+            }
+        }
+
+        let mut elements = Elements(Vec::new());
+        seq.visit_seq(&mut elements)?;
+
+        self.0 = AnyValue::Array(elements.0);
+        Ok(())
+    }
+
+    fn visit_map(&mut self, map: &'v dyn Map) -> Result<(), Error> {
+        struct Entries(Vec<(String, AnyValue)>);
+
+        impl<'v> VisitMap<'v> for Entries {
+            fn visit_entry(&mut self, key: Value<'v>, value: Value<'v>) -> Result<(), Error> {
+                let key = match key.to_borrowed_str() {
+                    Some(key) => key.into(),
+                    None => key.to_string(),
+                };
+
+                self.0.push((key, value.into()));
+                Ok(())
+            }
+        }
+
+        let mut entries = Entries(Vec::new());
+        map.visit_map(&mut entries)?;
+
+        self.0 = AnyValue::KvList(entries.0);
+        Ok(())
+    }
+}
+
+/// An encoder for batches of log records in the OTLP/JSON format.
+///
+/// Each batch is encoded as an `ExportLogsServiceRequest` with a single
+/// resource. Records are grouped into scopes by their [scope name], in the
+/// order each scope first appears.
+///
+/// # Examples
+///
+/// ```
+/// use log::otel::{LogRecord, OtlpJson};
+/// use log::{Level, Record};
+///
+/// let record = LogRecord::from_record(
+///     &Record::builder()
+///         .level(Level::Info)
+///         .target("checkout")
+///         .args(format_args!("order placed"))
+///         .build(),
+/// );
+///
+/// let json = OtlpJson::new()
+///     .resource_attribute("service.name", "shop")
+///     .encode(&[record]);
+///
+/// assert!(json.starts_with(r#"{"resourceLogs":[{"resource":{"attributes":[{"key":"service.name""#));
+/// ```
+///
+/// [scope name]: struct.LogRecord.html#method.scope_name
+#[derive(Clone, Debug, Default)]
+pub struct OtlpJson {
+    resource: Vec<(String, AnyValue)>,
+}
+
+impl OtlpJson {
+    /// Create an encoder with no resource attributes.
+    pub fn new() -> Self {
+        OtlpJson::default()
+    }
+
+    /// Add an attribute to the resource the records are exported from.
+    pub fn resource_attribute(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<AnyValue>,
+    ) -> Self {
+        self.resource.push((key.into(), value.into()));
+        self
+    }
+
+    /// Encode a batch of records as an OTLP/JSON export request.
+    pub fn encode(&self, records: &[LogRecord]) -> String {
+        let mut out = String::new();
+
+        // Writing to a `String` can't fail
+        let _ = self.write(&mut out, records);
+        out
+    }
+
+    fn write(&self, out: &mut String, records: &[LogRecord]) -> fmt::Result {
+        out.write_str(r#"{"resourceLogs":[{"resource":{"attributes":"#)?;
+        write_attributes(out, &self.resource)?;
+        out.write_str(r#"},"scopeLogs":["#)?;
+
+        let mut scopes: Vec<&str> = Vec::new();
+        for record in records {
+            if !scopes.contains(&record.scope_name()) {
+                scopes.push(record.scope_name());
+            }
+        }
+
+        for (i, scope) in scopes.into_iter().enumerate() {
+            if i > 0 {
+                out.write_char(',')?;
+            }
+
+            out.write_str(r#"{"scope":{"name":"#)?;
+            json::write_str(out, scope)?;
+            out.write_str(r#"},"logRecords":["#)?;
+
+            let in_scope = records.iter().filter(|r| r.scope_name() == scope);
+            for (i, record) in in_scope.enumerate() {
+                if i > 0 {
+                    out.write_char(',')?;
+                }
+
+                write_record(out, record)?;
+            }
+
+            out.write_str("]}")?;
+        }
+
+        out.write_str("]}]}")
+    }
+}
+
+fn write_record(out: &mut String, record: &LogRecord) -> fmt::Result {
+    // 64-bit integers are written as strings in OTLP/JSON
+    write!(
+        out,
+        r#"{{"observedTimeUnixNano":"{}","severityNumber":{},"severityText":"#,
+        record.observed_time_unix_nano, record.severity_number
+    )?;
+    json::write_str(out, record.severity_text)?;
+    out.write_str(r#","body":"#)?;
+    write_any_value(out, &record.body)?;
+    out.write_str(r#","attributes":"#)?;
+    write_attributes(out, &record.attributes)?;
+    out.write_char('}')
+}
+
+fn write_attributes(out: &mut String, attributes: &[(String, AnyValue)]) -> fmt::Result {
+    out.write_char('[')?;
+    for (i, (key, value)) in attributes.iter().enumerate() {
+        if i > 0 {
+            out.write_char(',')?;
+        }
+
+        out.write_str(r#"{"key":"#)?;
+        json::write_str(out, key)?;
+        out.write_str(r#","value":"#)?;
+        write_any_value(out, value)?;
+        out.write_char('}')?;
+    }
+    out.write_char(']')
+}
+
+fn write_any_value(out: &mut String, value: &AnyValue) -> fmt::Result {
+    match value {
+        AnyValue::Empty => out.write_str("{}"),
+        AnyValue::String(v) => {
+            out.write_str(r#"{"stringValue":"#)?;
+            json::write_str(out, v)?;
+            out.write_char('}')
+        }
+        AnyValue::Bool(v) => write!(out, r#"{{"boolValue":{}}}"#, v),
+        AnyValue::Int(v) => write!(out, r#"{{"intValue":"{}"}}"#, v),
+        // Non-finite doubles are written as strings, like protobuf's JSON mapping
+        AnyValue::Double(v) if v.is_nan() => out.write_str(r#"{"doubleValue":"NaN"}"#),
+        AnyValue::Double(v) if v.is_infinite() && *v > 0.0 => {
+            out.write_str(r#"{"doubleValue":"Infinity"}"#)
+        }
+        AnyValue::Double(v) if v.is_infinite() => out.write_str(r#"{"doubleValue":"-Infinity"}"#),
+        AnyValue::Double(v) => {
+            out.write_str(r#"{"doubleValue":"#)?;
+            json::write_f64(out, *v)?;
+            out.write_char('}')
+        }
+        AnyValue::Bytes(v) => write!(
+            out,
+            r#"{{"bytesValue":"{}"}}"#,
+            FormatBytes::new(v).format(BytesFormat::Base64)
+        ),
+        AnyValue::Array(values) => {
+            out.write_str(r#"{"arrayValue":{"values":["#)?;
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.write_char(',')?;
+                }
+
+                write_any_value(out, value)?;
+            }
+            out.write_str("]}}")
+        }
+        AnyValue::KvList(values) => {
+            out.write_str(r#"{"kvlistValue":{"values":"#)?;
+            write_attributes(out, values)?;
+            out.write_str("}}")
+        }
+    }
+}
+
+/// An exporter that posts batches of log records to a collector over HTTP.
+///
+/// Records are encoded with [`OtlpJson`] and sent in a `POST` request to the
+/// collector's logs endpoint. Each export uses a new connection. Only plain
+/// `http` endpoints are supported; use a local collector or proxy to reach
+/// endpoints that need TLS.
+///
+/// [`OtlpJson`]: struct.OtlpJson.html
+#[derive(Clone, Debug)]
+pub struct HttpExporter {
+    host: String,
+    port: u16,
+    path: String,
+    timeout: Duration,
+    encoder: OtlpJson,
+}
+
+impl HttpExporter {
+    /// Create an exporter for a collector endpoint, like `http://localhost:4318`.
+    ///
+    /// If the endpoint doesn't have a path then the standard `/v1/logs` path is used.
+    pub fn new(endpoint: &str) -> io::Result<Self> {
+        let rest = endpoint.strip_prefix("http://").ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "only `http://` collector endpoints are supported",
+            )
+        })?;
+
+        let (authority, path) = match rest.find('/') {
+            Some(i) if i + 1 < rest.len() => (&rest[..i], &rest[i..]),
+            Some(i) => (&rest[..i], "/v1/logs"),
+            None => (rest, "/v1/logs"),
+        };
+
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse().map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "the collector endpoint has an invalid port",
+                    )
+                })?,
+            ),
+            None => (authority, 80),
+        };
+
+        if host.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the collector endpoint is missing a host",
+            ));
+        }
+
+        Ok(HttpExporter {
+            host: host.into(),
+            port,
+            path: path.into(),
+            timeout: Duration::from_secs(10),
+            encoder: OtlpJson::new(),
+        })
+    }
+
+    /// Add an attribute to the resource the records are exported from.
+    pub fn resource_attribute(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<AnyValue>,
+    ) -> Self {
+        self.encoder = self.encoder.resource_attribute(key, value);
+        self
+    }
+
+    /// Set the timeout for connecting to, writing to, and reading from the collector.
+    ///
+    /// The default is 10 seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Export a batch of records.
+    ///
+    /// Returns an error if the collector can't be reached or doesn't respond
+    /// with a `2xx` status.
+    pub fn export(&self, records: &[LogRecord]) -> io::Result<()> {
+        let body = self.encoder.encode(records);
+
+        let mut stream = self.connect()?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        write!(
+            stream,
+            "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            self.path,
+            self.host,
+            self.port,
+            body.len()
+        )?;
+        stream.write_all(body.as_bytes())?;
+        stream.flush()?;
+
+        let mut status_line = String::new();
+        BufReader::new(stream).read_line(&mut status_line)?;
+
+        match status_line.split_whitespace().nth(1) {
+            Some(status) if status.starts_with('2') => Ok(()),
+            Some(status) => Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("the collector responded with status {}", status),
+            )),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the collector sent an invalid response",
+            )),
+        }
+    }
+
+    fn connect(&self) -> io::Result<TcpStream> {
+        // IPv6 hosts are written in brackets, but resolved without them
+        let host = self.host.trim_start_matches('[').trim_end_matches(']');
+
+        let mut last_err = None;
+        for addr in (host, self.port).to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, self.timeout) {
+                Ok(stream) => return Ok(stream),
+                Err(err) => last_err = Some(err),
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "the collector host didn't resolve to any addresses",
+            )
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Level;
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::thread;
+
+    fn log_record(record: &Record) -> LogRecord {
+        LogRecord::from_record(record).with_observed_time(UNIX_EPOCH + Duration::from_secs(1))
+    }
+
+    #[test]
+    fn severity() {
+        assert_eq!(1, Level::Trace.otel_severity_number());
+        assert_eq!(5, Level::Debug.otel_severity_number());
+        assert_eq!(9, Level::Info.otel_severity_number());
+        assert_eq!(13, Level::Warn.otel_severity_number());
+        assert_eq!(17, Level::Error.otel_severity_number());
+    }
+
+    #[test]
+    fn from_record() {
+        let kvs: &[(&str, &dyn kv::ToValue)] = &[("user", &"alice"), ("missing", &None::<u8>)];
+
+        let record = log_record(
+            &Record::builder()
+                .level(Level::Warn)
+                .target("shop::checkout")
+                .module_path(Some("shop::checkout"))
+                .file(Some("src/checkout.rs"))
+                .line(Some(42))
+                .args(format_args!("{} retries", 3))
+                .key_values(&kvs)
+                .build(),
+        );
+
+        assert_eq!(1_000_000_000, record.observed_time_unix_nano());
+        assert_eq!(13, record.severity_number());
+        assert_eq!("WARN", record.severity_text());
+        assert_eq!(&AnyValue::String("3 retries".into()), record.body());
+        assert_eq!("shop::checkout", record.scope_name());
+        assert_eq!(
+            &[
+                ("user".to_owned(), AnyValue::String("alice".into())),
+                (
+                    "code.filepath".to_owned(),
+                    AnyValue::String("src/checkout.rs".into())
+                ),
+                ("code.lineno".to_owned(), AnyValue::Int(42)),
+                (
+                    "code.namespace".to_owned(),
+                    AnyValue::String("shop::checkout".into())
+                ),
+            ][..],
+            record.attributes()
+        );
+        assert!(record.attribute("missing").is_none());
+    }
+
+    #[test]
+    fn any_value() {
+        assert_eq!(AnyValue::Int(-1), Value::from(-1i8).into());
+        assert_eq!(AnyValue::Int(1), Value::from(&1u128).into());
+        assert_eq!(
+            AnyValue::String(u64::MAX.to_string()),
+            Value::from(u64::MAX).into()
+        );
+        assert_eq!(AnyValue::Double(1.5), Value::from(1.5).into());
+        assert_eq!(
+            AnyValue::Double(1.5),
+            Value::from(Duration::from_millis(1500)).into()
+        );
+        assert_eq!(
+            AnyValue::String("1970-01-01T00:00:01Z".into()),
+            Value::from(UNIX_EPOCH + Duration::from_secs(1)).into()
+        );
+        assert_eq!(AnyValue::String("a".into()), Value::from('a').into());
+        assert_eq!(
+            AnyValue::Bytes(vec![1, 2]),
+            Value::from_bytes(&[1, 2]).into()
+        );
+        assert_eq!(AnyValue::Empty, kv::ToValue::to_value(&None::<u8>).into());
+        assert_eq!(
+            AnyValue::String("1.0.0".into()),
+            Value::from_display(&"1.0.0").into()
+        );
+        assert_eq!(
+            AnyValue::Array(vec![AnyValue::Int(1), AnyValue::Int(2)]),
+            Value::from_seq(&[1, 2]).into()
+        );
+
+        let mut map = std::collections::BTreeMap::new();
+        map.insert("a", true);
+        assert_eq!(
+            AnyValue::KvList(vec![("a".into(), AnyValue::Bool(true))]),
+            Value::from_map(&map).into()
+        );
+    }
+
+    #[test]
+    fn encode() {
+        let kvs: &[(&str, &dyn kv::ToValue)] = &[
+            ("tags", &["a", "b"]),
+            ("ratio", &0.5),
+            ("ok", &true),
+            ("raw", &Value::from_bytes(b"hi")),
+            ("nan", &f64::NAN),
+        ];
+
+        let first = log_record(
+            &Record::builder()
+                .level(Level::Info)
+                .target("app")
+                .args(format_args!("say \"hi\""))
+                .key_values(&kvs)
+                .build(),
+        );
+        let second = log_record(
+            &Record::builder()
+                .level(Level::Error)
+                .target("db")
+                .args(format_args!("failed"))
+                .build(),
+        );
+        let third = log_record(
+            &Record::builder()
+                .level(Level::Debug)
+                .target("app")
+                .args(format_args!("done"))
+                .build(),
+        );
+
+        let json = OtlpJson::new()
+            .resource_attribute("service.name", "shop")
+            .resource_attribute("service.instance", 7i64)
+            .encode(&[first, second, third]);
+
+        assert_eq!(
+            concat!(
+                r#"{"resourceLogs":[{"resource":{"attributes":["#,
+                r#"{"key":"service.name","value":{"stringValue":"shop"}},"#,
+                r#"{"key":"service.instance","value":{"intValue":"7"}}]},"#,
+                r#""scopeLogs":[{"scope":{"name":"app"},"logRecords":["#,
+                r#"{"observedTimeUnixNano":"1000000000","severityNumber":9,"severityText":"INFO","#,
+                r#""body":{"stringValue":"say \"hi\""},"attributes":["#,
+                r#"{"key":"tags","value":{"arrayValue":{"values":[{"stringValue":"a"},{"stringValue":"b"}]}}},"#,
+                r#"{"key":"ratio","value":{"doubleValue":0.5}},"#,
+                r#"{"key":"ok","value":{"boolValue":true}},"#,
+                r#"{"key":"raw","value":{"bytesValue":"aGk="}},"#,
+                r#"{"key":"nan","value":{"doubleValue":"NaN"}}]},"#,
+                r#"{"observedTimeUnixNano":"1000000000","severityNumber":5,"severityText":"DEBUG","#,
+                r#""body":{"stringValue":"done"},"attributes":[]}]},"#,
+                r#"{"scope":{"name":"db"},"logRecords":["#,
+                r#"{"observedTimeUnixNano":"1000000000","severityNumber":17,"severityText":"ERROR","#,
+                r#""body":{"stringValue":"failed"},"attributes":[]}]}]}]}"#,
+            ),
+            json
+        );
+    }
+
+    #[test]
+    fn encode_empty() {
+        assert_eq!(
+            r#"{"resourceLogs":[{"resource":{"attributes":[]},"scopeLogs":[]}]}"#,
+            OtlpJson::new().encode(&[])
+        );
+    }
+
+    #[test]
+    fn exporter_endpoint() {
+        let exporter = HttpExporter::new("http://collector:4318").unwrap();
+        assert_eq!(
+            ("collector", 4318, "/v1/logs"),
+            (&*exporter.host, exporter.port, &*exporter.path)
+        );
+
+        let exporter = HttpExporter::new("http://collector/custom/logs").unwrap();
+        assert_eq!(
+            ("collector", 80, "/custom/logs"),
+            (&*exporter.host, exporter.port, &*exporter.path)
+        );
+
+        let exporter = HttpExporter::new("http://[::1]:4318/").unwrap();
+        assert_eq!(
+            ("[::1]", 4318, "/v1/logs"),
+            (&*exporter.host, exporter.port, &*exporter.path)
+        );
+
+        assert!(HttpExporter::new("https://collector:4318").is_err());
+        assert!(HttpExporter::new("http://collector:port").is_err());
+        assert!(HttpExporter::new("http://:4318").is_err());
+    }
+
+    // A stand-in collector that accepts one request, responds with the given
+    // status, and returns the request it received.
+    fn collector(status: &'static str) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut head = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(len) = line.strip_prefix("Content-Length: ") {
+                    content_length = len.trim().parse().unwrap();
+                }
+                head.push_str(&line);
+                if line == "\r\n" {
+                    break;
+                }
+            }
+
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+
+            let mut stream = reader.into_inner();
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                status
+            )
+            .unwrap();
+
+            head + &String::from_utf8(body).unwrap()
+        });
+
+        (endpoint, handle)
+    }
+
+    #[test]
+    fn export() {
+        let (endpoint, collector) = collector("200 OK");
+
+        let record = log_record(
+            &Record::builder()
+                .level(Level::Info)
+                .target("app")
+                .args(format_args!("exported"))
+                .build(),
+        );
+
+        let exporter = HttpExporter::new(&endpoint)
+            .unwrap()
+            .resource_attribute("service.name", "shop");
+        exporter.export(std::slice::from_ref(&record)).unwrap();
+
+        let request = collector.join().unwrap();
+        let (head, body) = request.split_once("\r\n\r\n").unwrap();
+
+        assert!(head.starts_with("POST /v1/logs HTTP/1.1\r\n"));
+        assert!(head.contains("Content-Type: application/json\r\n"));
+        assert_eq!(
+            OtlpJson::new()
+                .resource_attribute("service.name", "shop")
+                .encode(&[record]),
+            body
+        );
+    }
+
+    #[test]
+    fn export_error_status() {
+        let (endpoint, collector) = collector("503 Service Unavailable");
+
+        let err = HttpExporter::new(&endpoint)
+            .unwrap()
+            .export(&[])
+            .unwrap_err();
+        collector.join().unwrap();
+
+        assert_eq!("the collector responded with status 503", err.to_string());
+    }
+}
@@ -0,0 +1,501 @@
+//! Owned log records.
+
+use std::fmt;
+
+use crate::{Level, Log, Record};
+
+/// An owned log record.
+///
+/// An `OwnedRecord` captures a [`Record`]'s metadata, formatted message, source
+/// location, and, when the `kv_std` feature is enabled, key-values, so it can
+/// be kept after the call to [`Log::log`] that produced it returns. It can be
+/// replayed through any logger with [`OwnedRecord::log`].
+///
+/// When the `serde` feature is enabled an `OwnedRecord` can be serialized and
+/// deserialized as a map with the fields `level`, `target`, `module_path`,
+/// `file`, `line`, and `message`. When the `kv_serde` feature is also enabled
+/// the record's key-values are included as a map under `key_values`. Missing
+/// optional fields are treated as `None`, and unknown fields are ignored.
+///
+/// # Examples
+///
+/// ```
+/// use log::{Level, Log, Metadata, OwnedRecord, Record};
+///
+/// struct Print;
+///
+/// impl Log for Print {
+///     fn enabled(&self, _: &Metadata) -> bool {
+///         true
+///     }
+///
+///     fn log(&self, record: &Record) {
+///         println!("{} {}", record.level(), record.args());
+///     }
+///
+///     fn flush(&self) {}
+/// }
+///
+/// let owned = OwnedRecord::from_record(
+///     &Record::builder()
+///         .level(Level::Warn)
+///         .args(format_args!("disk is {}% full", 95))
+///         .build(),
+/// );
+///
+/// assert_eq!("disk is 95% full", owned.message());
+///
+/// owned.log(&Print);
+/// ```
+///
+/// [`Record`]: struct.Record.html
+/// [`Log::log`]: trait.Log.html#tymethod.log
+/// [`OwnedRecord::log`]: #method.log
+#[derive(Clone, Debug, PartialEq)]
+pub struct OwnedRecord {
+    level: Level,
+    target: String,
+    module_path: Option<String>,
+    file: Option<String>,
+    line: Option<u32>,
+    message: String,
+    #[cfg(feature = "kv_std")]
+    key_values: crate::kv::OwnedSource,
+}
+
+impl OwnedRecord {
+    /// Capture a record.
+    ///
+    /// The record's message is formatted now. When the `kv_std` feature is enabled
+    /// its key-values are also captured.
+    pub fn from_record(record: &Record) -> Self {
+        OwnedRecord {
+            level: record.level(),
+            target: record.target().into(),
+            module_path: record.module_path().map(Into::into),
+            file: record.file().map(Into::into),
+            line: record.line(),
+            message: match record.args().as_str() {
+                Some(msg) => msg.into(),
+                None => record.args().to_string(),
+            },
+            #[cfg(feature = "kv_std")]
+            key_values: crate::kv::OwnedSource::from_source(record.key_values()),
+        }
+    }
+
+    /// The verbosity level of the message.
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// The name of the target of the directive.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// The module path of the message.
+    pub fn module_path(&self) -> Option<&str> {
+        self.module_path.as_deref()
+    }
+
+    /// The source file containing the message.
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    /// The line containing the message.
+    pub fn line(&self) -> Option<u32> {
+        self.line
+    }
+
+    /// The formatted message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The structured key-value pairs associated with the message.
+    #[cfg(feature = "kv_std")]
+    pub fn key_values(&self) -> &crate::kv::OwnedSource {
+        &self.key_values
+    }
+
+    /// Log this record through a logger.
+    ///
+    /// The logger receives a [`Record`] built from this record's data.
+    ///
+    /// [`Record`]: struct.Record.html
+    pub fn log(&self, logger: &dyn Log) {
+        self.log_impl(logger, format_args!("{}", self.message));
+    }
+
+    fn log_impl(&self, logger: &dyn Log, args: fmt::Arguments) {
+        let mut builder = Record::builder();
+
+        builder
+            .args(args)
+            .level(self.level)
+            .target(&self.target)
+            .module_path(self.module_path.as_deref())
+            .file(self.file.as_deref())
+            .line(self.line);
+
+        #[cfg(feature = "kv_std")]
+        builder.key_values(&self.key_values);
+
+        logger.log(&builder.build());
+    }
+}
+
+impl<'a> From<&'a Record<'a>> for OwnedRecord {
+    fn from(record: &'a Record<'a>) -> Self {
+        OwnedRecord::from_record(record)
+    }
+}
+
+impl fmt::Display for OwnedRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_support {
+    use super::*;
+
+    use serde::de::{self, Deserialize, Deserializer, IgnoredAny, MapAccess};
+    use serde::ser::{Serialize, SerializeMap, Serializer};
+
+    impl Serialize for OwnedRecord {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let mut map = serializer.serialize_map(None)?;
+
+            map.serialize_entry("level", &self.level)?;
+            map.serialize_entry("target", &self.target)?;
+            if let Some(ref module_path) = self.module_path {
+                map.serialize_entry("module_path", module_path)?;
+            }
+            if let Some(ref file) = self.file {
+                map.serialize_entry("file", file)?;
+            }
+            if let Some(line) = self.line {
+                map.serialize_entry("line", &line)?;
+            }
+            map.serialize_entry("message", &self.message)?;
+            #[cfg(feature = "kv_serde")]
+            if !self.key_values.is_empty() {
+                map.serialize_entry("key_values", &self.key_values)?;
+            }
+
+            map.end()
+        }
+    }
+
+    impl<'de> Deserialize<'de> for OwnedRecord {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            struct RecordVisitor;
+
+            impl<'de> de::Visitor<'de> for RecordVisitor {
+                type Value = OwnedRecord;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("a log record")
+                }
+
+                fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+                where
+                    A: MapAccess<'de>,
+                {
+                    let mut level = None;
+                    let mut target = None;
+                    let mut module_path = None;
+                    let mut file = None;
+                    let mut line = None;
+                    let mut message = None;
+                    #[cfg(feature = "kv_serde")]
+                    let mut key_values = None;
+
+                    while let Some(field) = map.next_key::<String>()? {
+                        match &*field {
+                            "level" => level = Some(map.next_value()?),
+                            "target" => target = Some(map.next_value()?),
+                            "module_path" => module_path = map.next_value()?,
+                            "file" => file = map.next_value()?,
+                            "line" => line = map.next_value()?,
+                            "message" => message = Some(map.next_value()?),
+                            #[cfg(feature = "kv_serde")]
+                            "key_values" => key_values = Some(map.next_value()?),
+                            _ => {
+                                map.next_value::<IgnoredAny>()?;
+                            }
+                        }
+                    }
+
+                    Ok(OwnedRecord {
+                        level: level.ok_or_else(|| de::Error::missing_field("level"))?,
+                        target: target.unwrap_or_default(),
+                        module_path,
+                        file,
+                        line,
+                        message: message.ok_or_else(|| de::Error::missing_field("message"))?,
+                        #[cfg(feature = "kv_serde")]
+                        key_values: key_values.unwrap_or_default(),
+                        #[cfg(all(feature = "kv_std", not(feature = "kv_serde")))]
+                        key_values: Default::default(),
+                    })
+                }
+            }
+
+            deserializer.deserialize_map(RecordVisitor)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Metadata;
+    use std::sync::Mutex;
+
+    struct Capture(Mutex<Vec<OwnedRecord>>);
+
+    impl Log for Capture {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(OwnedRecord::from_record(record));
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn from_record() {
+        let owned = OwnedRecord::from_record(
+            &Record::builder()
+                .level(Level::Warn)
+                .target("app")
+                .module_path(Some("app::db"))
+                .file(Some("src/db.rs"))
+                .line(Some(42))
+                .args(format_args!("{} connections", 3))
+                .build(),
+        );
+
+        assert_eq!(Level::Warn, owned.level());
+        assert_eq!("app", owned.target());
+        assert_eq!(Some("app::db"), owned.module_path());
+        assert_eq!(Some("src/db.rs"), owned.file());
+        assert_eq!(Some(42), owned.line());
+        assert_eq!("3 connections", owned.message());
+        assert_eq!("3 connections", owned.to_string());
+    }
+
+    #[test]
+    fn log_replays_record() {
+        let owned = OwnedRecord::from_record(
+            &Record::builder()
+                .level(Level::Error)
+                .target("app")
+                .file(Some("src/main.rs"))
+                .line(Some(7))
+                .args(format_args!("failed"))
+                .build(),
+        );
+
+        let logger = Capture(Mutex::new(Vec::new()));
+        owned.log(&logger);
+
+        assert_eq!(vec![owned], *logger.0.lock().unwrap());
+    }
+
+    #[cfg(feature = "kv_std")]
+    #[test]
+    fn key_values() {
+        use crate::kv::{Key, Source};
+
+        let kvs: &[(&str, i32)] = &[("a", 1), ("b", 2)];
+        let owned = OwnedRecord::from_record(&Record::builder().key_values(&kvs).build());
+
+        assert_eq!(2, owned.key_values().count());
+        assert_eq!(
+            Some(2),
+            owned
+                .key_values()
+                .get(Key::from_str("b"))
+                .and_then(|v| v.to_i64())
+        );
+
+        let logger = Capture(Mutex::new(Vec::new()));
+        owned.log(&logger);
+
+        assert_eq!(vec![owned], *logger.0.lock().unwrap());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use serde_test::{assert_de_tokens, assert_de_tokens_error, assert_ser_tokens, Token};
+
+        let owned = OwnedRecord::from_record(
+            &Record::builder()
+                .level(Level::Info)
+                .target("app")
+                .line(Some(1))
+                .args(format_args!("started"))
+                .build(),
+        );
+
+        assert_ser_tokens(
+            &owned,
+            &[
+                Token::Map { len: None },
+                Token::Str("level"),
+                Token::UnitVariant {
+                    name: "Level",
+                    variant: "INFO",
+                },
+                Token::Str("target"),
+                Token::Str("app"),
+                Token::Str("line"),
+                Token::U32(1),
+                Token::Str("message"),
+                Token::Str("started"),
+                Token::MapEnd,
+            ],
+        );
+
+        // Unknown fields are ignored and optional ones may be null
+        assert_de_tokens(
+            &owned,
+            &[
+                Token::Map { len: None },
+                Token::Str("timestamp"),
+                Token::Str("2024-01-01T00:00:00Z"),
+                Token::Str("message"),
+                Token::Str("started"),
+                Token::Str("file"),
+                Token::None,
+                Token::Str("line"),
+                Token::Some,
+                Token::U32(1),
+                Token::Str("target"),
+                Token::Str("app"),
+                Token::Str("level"),
+                Token::UnitVariant {
+                    name: "Level",
+                    variant: "info",
+                },
+                Token::MapEnd,
+            ],
+        );
+
+        assert_de_tokens_error::<OwnedRecord>(
+            &[
+                Token::Map { len: None },
+                Token::Str("level"),
+                Token::UnitVariant {
+                    name: "Level",
+                    variant: "INFO",
+                },
+                Token::MapEnd,
+            ],
+            "missing field `message`",
+        );
+    }
+
+    #[cfg(feature = "kv_serde")]
+    #[test]
+    fn serde_key_values() {
+        use serde_test::{assert_tokens, Token};
+
+        let kvs: &[(&str, &str)] = &[("user", "alice")];
+        let owned = OwnedRecord::from_record(
+            &Record::builder()
+                .level(Level::Info)
+                .target("app")
+                .args(format_args!("login"))
+                .key_values(&kvs)
+                .build(),
+        );
+
+        assert_tokens(
+            &owned,
+            &[
+                Token::Map { len: None },
+                Token::Str("level"),
+                Token::UnitVariant {
+                    name: "Level",
+                    variant: "INFO",
+                },
+                Token::Str("target"),
+                Token::Str("app"),
+                Token::Str("message"),
+                Token::Str("login"),
+                Token::Str("key_values"),
+                Token::Map { len: Some(1) },
+                Token::Str("user"),
+                Token::Str("alice"),
+                Token::MapEnd,
+                Token::MapEnd,
+            ],
+        );
+    }
+
+    #[cfg(feature = "kv_serde")]
+    #[test]
+    fn json_replay() {
+        use crate::kv::{Key, Source};
+
+        let json = r#"{
+            "level": "warn",
+            "target": "app::db",
+            "line": 12,
+            "message": "slow query",
+            "key_values": {"elapsed_ms": 250, "retry": true, "tables": ["users", "orders"]},
+            "host": "db-1"
+        }"#;
+
+        let owned: OwnedRecord = serde_json::from_str(json).unwrap();
+
+        let logger = Capture(Mutex::new(Vec::new()));
+        owned.log(&logger);
+
+        let replayed = logger.0.lock().unwrap().pop().unwrap();
+        assert_eq!(Level::Warn, replayed.level());
+        assert_eq!("app::db", replayed.target());
+        assert_eq!(Some(12), replayed.line());
+        assert_eq!(None, replayed.file());
+        assert_eq!("slow query", replayed.message());
+
+        let kvs = replayed.key_values();
+        assert_eq!(3, kvs.count());
+        assert_eq!(
+            Some(250),
+            kvs.get(Key::from_str("elapsed_ms"))
+                .and_then(|v| v.to_u64())
+        );
+        assert_eq!(
+            Some(true),
+            kvs.get(Key::from_str("retry")).and_then(|v| v.to_bool())
+        );
+        assert_eq!(
+            "[users, orders]",
+            kvs.get(Key::from_str("tables")).unwrap().to_string()
+        );
+
+        let json = serde_json::to_string(&replayed).unwrap();
+        assert_eq!(owned, serde_json::from_str::<OwnedRecord>(&json).unwrap());
+    }
+}
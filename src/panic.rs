@@ -0,0 +1,282 @@
+//! Route panics through the logger.
+//!
+//! By default a panic message is written straight to `stderr`, bypassing
+//! whatever logging implementation has been installed. The [`install_hook`]
+//! function adds a panic hook that emits a [`Record`] for each panic before
+//! handing over to the previously installed hook.
+//!
+//! Requires the `std` feature.
+//!
+//! # Examples
+//!
+//! ```edition2018
+//! use log::Level;
+//!
+//! # fn main() {
+//! log::panic::install_hook(Level::Error);
+//! # }
+//! ```
+//!
+//! [`install_hook`]: fn.install_hook.html
+//! [`Record`]: ../struct.Record.html
+
+use std::any::Any;
+use std::fmt;
+use std::panic::{self, Location};
+#[cfg(feature = "kv")]
+use std::thread;
+
+use crate::{Level, Log, Record};
+
+/// The target of records emitted for panics.
+pub const TARGET: &str = "panic";
+
+/// Install a panic hook that logs panics at the given `level`.
+///
+/// The hook emits a [`Record`] with the target `"panic"`, the panic payload as
+/// its message, and the file and line of the panic location. When the
+/// `kv` feature is enabled the name of the panicking thread is attached
+/// as the `thread` key-value, unless the panic happens while the thread's
+/// thread-locals are being destroyed. With the `panic_backtrace` feature a
+/// backtrace is also attached as the `backtrace` key-value, if one is
+/// captured according to the `RUST_BACKTRACE` and `RUST_LIB_BACKTRACE`
+/// environment variables.
+///
+/// After the record is logged the logger is flushed and the previously installed
+/// panic hook is called, so the default message on `stderr` is still printed
+/// unless another hook has replaced it.
+///
+/// The record is subject to the same level filtering as the logging macros.
+///
+/// [`Record`]: ../struct.Record.html
+pub fn install_hook(level: Level) {
+    let prev = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        if level <= crate::STATIC_MAX_LEVEL && level <= crate::max_level() {
+            let logger = crate::logger();
+
+            log_panic(logger, level, info.payload(), info.location());
+            logger.flush();
+        }
+
+        prev(info);
+    }));
+}
+
+fn log_panic(
+    logger: &dyn Log,
+    level: Level,
+    payload: &(dyn Any + Send),
+    location: Option<&Location>,
+) {
+    log_impl(
+        logger,
+        format_args!("{}", payload_str(payload)),
+        level,
+        location,
+    );
+}
+
+fn log_impl(logger: &dyn Log, args: fmt::Arguments, level: Level, location: Option<&Location>) {
+    let mut builder = Record::builder();
+
+    builder
+        .args(args)
+        .level(level)
+        .target(TARGET)
+        .file(location.map(Location::file))
+        .line(location.map(Location::line));
+
+    #[cfg(feature = "kv")]
+    {
+        use crate::kv::Value;
+
+        let thread = current_thread();
+        let thread = thread
+            .as_ref()
+            .map(|thread| ("thread", Value::from(thread.name().unwrap_or("<unnamed>"))));
+
+        #[cfg(feature = "panic_backtrace")]
+        let backtrace = backtrace();
+        #[cfg(feature = "panic_backtrace")]
+        let backtrace = backtrace
+            .as_ref()
+            .map(|backtrace| ("backtrace", Value::from_display(backtrace)));
+        #[cfg(not(feature = "panic_backtrace"))]
+        let backtrace = None;
+
+        let kvs = [thread, backtrace];
+        let kvs = &kvs[..];
+        logger.log(&builder.key_values(&kvs).build());
+    }
+
+    #[cfg(not(feature = "kv"))]
+    logger.log(&builder.build());
+}
+
+// The panicking thread, unless its thread-locals are being destroyed
+//
+// `thread::current` panics once the thread's handle has been destroyed,
+// which aborts a panic raised by a thread-local's destructor. The handle is
+// kept in a thread-local of our own instead, and `try_with` fails once it's
+// been destroyed.
+#[cfg(feature = "kv")]
+fn current_thread() -> Option<thread::Thread> {
+    thread_local! {
+        static CURRENT: thread::Thread = thread::current();
+    }
+
+    CURRENT.try_with(thread::Thread::clone).ok()
+}
+
+// Capture a backtrace, if `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` ask for one
+#[cfg(feature = "panic_backtrace")]
+#[clippy::msrv = "1.65"]
+fn backtrace() -> Option<std::backtrace::Backtrace> {
+    let backtrace = std::backtrace::Backtrace::capture();
+
+    match backtrace.status() {
+        std::backtrace::BacktraceStatus::Captured => Some(backtrace),
+        _ => None,
+    }
+}
+
+fn payload_str(payload: &(dyn Any + Send)) -> &str {
+    if let Some(s) = payload.downcast_ref::<&'static str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s
+    } else {
+        "Box<dyn Any>"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Metadata;
+    use std::sync::Mutex;
+    use std::thread;
+
+    #[derive(Debug, PartialEq)]
+    struct Captured {
+        level: Level,
+        target: String,
+        message: String,
+        file: Option<String>,
+        line: Option<u32>,
+        #[cfg(feature = "kv")]
+        thread: Option<String>,
+    }
+
+    struct Capture(Mutex<Vec<Captured>>);
+
+    impl Log for Capture {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            self.0.lock().unwrap().push(Captured {
+                level: record.level(),
+                target: record.target().into(),
+                message: record.args().to_string(),
+                file: record.file().map(Into::into),
+                line: record.line(),
+                #[cfg(feature = "kv")]
+                thread: record
+                    .key_values()
+                    .get("thread".into())
+                    .map(|v| v.to_string()),
+            });
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn payload_str_static() {
+        let payload: Box<dyn Any + Send> = Box::new("a static str");
+        assert_eq!("a static str", payload_str(&*payload));
+    }
+
+    #[test]
+    fn payload_str_string() {
+        let payload: Box<dyn Any + Send> = Box::new(String::from("a string"));
+        assert_eq!("a string", payload_str(&*payload));
+    }
+
+    #[test]
+    fn payload_str_other() {
+        let payload: Box<dyn Any + Send> = Box::new(42);
+        assert_eq!("Box<dyn Any>", payload_str(&*payload));
+    }
+
+    #[test]
+    fn log_panic_record() {
+        let logger = Capture(Mutex::new(Vec::new()));
+        let location = Location::caller();
+
+        let handle = thread::Builder::new()
+            .name("panicking".into())
+            .spawn(move || {
+                let logger = &logger;
+                log_panic(logger, Level::Warn, &"boom", Some(location));
+                logger.0.lock().unwrap().pop().unwrap()
+            })
+            .unwrap();
+
+        assert_eq!(
+            Captured {
+                level: Level::Warn,
+                target: "panic".into(),
+                message: "boom".into(),
+                file: Some(location.file().into()),
+                line: Some(location.line()),
+                #[cfg(feature = "kv")]
+                thread: Some("panicking".into()),
+            },
+            handle.join().unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "kv", target_os = "linux"))]
+    fn log_panic_during_thread_local_teardown() {
+        use std::cell::RefCell;
+        use std::sync::Arc;
+
+        struct LogOnDrop(Arc<Capture>);
+
+        impl Drop for LogOnDrop {
+            fn drop(&mut self) {
+                log_panic(&*self.0, Level::Error, &"dropped", None);
+            }
+        }
+
+        thread_local! {
+            static LOG_ON_DROP: RefCell<Option<LogOnDrop>> = const { RefCell::new(None) };
+        }
+
+        let logger = Arc::new(Capture(Mutex::new(Vec::new())));
+
+        let thread_logger = logger.clone();
+        thread::Builder::new()
+            .name("tearing-down".into())
+            .spawn(move || {
+                // Thread-locals are destroyed in the reverse order they're
+                // initialized in, so the cached handle is gone by the time
+                // `LOG_ON_DROP` is dropped
+                LOG_ON_DROP.with(|slot| *slot.borrow_mut() = Some(LogOnDrop(thread_logger)));
+                assert!(current_thread().is_some());
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        let captured = logger.0.lock().unwrap().pop().unwrap();
+        assert_eq!("dropped", captured.message);
+        assert_eq!(None, captured.thread);
+    }
+}
@@ -0,0 +1,445 @@
+//! An in-memory ring buffer logger.
+//!
+//! [`RingBufferLogger`] formats records into a fixed-size byte buffer without
+//! allocating, so it's usable on targets with no heap and no standard output.
+//! When the buffer is full the oldest records are overwritten. The contents can
+//! be drained from anywhere, including an interrupt handler, and dumped after a
+//! crash.
+//!
+//! # Examples
+//!
+//! ```edition2018
+//! use log::ring::RingBufferLogger;
+//! use log::{info, LevelFilter};
+//!
+//! static LOGGER: RingBufferLogger<1024> = RingBufferLogger::new(LevelFilter::Info);
+//!
+//! # fn main() {
+//! # #[cfg(target_has_atomic = "ptr")]
+//! log::set_logger(&LOGGER).unwrap();
+//! # #[cfg(target_has_atomic = "ptr")]
+//! log::set_max_level(LevelFilter::Info);
+//!
+//! info!("hello ring");
+//!
+//! let mut out = [0; 64];
+//! while let Some(read) = LOGGER.drain(&mut out) {
+//!     if read == 0 {
+//!         break;
+//!     }
+//!
+//!     // Ship `&out[..read]` somewhere...
+//! }
+//! # }
+//! ```
+//!
+//! # Synchronization
+//!
+//! The buffer is guarded by a lock that is never waited on. If a record is
+//! logged while the buffer is being drained, or the buffer is drained while a
+//! record is being logged, then the call returns immediately instead; a record
+//! that couldn't be written is counted by [`RingBufferLogger::dropped`]. This
+//! makes it safe to log or drain from an interrupt handler that preempts the
+//! other operation.
+//!
+//! On platforms without atomics the lock is a plain `Cell` like the rest of
+//! this crate, so it relies on there being a single core.
+//!
+//! [`RingBufferLogger`]: struct.RingBufferLogger.html
+//! [`RingBufferLogger::dropped`]: struct.RingBufferLogger.html#method.dropped
+
+use std::cell::UnsafeCell;
+use std::fmt::{self, Write};
+
+use crate::{AtomicUsize, LevelFilter, Log, Metadata, Ordering, Record};
+
+const UNLOCKED: usize = 0;
+const LOCKED: usize = 1;
+
+/// A logger that writes formatted records into a fixed-size ring buffer.
+///
+/// Each record is written as a single line:
+///
+/// ```text
+/// LEVEL target: message
+/// ```
+///
+/// When there isn't enough space for a new record, whole records are evicted
+/// from the front of the buffer to make room. A single record that is longer
+/// than the buffer keeps only its most recent `N` bytes.
+pub struct RingBufferLogger<const N: usize> {
+    level: LevelFilter,
+    lock: AtomicUsize,
+    dropped: AtomicUsize,
+    ring: UnsafeCell<Ring<N>>,
+}
+
+// SAFETY: The `ring` is only accessed while `lock` is held,
+// or by the caller of `dump`, which promises that nothing else is running.
+unsafe impl<const N: usize> Sync for RingBufferLogger<N> {}
+
+impl<const N: usize> RingBufferLogger<N> {
+    /// Create an empty logger that accepts records up to the given level.
+    pub const fn new(level: LevelFilter) -> Self {
+        RingBufferLogger {
+            level,
+            lock: AtomicUsize::new(UNLOCKED),
+            dropped: AtomicUsize::new(0),
+            ring: UnsafeCell::new(Ring {
+                buf: [0; N],
+                start: 0,
+                len: 0,
+            }),
+        }
+    }
+
+    /// The capacity of the buffer in bytes.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// The number of records that were dropped because the buffer was busy.
+    pub fn dropped(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Move the oldest buffered bytes into `out`.
+    ///
+    /// Returns the number of bytes written to `out`, which is `0` once the
+    /// buffer is empty. Returns `None` without reading anything if a record is
+    /// currently being written to the buffer.
+    pub fn drain(&self, out: &mut [u8]) -> Option<usize> {
+        let mut ring = self.try_lock()?;
+
+        let read = out.len().min(ring.len);
+        if read == 0 {
+            return Some(0);
+        }
+
+        for (i, b) in out[..read].iter_mut().enumerate() {
+            *b = ring.buf[(ring.start + i) % N];
+        }
+
+        ring.start = (ring.start + read) % N;
+        ring.len -= read;
+
+        Some(read)
+    }
+
+    /// Inspect the buffered bytes without draining them or taking the lock.
+    ///
+    /// The contents are passed to `f` in order, as up to two slices when the
+    /// buffer has wrapped around.
+    ///
+    /// This is intended for use after a crash, such as from a panic handler or
+    /// fault handler, where the code that was logging may have been interrupted
+    /// part way through a record and will never release the lock.
+    ///
+    /// # Safety
+    ///
+    /// Nothing may log to or drain this logger while this function runs.
+    pub unsafe fn dump(&self, mut f: impl FnMut(&[u8])) {
+        let ring = &*self.ring.get();
+        let (front, back) = ring.as_slices();
+
+        f(front);
+        if !back.is_empty() {
+            f(back);
+        }
+    }
+
+    fn try_lock(&self) -> Option<Guard<'_, N>> {
+        #[cfg(target_has_atomic = "ptr")]
+        let acquired = self
+            .lock
+            .compare_exchange(UNLOCKED, LOCKED, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok();
+
+        // Without atomics we assume a single core, where anything that preempts
+        // us between the load and store runs to completion before we resume.
+        #[cfg(not(target_has_atomic = "ptr"))]
+        let acquired = if self.lock.load(Ordering::Relaxed) == UNLOCKED {
+            self.lock.store(LOCKED, Ordering::Relaxed);
+            true
+        } else {
+            false
+        };
+
+        if acquired {
+            Some(Guard { logger: self })
+        } else {
+            None
+        }
+    }
+}
+
+impl<const N: usize> Log for RingBufferLogger<N> {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        match self.try_lock() {
+            Some(mut ring) => {
+                let _ = writeln!(
+                    &mut *ring,
+                    "{} {}: {}",
+                    record.level(),
+                    record.target(),
+                    record.args()
+                );
+            }
+            None => {
+                #[cfg(target_has_atomic = "ptr")]
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+
+                // Without atomic read-modify-write operations the count may
+                // miss a record dropped by something that preempts us here.
+                #[cfg(not(target_has_atomic = "ptr"))]
+                self.dropped.store(self.dropped() + 1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+impl<const N: usize> fmt::Debug for RingBufferLogger<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RingBufferLogger")
+            .field("level", &self.level)
+            .field("capacity", &N)
+            .field("dropped", &self.dropped())
+            .finish()
+    }
+}
+
+struct Ring<const N: usize> {
+    buf: [u8; N],
+    start: usize,
+    len: usize,
+}
+
+impl<const N: usize> Ring<N> {
+    fn push(&mut self, b: u8) {
+        if N == 0 {
+            return;
+        }
+
+        if self.len == N {
+            self.evict();
+        }
+
+        self.buf[(self.start + self.len) % N] = b;
+        self.len += 1;
+    }
+
+    // Evict the oldest record, or a single byte if the buffer only holds part
+    // of the record currently being written.
+    fn evict(&mut self) {
+        let mut evicted = 0;
+        while evicted < self.len {
+            let b = self.buf[(self.start + evicted) % N];
+            evicted += 1;
+
+            if b == b'\n' {
+                break;
+            }
+        }
+
+        if evicted == self.len && self.buf[(self.start + self.len - 1) % N] != b'\n' {
+            evicted = 1;
+        }
+
+        self.start = (self.start + evicted) % N;
+        self.len -= evicted;
+    }
+
+    fn as_slices(&self) -> (&[u8], &[u8]) {
+        let end = self.start + self.len;
+
+        if end <= N {
+            (&self.buf[self.start..end], &[])
+        } else {
+            (&self.buf[self.start..], &self.buf[..end - N])
+        }
+    }
+}
+
+impl<const N: usize> Write for Ring<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for &b in s.as_bytes() {
+            self.push(b);
+        }
+
+        Ok(())
+    }
+}
+
+struct Guard<'a, const N: usize> {
+    logger: &'a RingBufferLogger<N>,
+}
+
+impl<'a, const N: usize> std::ops::Deref for Guard<'a, N> {
+    type Target = Ring<N>;
+
+    fn deref(&self) -> &Ring<N> {
+        // SAFETY: The lock is held for the lifetime of the guard
+        unsafe { &*self.logger.ring.get() }
+    }
+}
+
+impl<'a, const N: usize> std::ops::DerefMut for Guard<'a, N> {
+    fn deref_mut(&mut self) -> &mut Ring<N> {
+        // SAFETY: The lock is held for the lifetime of the guard
+        unsafe { &mut *self.logger.ring.get() }
+    }
+}
+
+impl<'a, const N: usize> Drop for Guard<'a, N> {
+    fn drop(&mut self) {
+        self.logger.lock.store(UNLOCKED, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Level;
+
+    fn log<const N: usize>(logger: &RingBufferLogger<N>, level: Level, msg: &str) {
+        logger.log(
+            &Record::builder()
+                .args(format_args!("{}", msg))
+                .level(level)
+                .target("ring")
+                .build(),
+        );
+    }
+
+    fn drain_all<const N: usize>(logger: &RingBufferLogger<N>) -> String {
+        let mut contents = Vec::new();
+        let mut out = [0; 7];
+
+        while let Some(read) = logger.drain(&mut out) {
+            if read == 0 {
+                break;
+            }
+
+            contents.extend_from_slice(&out[..read]);
+        }
+
+        String::from_utf8(contents).unwrap()
+    }
+
+    fn dump_all<const N: usize>(logger: &RingBufferLogger<N>) -> String {
+        let mut contents = Vec::new();
+        unsafe { logger.dump(|bytes| contents.extend_from_slice(bytes)) };
+
+        String::from_utf8(contents).unwrap()
+    }
+
+    #[test]
+    fn log_and_drain() {
+        let logger = RingBufferLogger::<128>::new(LevelFilter::Info);
+
+        log(&logger, Level::Info, "first");
+        log(&logger, Level::Debug, "filtered");
+        log(&logger, Level::Error, "second");
+
+        assert_eq!("INFO ring: first\nERROR ring: second\n", drain_all(&logger));
+        assert_eq!("", drain_all(&logger));
+    }
+
+    #[test]
+    fn overwrite_oldest_records() {
+        let logger = RingBufferLogger::<39>::new(LevelFilter::Trace);
+
+        log(&logger, Level::Info, "record 1");
+        log(&logger, Level::Info, "record 2");
+        log(&logger, Level::Info, "record 3");
+
+        assert_eq!("INFO ring: record 3\n", dump_all(&logger));
+        assert_eq!("INFO ring: record 3\n", drain_all(&logger));
+    }
+
+    #[test]
+    fn dump_wrapped() {
+        let logger = RingBufferLogger::<48>::new(LevelFilter::Trace);
+
+        log(&logger, Level::Info, "a");
+        log(&logger, Level::Info, "b");
+        log(&logger, Level::Info, "c");
+        log(&logger, Level::Info, "d");
+
+        let mut slices = 0;
+        unsafe { logger.dump(|_| slices += 1) };
+
+        assert_eq!(2, slices);
+        assert_eq!(
+            "INFO ring: b\nINFO ring: c\nINFO ring: d\n",
+            dump_all(&logger)
+        );
+    }
+
+    #[test]
+    fn oversized_record() {
+        let logger = RingBufferLogger::<8>::new(LevelFilter::Trace);
+
+        log(&logger, Level::Info, "a long message");
+
+        assert_eq!("message\n", dump_all(&logger));
+    }
+
+    #[test]
+    fn busy() {
+        let logger = RingBufferLogger::<64>::new(LevelFilter::Trace);
+
+        {
+            let _guard = logger.try_lock().unwrap();
+
+            log(&logger, Level::Info, "dropped");
+            assert_eq!(None, logger.drain(&mut [0; 8]));
+        }
+
+        log(&logger, Level::Info, "kept");
+
+        assert_eq!(1, logger.dropped());
+        assert_eq!("INFO ring: kept\n", drain_all(&logger));
+    }
+
+    #[test]
+    fn busy_threads() {
+        use std::thread;
+
+        const THREADS: usize = 8;
+        const RECORDS: usize = 1000;
+
+        static LOGGER: RingBufferLogger<64> = RingBufferLogger::new(LevelFilter::Trace);
+
+        // Hold the lock so every record logged by the threads is dropped
+        let guard = LOGGER.try_lock().unwrap();
+
+        let threads: Vec<_> = (0..THREADS)
+            .map(|_| {
+                thread::spawn(|| {
+                    for _ in 0..RECORDS {
+                        log(&LOGGER, Level::Info, "dropped");
+                    }
+                })
+            })
+            .collect();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        drop(guard);
+
+        assert_eq!(THREADS * RECORDS, LOGGER.dropped());
+    }
+}
@@ -1,12 +1,20 @@
+//! Serde support.
+//!
+//! `Level` and `LevelFilter` are serialized as their upper-case names by
+//! default. The modules here provide other representations that can be used
+//! with `#[serde(with = "...")]`.
+//!
+//! Requires the `serde` feature.
+
 #![cfg(feature = "serde")]
 
 use serde::de::{
     Deserialize, DeserializeSeed, Deserializer, EnumAccess, Error, Unexpected, VariantAccess,
     Visitor,
 };
-use serde::ser::{Serialize, Serializer};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
 
-use crate::{Level, LevelFilter, LOG_LEVEL_NAMES};
+use crate::{Level, LevelFilter, Metadata, Record, LOG_LEVEL_NAMES};
 
 use std::fmt;
 use std::str::{self, FromStr};
@@ -202,10 +210,329 @@ impl<'de> Deserialize<'de> for LevelFilter {
     }
 }
 
+// A visitor for the helper modules that accepts a level by name or by number.
+struct AnyForm<T> {
+    expecting: &'static str,
+    from_number: fn(u64) -> Option<T>,
+    from_name: fn(&str) -> Option<T>,
+}
+
+impl<'de, T> Visitor<'de> for AnyForm<T> {
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.expecting)
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        (self.from_number)(v).ok_or_else(|| Error::invalid_value(Unexpected::Unsigned(v), &self))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        u64::try_from(v)
+            .ok()
+            .and_then(self.from_number)
+            .ok_or_else(|| Error::invalid_value(Unexpected::Signed(v), &self))
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        (self.from_name)(s).ok_or_else(|| Error::invalid_value(Unexpected::Str(s), &self))
+    }
+
+    fn visit_bytes<E>(self, value: &[u8]) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        let variant = str::from_utf8(value)
+            .map_err(|_| Error::invalid_value(Unexpected::Bytes(value), &self))?;
+
+        self.visit_str(variant)
+    }
+}
+
+/// Alternative serde representations for [`Level`].
+///
+/// Each module can be used with `#[serde(with = "...")]` on a `Level` field.
+/// They all deserialize a level from its name in any case, or from the numbers
+/// they serialize, so they need a self-describing format like JSON.
+///
+/// # Examples
+///
+/// ```
+/// use log::Level;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Entry {
+///     #[serde(with = "log::serde::level::lowercase")]
+///     level: Level,
+///     #[serde(with = "log::serde::level::otel_severity")]
+///     severity_number: Level,
+/// }
+///
+/// let entry = Entry {
+///     level: Level::Warn,
+///     severity_number: Level::Warn,
+/// };
+///
+/// assert_eq!(
+///     r#"{"level":"warn","severity_number":13}"#,
+///     serde_json::to_string(&entry).unwrap()
+/// );
+///
+/// let entry: Entry = serde_json::from_str(r#"{"level":"WARN","severity_number":"warn"}"#).unwrap();
+/// assert_eq!(Level::Warn, entry.severity_number);
+/// ```
+///
+/// [`Level`]: ../../enum.Level.html
+pub mod level {
+    use super::*;
+
+    fn from_name(s: &str) -> Option<Level> {
+        // Case insensitive.
+        Level::from_str(s).ok()
+    }
+
+    /// Serialize a level as its lower-case name, like `"warn"`.
+    pub mod lowercase {
+        use super::*;
+
+        /// Serialize a level as its lower-case name.
+        pub fn serialize<S>(level: &Level, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.serialize_str(match *level {
+                Level::Error => "error",
+                Level::Warn => "warn",
+                Level::Info => "info",
+                Level::Debug => "debug",
+                Level::Trace => "trace",
+            })
+        }
+
+        /// Deserialize a level from its name in any case, or its number.
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Level, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            super::numeric::deserialize(deserializer)
+        }
+    }
+
+    /// Serialize a level as a number, from `1` for `Error` to `5` for `Trace`.
+    ///
+    /// These are the same numbers `Level` converts to with `as usize`.
+    pub mod numeric {
+        use super::*;
+
+        /// Serialize a level as a number.
+        pub fn serialize<S>(level: &Level, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.serialize_u64(*level as u64)
+        }
+
+        /// Deserialize a level from its name in any case, or its number.
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Level, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_any(AnyForm {
+                expecting: "log level name or number",
+                from_number: |n| usize::try_from(n).ok().and_then(Level::from_usize),
+                from_name,
+            })
+        }
+    }
+
+    /// Serialize a level as an OpenTelemetry severity number.
+    ///
+    /// Levels are serialized as [`Level::otel_severity_number`].
+    ///
+    /// Deserializing accepts any number from `1` to `24`, mapping each range back
+    /// to its level. `FATAL` severities map to `Error`. Severity texts like `"FATAL"`
+    /// are also accepted.
+    ///
+    /// [`Level::otel_severity_number`]: ../../../enum.Level.html#method.otel_severity_number
+    pub mod otel_severity {
+        use super::*;
+
+        /// Get the level for an OpenTelemetry severity number.
+        ///
+        /// Returns `None` if the number isn't between `1` and `24`.
+        pub fn to_level(severity: u8) -> Option<Level> {
+            match severity {
+                1..=4 => Some(Level::Trace),
+                5..=8 => Some(Level::Debug),
+                9..=12 => Some(Level::Info),
+                13..=16 => Some(Level::Warn),
+                17..=24 => Some(Level::Error),
+                _ => None,
+            }
+        }
+
+        /// Serialize a level as an OpenTelemetry severity number.
+        pub fn serialize<S>(level: &Level, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.serialize_u64(level.otel_severity_number() as u64)
+        }
+
+        /// Deserialize a level from its name in any case, or an OpenTelemetry
+        /// severity number.
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Level, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_any(AnyForm {
+                expecting: "log level name or OpenTelemetry severity number",
+                from_number: |n| u8::try_from(n).ok().and_then(to_level),
+                from_name: |s| {
+                    if s.eq_ignore_ascii_case("fatal") {
+                        Some(Level::Error)
+                    } else {
+                        from_name(s)
+                    }
+                },
+            })
+        }
+    }
+}
+
+/// Alternative serde representations for [`LevelFilter`].
+///
+/// These work like the modules in [`level`](../level/index.html).
+///
+/// [`LevelFilter`]: ../../enum.LevelFilter.html
+pub mod level_filter {
+    use super::*;
+
+    fn from_name(s: &str) -> Option<LevelFilter> {
+        // Case insensitive.
+        LevelFilter::from_str(s).ok()
+    }
+
+    /// Serialize a level filter as its lower-case name, like `"off"`.
+    pub mod lowercase {
+        use super::*;
+
+        /// Serialize a level filter as its lower-case name.
+        pub fn serialize<S>(filter: &LevelFilter, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.serialize_str(match *filter {
+                LevelFilter::Off => "off",
+                LevelFilter::Error => "error",
+                LevelFilter::Warn => "warn",
+                LevelFilter::Info => "info",
+                LevelFilter::Debug => "debug",
+                LevelFilter::Trace => "trace",
+            })
+        }
+
+        /// Deserialize a level filter from its name in any case, or its number.
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<LevelFilter, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            super::numeric::deserialize(deserializer)
+        }
+    }
+
+    /// Serialize a level filter as a number, from `0` for `Off` to `5` for `Trace`.
+    ///
+    /// These are the same numbers `LevelFilter` converts to with `as usize`.
+    pub mod numeric {
+        use super::*;
+
+        /// Serialize a level filter as a number.
+        pub fn serialize<S>(filter: &LevelFilter, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.serialize_u64(*filter as u64)
+        }
+
+        /// Deserialize a level filter from its name in any case, or its number.
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<LevelFilter, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_any(AnyForm {
+                expecting: "log level filter name or number",
+                from_number: |n| usize::try_from(n).ok().and_then(LevelFilter::from_usize),
+                from_name,
+            })
+        }
+    }
+}
+
+impl<'a> Serialize for Metadata<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("Metadata", 2)?;
+        s.serialize_field("level", &self.level())?;
+        s.serialize_field("target", self.target())?;
+        s.end()
+    }
+}
+
+// Records are serialized with the same fields an `OwnedRecord` is deserialized from.
+impl<'a> Serialize for Record<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        struct Message<'a>(fmt::Arguments<'a>);
+
+        impl<'a> Serialize for Message<'a> {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                match self.0.as_str() {
+                    Some(msg) => serializer.serialize_str(msg),
+                    None => serializer.collect_str(&self.0),
+                }
+            }
+        }
+
+        let len = if cfg!(feature = "kv_serde") { 7 } else { 6 };
+
+        let mut s = serializer.serialize_struct("Record", len)?;
+        s.serialize_field("level", &self.level())?;
+        s.serialize_field("target", self.target())?;
+        s.serialize_field("module_path", &self.module_path())?;
+        s.serialize_field("file", &self.file())?;
+        s.serialize_field("line", &self.line())?;
+        s.serialize_field("message", &Message(*self.args()))?;
+        #[cfg(feature = "kv_serde")]
+        s.serialize_field("key_values", &crate::kv::source::as_map(self.key_values()))?;
+        s.end()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{Level, LevelFilter};
-    use serde_test::{assert_de_tokens, assert_de_tokens_error, assert_tokens, Token};
+    use crate::{Level, LevelFilter, Metadata, Record};
+    use serde_test::{
+        assert_de_tokens, assert_de_tokens_error, assert_ser_tokens, assert_tokens, Token,
+    };
 
     fn level_token(variant: &'static str) -> Token {
         Token::UnitVariant {
@@ -394,4 +721,181 @@ mod tests {
                    `OFF`, `ERROR`, `WARN`, `INFO`, `DEBUG`, `TRACE`";
         assert_de_tokens_error::<LevelFilter>(&[level_filter_token("errorx")], msg);
     }
+
+    #[test]
+    fn test_metadata_ser() {
+        let metadata = Metadata::builder().level(Level::Warn).target("app").build();
+
+        assert_ser_tokens(
+            &metadata,
+            &[
+                Token::Struct {
+                    name: "Metadata",
+                    len: 2,
+                },
+                Token::Str("level"),
+                level_token("WARN"),
+                Token::Str("target"),
+                Token::Str("app"),
+                Token::StructEnd,
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "kv_serde"))]
+    fn test_record_ser() {
+        assert_ser_tokens(
+            &Record::builder()
+                .level(Level::Info)
+                .target("app")
+                .line(Some(7))
+                .args(format_args!("{} started", "app"))
+                .build(),
+            &[
+                Token::Struct {
+                    name: "Record",
+                    len: 6,
+                },
+                Token::Str("level"),
+                level_token("INFO"),
+                Token::Str("target"),
+                Token::Str("app"),
+                Token::Str("module_path"),
+                Token::None,
+                Token::Str("file"),
+                Token::None,
+                Token::Str("line"),
+                Token::Some,
+                Token::U32(7),
+                Token::Str("message"),
+                Token::Str("app started"),
+                Token::StructEnd,
+            ],
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "kv_serde")]
+    fn test_record_ser_key_values() {
+        let kvs: &[(&str, i32)] = &[("a", -1)];
+        let record = Record::builder()
+            .level(Level::Info)
+            .target("app")
+            .args(format_args!("started"))
+            .key_values(&kvs)
+            .build();
+
+        assert_ser_tokens(
+            &record,
+            &[
+                Token::Struct {
+                    name: "Record",
+                    len: 7,
+                },
+                Token::Str("level"),
+                level_token("INFO"),
+                Token::Str("target"),
+                Token::Str("app"),
+                Token::Str("module_path"),
+                Token::None,
+                Token::Str("file"),
+                Token::None,
+                Token::Str("line"),
+                Token::None,
+                Token::Str("message"),
+                Token::Str("started"),
+                Token::Str("key_values"),
+                Token::Map { len: Some(1) },
+                Token::Str("a"),
+                Token::I64(-1),
+                Token::MapEnd,
+                Token::StructEnd,
+            ],
+        );
+
+        // A serialized record can be read back as an `OwnedRecord`
+        let json = serde_json::to_string(&record).unwrap();
+        assert_eq!(
+            crate::OwnedRecord::from_record(&record),
+            serde_json::from_str(&json).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_level_helpers() {
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        #[serde(transparent)]
+        struct Lowercase(#[serde(with = "crate::serde::level::lowercase")] Level);
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        #[serde(transparent)]
+        struct Numeric(#[serde(with = "crate::serde::level::numeric")] Level);
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        #[serde(transparent)]
+        struct Otel(#[serde(with = "crate::serde::level::otel_severity")] Level);
+
+        assert_tokens(&Lowercase(Level::Warn), &[Token::Str("warn")]);
+        assert_tokens(&Numeric(Level::Warn), &[Token::U64(2)]);
+        assert_tokens(&Otel(Level::Warn), &[Token::U64(13)]);
+
+        // Every helper accepts names in any case
+        assert_de_tokens(&Lowercase(Level::Trace), &[Token::Str("TRACE")]);
+        assert_de_tokens(&Numeric(Level::Trace), &[Token::Str("Trace")]);
+        assert_de_tokens(&Otel(Level::Trace), &[Token::Str("trace")]);
+
+        assert_de_tokens(&Lowercase(Level::Error), &[Token::U64(1)]);
+        assert_de_tokens(&Numeric(Level::Info), &[Token::I64(3)]);
+        assert_de_tokens(&Otel(Level::Info), &[Token::U64(12)]);
+        assert_de_tokens(&Otel(Level::Error), &[Token::U64(21)]);
+        assert_de_tokens(&Otel(Level::Error), &[Token::Str("FATAL")]);
+
+        assert_de_tokens_error::<Numeric>(
+            &[Token::U64(6)],
+            "invalid value: integer `6`, expected log level name or number",
+        );
+        assert_de_tokens_error::<Otel>(
+            &[Token::U64(25)],
+            "invalid value: integer `25`, expected log level name or OpenTelemetry severity number",
+        );
+        assert_de_tokens_error::<Lowercase>(
+            &[Token::Str("loud")],
+            "invalid value: string \"loud\", expected log level name or number",
+        );
+
+        for level in Level::iter() {
+            assert_eq!(
+                Some(level),
+                crate::serde::level::otel_severity::to_level(level.otel_severity_number())
+            );
+        }
+    }
+
+    #[test]
+    fn test_level_filter_helpers() {
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        #[serde(transparent)]
+        struct Lowercase(#[serde(with = "crate::serde::level_filter::lowercase")] LevelFilter);
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        #[serde(transparent)]
+        struct Numeric(#[serde(with = "crate::serde::level_filter::numeric")] LevelFilter);
+
+        assert_tokens(&Lowercase(LevelFilter::Off), &[Token::Str("off")]);
+        assert_tokens(&Numeric(LevelFilter::Off), &[Token::U64(0)]);
+        assert_tokens(&Numeric(LevelFilter::Trace), &[Token::U64(5)]);
+
+        assert_de_tokens(&Lowercase(LevelFilter::Debug), &[Token::U64(4)]);
+        assert_de_tokens(&Numeric(LevelFilter::Debug), &[Token::Str("DEBUG")]);
+
+        assert_de_tokens_error::<Numeric>(
+            &[Token::I64(-1)],
+            "invalid value: integer `-1`, expected log level filter name or number",
+        );
+    }
 }
@@ -0,0 +1,368 @@
+//! Bridge `std::io::Write` and `std::fmt::Write` into log records.
+//!
+//! Some libraries report diagnostics by writing to a handle rather than
+//! through the logging facade. A [`LogWriter`] can be handed to them in place of
+//! that handle; it buffers what's written and emits each complete line as a
+//! [`Record`].
+//!
+//! Requires the `std` feature.
+//!
+//! # Examples
+//!
+//! Capturing the `stderr` of a child process:
+//!
+//! ```edition2018,no_run
+//! use std::io;
+//! use std::process::{Command, Stdio};
+//!
+//! use log::writer::LogWriter;
+//! use log::Level;
+//!
+//! # fn main() -> io::Result<()> {
+//! let mut child = Command::new("cargo")
+//!     .arg("build")
+//!     .stderr(Stdio::piped())
+//!     .spawn()?;
+//!
+//! let mut stderr = child.stderr.take().unwrap();
+//! io::copy(&mut stderr, &mut LogWriter::new(Level::Warn, "cargo"))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`LogWriter`]: struct.LogWriter.html
+//! [`Record`]: ../struct.Record.html
+
+use std::fmt;
+use std::io;
+
+use crate::{Level, Log, Record};
+
+/// An adapter that turns written bytes into log records.
+///
+/// Bytes are buffered until a newline is written, at which point the line,
+/// without its trailing `\n` or `\r\n`, is logged as the message of a
+/// [`Record`] with the configured level and target. Lines that aren't valid
+/// UTF-8 are logged lossily, with invalid sequences replaced by `U+FFFD`.
+///
+/// A trailing partial line is logged when the writer is flushed or dropped.
+/// Lines longer than the [maximum line length] are split, with each piece
+/// logged as a record as soon as the maximum is reached, so a writer never
+/// buffers more than that many bytes.
+///
+/// Records are subject to the same level filtering as the logging macros.
+///
+/// [`Record`]: ../struct.Record.html
+/// [maximum line length]: #method.set_max_line_len
+pub struct LogWriter {
+    logger: Option<&'static dyn Log>,
+    level: Level,
+    target: String,
+    max_line_len: usize,
+    buf: Vec<u8>,
+}
+
+// The default maximum length of a line, in bytes
+const DEFAULT_MAX_LINE_LEN: usize = 8 * 1024;
+
+impl LogWriter {
+    /// Create a writer that logs to the global logger.
+    ///
+    /// The global logger is looked up each time a line is logged, so the
+    /// writer may be created before the logger is set.
+    pub fn new(level: Level, target: impl Into<String>) -> Self {
+        LogWriter {
+            logger: None,
+            level,
+            target: target.into(),
+            max_line_len: DEFAULT_MAX_LINE_LEN,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Create a writer that logs to the given logger instead of the global one.
+    pub fn with_logger(logger: &'static dyn Log, level: Level, target: impl Into<String>) -> Self {
+        LogWriter {
+            logger: Some(logger),
+            level,
+            target: target.into(),
+            max_line_len: DEFAULT_MAX_LINE_LEN,
+            buf: Vec::new(),
+        }
+    }
+
+    /// The level that lines are logged at.
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// The target that lines are logged to.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// The maximum length of a line, in bytes.
+    pub fn max_line_len(&self) -> usize {
+        self.max_line_len
+    }
+
+    /// Set the maximum length of a line, in bytes.
+    ///
+    /// Once this many bytes are buffered without a newline they're logged as
+    /// a record, and the rest of the line is buffered as a new one. A line
+    /// split this way may have a multi-byte UTF-8 sequence split across its
+    /// records, which are then logged lossily.
+    ///
+    /// The default is 8KiB. A maximum of `0` is treated as `1`.
+    pub fn set_max_line_len(&mut self, max_line_len: usize) {
+        self.max_line_len = max_line_len.max(1);
+    }
+
+    fn logger(&self) -> &'static dyn Log {
+        self.logger.unwrap_or_else(crate::logger)
+    }
+
+    fn write_bytes(&mut self, mut bytes: &[u8]) {
+        while !bytes.is_empty() {
+            // A full line is only logged once there's more of it to buffer,
+            // so a line of exactly the maximum length isn't followed by an
+            // empty one when its newline arrives
+            if self.buf.len() >= self.max_line_len && bytes[0] != b'\n' {
+                self.log_buf();
+            }
+
+            let room = self.max_line_len.saturating_sub(self.buf.len());
+            let window = &bytes[..bytes.len().min(room + 1)];
+
+            match window.iter().position(|&b| b == b'\n') {
+                Some(end) => {
+                    self.buf.extend_from_slice(&bytes[..end]);
+                    self.log_buf();
+
+                    bytes = &bytes[end + 1..];
+                }
+                None => {
+                    let end = window.len().min(room);
+                    self.buf.extend_from_slice(&bytes[..end]);
+
+                    bytes = &bytes[end..];
+                }
+            }
+        }
+    }
+
+    fn log_buf(&mut self) {
+        let mut line = &self.buf[..];
+        if line.last() == Some(&b'\r') {
+            line = &line[..line.len() - 1];
+        }
+
+        if self.level <= crate::STATIC_MAX_LEVEL && self.level <= crate::max_level() {
+            let line = String::from_utf8_lossy(line);
+
+            self.logger().log(
+                &Record::builder()
+                    .args(format_args!("{}", line))
+                    .level(self.level)
+                    .target(&self.target)
+                    .build(),
+            );
+        }
+
+        self.buf.clear();
+    }
+}
+
+impl io::Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_bytes(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.buf.is_empty() {
+            self.log_buf();
+        }
+
+        self.logger().flush();
+        Ok(())
+    }
+}
+
+impl fmt::Write for LogWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.write_bytes(s.as_bytes());
+        Ok(())
+    }
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        if !self.buf.is_empty() {
+            self.log_buf();
+        }
+    }
+}
+
+impl fmt::Debug for LogWriter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LogWriter")
+            .field("level", &self.level)
+            .field("target", &self.target)
+            .field("max_line_len", &self.max_line_len)
+            .field("buffered", &String::from_utf8_lossy(&self.buf))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LevelFilter, Metadata};
+    use std::io::Write as _;
+    use std::sync::Mutex;
+
+    struct Capture(Mutex<Vec<(Level, String, String)>>);
+
+    impl Log for Capture {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            self.0.lock().unwrap().push((
+                record.level(),
+                record.target().into(),
+                record.args().to_string(),
+            ));
+        }
+
+        fn flush(&self) {}
+    }
+
+    fn capture() -> &'static Capture {
+        crate::set_max_level(LevelFilter::Trace);
+        Box::leak(Box::new(Capture(Mutex::new(Vec::new()))))
+    }
+
+    // These tests don't make sense when static max level filtering is applied
+    fn filtered() -> bool {
+        crate::STATIC_MAX_LEVEL < LevelFilter::Trace
+    }
+
+    fn lines(capture: &Capture) -> Vec<String> {
+        capture
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, _, line)| line.clone())
+            .collect()
+    }
+
+    #[test]
+    fn io_write_lines() {
+        if filtered() {
+            return;
+        }
+
+        let capture = capture();
+        let mut writer = LogWriter::with_logger(capture, Level::Warn, "child");
+
+        writer.write_all(b"first line\nsecond ").unwrap();
+        writer.write_all(b"line\r\nthird").unwrap();
+
+        assert_eq!(vec!["first line", "second line"], lines(capture));
+
+        writer.flush().unwrap();
+
+        assert_eq!(
+            vec![
+                (Level::Warn, "child".into(), "first line".into()),
+                (Level::Warn, "child".into(), "second line".into()),
+                (Level::Warn, "child".into(), "third".into()),
+            ],
+            *capture.0.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn fmt_write_lines() {
+        if filtered() {
+            return;
+        }
+
+        let capture = capture();
+        let mut writer = LogWriter::with_logger(capture, Level::Info, "fmt");
+
+        fmt::Write::write_fmt(&mut writer, format_args!("a {}\nb {}", 1, 2)).unwrap();
+        assert_eq!(vec!["a 1"], lines(capture));
+
+        drop(writer);
+        assert_eq!(vec!["a 1", "b 2"], lines(capture));
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        if filtered() {
+            return;
+        }
+
+        let capture = capture();
+        let mut writer = LogWriter::with_logger(capture, Level::Info, "bytes");
+
+        writer.write_all(b"valid \xF0\x90\x80 invalid\n").unwrap();
+
+        assert_eq!(vec!["valid \u{FFFD} invalid"], lines(capture));
+    }
+
+    #[test]
+    fn max_line_len() {
+        if filtered() {
+            return;
+        }
+
+        let capture = capture();
+        let mut writer = LogWriter::with_logger(capture, Level::Info, "long");
+        writer.set_max_line_len(4);
+
+        writer.write_all(b"abcdefghij\n").unwrap();
+        assert_eq!(vec!["abcd", "efgh", "ij"], lines(capture));
+
+        // A line of exactly the maximum length, split across writes
+        writer.write_all(b"kl").unwrap();
+        writer.write_all(b"mn").unwrap();
+        assert_eq!(4, writer.buf.len());
+        writer.write_all(b"\nop").unwrap();
+        assert_eq!(vec!["abcd", "efgh", "ij", "klmn"], lines(capture));
+
+        // Buffered bytes never exceed the maximum
+        writer.write_all(b"qrstuvwxyz").unwrap();
+        assert!(writer.buf.len() <= 4);
+
+        drop(writer);
+        assert_eq!(
+            vec!["abcd", "efgh", "ij", "klmn", "opqr", "stuv", "wxyz"],
+            lines(capture)
+        );
+    }
+
+    #[test]
+    fn max_line_len_min() {
+        let mut writer = LogWriter::new(Level::Info, "min");
+        writer.set_max_line_len(0);
+
+        assert_eq!(1, writer.max_line_len());
+    }
+
+    #[test]
+    fn empty_flush() {
+        let capture = capture();
+        let mut writer = LogWriter::with_logger(capture, Level::Info, "empty");
+
+        writer.flush().unwrap();
+        drop(writer);
+
+        assert!(lines(capture).is_empty());
+    }
+}
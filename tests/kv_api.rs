@@ -0,0 +1,445 @@
+//! Pins the stable API of the `kv` module.
+//!
+//! Everything in here should keep compiling and passing across
+//! semver-compatible releases. A change that breaks this file
+//! is a breaking change to `log::kv`.
+
+#![cfg(feature = "kv")]
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::hash::Hash;
+
+use log::kv::{Error, ErrorKind, Key, Source, ToKey, ToValue, Value, ValueKind, Visit, Visitor};
+
+fn assert_key_traits<'k, T>()
+where
+    T: Clone
+        + fmt::Debug
+        + fmt::Display
+        + Eq
+        + Ord
+        + Hash
+        + AsRef<str>
+        + Borrow<str>
+        + From<&'k str>
+        + ToKey,
+{
+}
+
+fn assert_value_traits<'v, T>()
+where
+    T: fmt::Debug
+        + fmt::Display
+        + ToValue
+        + From<&'v str>
+        + From<u8>
+        + From<u16>
+        + From<u32>
+        + From<u64>
+        + From<usize>
+        + From<i8>
+        + From<i16>
+        + From<i32>
+        + From<i64>
+        + From<isize>
+        + From<f32>
+        + From<f64>
+        + From<bool>
+        + From<char>
+        + From<std::time::Duration>
+        + From<&'v [u8]>
+        + From<&'v u128>
+        + From<&'v i128>,
+{
+}
+
+fn assert_to_value<T: ToValue + ?Sized>() {}
+
+fn assert_source<T: Source + ?Sized>() {}
+
+#[test]
+fn key_api() {
+    assert_key_traits::<Key>();
+
+    let _: fn(&'static str) -> Key<'static> = Key::from_str;
+    let _: for<'a> fn(&'a Key<'static>) -> &'a str = Key::as_str;
+
+    fn to_key(key: &dyn ToKey) -> Key<'_> {
+        key.to_key()
+    }
+
+    assert_eq!("a", to_key(&"a").as_str());
+
+    let key = Key::from_str("a");
+    assert_eq!("a", key.as_str());
+    assert_eq!(key, "a".to_key());
+    assert_eq!(key, Key::from("a"));
+    assert_eq!("a", key.to_string());
+}
+
+#[test]
+fn value_api() {
+    assert_value_traits::<Value>();
+
+    assert_to_value::<str>();
+    assert_to_value::<()>();
+    assert_to_value::<Option<u8>>();
+    assert_to_value::<u128>();
+    assert_to_value::<i128>();
+    assert_to_value::<std::num::NonZeroU32>();
+    assert_to_value::<std::time::Duration>();
+    assert_to_value::<&[u8]>();
+    assert_to_value::<[u8; 4]>();
+    assert_to_value::<Value>();
+    assert_to_value::<&dyn ToValue>();
+
+    let _: fn(&'static u8) -> Value<'static> = Value::from_any::<u8>;
+    let _: fn(&'static u8) -> Value<'static> = Value::capture_debug::<u8>;
+    let _: fn(&'static u8) -> Value<'static> = Value::capture_display::<u8>;
+    let _: fn(&'static u8) -> Value<'static> = Value::from_debug::<u8>;
+    let _: fn(&'static u8) -> Value<'static> = Value::from_display::<u8>;
+    let _: fn(&'static dyn fmt::Debug) -> Value<'static> = Value::from_dyn_debug;
+    let _: fn(&'static dyn fmt::Display) -> Value<'static> = Value::from_dyn_display;
+
+    let _: fn(&Value<'static>) -> Option<u64> = Value::to_u64;
+    let _: fn(&Value<'static>) -> Option<i64> = Value::to_i64;
+    let _: fn(&Value<'static>) -> Option<u128> = Value::to_u128;
+    let _: fn(&Value<'static>) -> Option<i128> = Value::to_i128;
+    let _: fn(&Value<'static>) -> Option<f64> = Value::to_f64;
+    let _: fn(&Value<'static>) -> Option<char> = Value::to_char;
+    let _: fn(&Value<'static>) -> Option<bool> = Value::to_bool;
+    let _: for<'a> fn(&'a Value<'static>) -> Option<&'a str> = Value::to_borrowed_str;
+    let _: fn(&Value<'static>) -> bool = Value::is::<u8>;
+    let _: fn(&Value<'static>) -> bool = Value::is_unit;
+    let _: fn(&Value<'static>) -> ValueKind = Value::kind;
+    let _: fn(&'static [u8]) -> Value<'static> = Value::from_bytes;
+    let _: for<'a> fn(&'a Value<'static>) -> Option<&'a [u8]> = Value::to_borrowed_bytes;
+    let _: fn(&Value<'static>) -> Option<std::time::Duration> = Value::to_duration;
+    let _: for<'a> fn(&'a Value<'static>) -> Option<&'a u8> = Value::downcast_ref::<u8>;
+
+    assert_eq!(Some(1), Value::from(1u8).to_u64());
+    assert_eq!(Some(-1), Value::from(-1i8).to_i64());
+    assert_eq!(Some(true), true.to_value().to_bool());
+    assert_eq!(Some("a"), "a".to_value().to_borrowed_str());
+
+    #[derive(Debug)]
+    struct Data(u8);
+
+    let data = Data(1);
+    let value = Value::capture_debug(&data);
+    assert!(value.is::<Data>());
+    assert_eq!(1, value.downcast_ref::<Data>().unwrap().0);
+    assert_eq!("1", Value::from_debug(&1u8).to_string());
+
+    fn assert_value_kind<T: fmt::Debug + Clone + Copy + Eq + Hash>() {}
+
+    assert_value_kind::<ValueKind>();
+
+    assert_eq!(ValueKind::U64, Value::from(1u8).kind());
+    assert_eq!(ValueKind::Unit, ().to_value().kind());
+}
+
+#[test]
+fn source_api() {
+    assert_source::<dyn Source>();
+    assert_source::<(&str, u8)>();
+    assert_source::<(Key, Value)>();
+    assert_source::<[(&str, u8)]>();
+    assert_source::<Option<(&str, u8)>>();
+    assert_source::<&dyn Source>();
+
+    fn visit<'kvs>(source: &'kvs dyn Source, visitor: &mut dyn Visitor<'kvs>) -> Result<(), Error> {
+        source.visit(visitor)
+    }
+
+    fn get<'a>(source: &'a dyn Source, key: Key) -> Option<Value<'a>> {
+        source.get(key)
+    }
+
+    fn count(source: &dyn Source) -> usize {
+        source.count()
+    }
+
+    struct Ignore;
+
+    impl<'kvs> Visitor<'kvs> for Ignore {
+        fn visit_pair(&mut self, _: Key<'kvs>, _: Value<'kvs>) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    assert!(visit(&("a", 1), &mut Ignore).is_ok());
+    assert!(get(&("a", 1), Key::from_str("a")).is_some());
+    assert_eq!(1, count(&("a", 1)));
+
+    let source = [("a", 1), ("b", 2)];
+    let source = &source as &[_];
+
+    assert_eq!(2, Source::count(source));
+    assert_eq!(
+        Some(2),
+        Source::get(source, Key::from_str("b")).unwrap().to_i64()
+    );
+    assert!(Source::get(source, Key::from_str("c")).is_none());
+
+    let _: log::kv::source::AsMap<_> = log::kv::source::as_map(source);
+    let _: log::kv::source::AsList<_> = log::kv::source::as_list(source);
+}
+
+#[test]
+fn visitor_api() {
+    struct Count(usize);
+
+    impl<'kvs> Visitor<'kvs> for Count {
+        fn visit_pair(&mut self, _: Key<'kvs>, _: Value<'kvs>) -> Result<(), Error> {
+            self.0 += 1;
+            Ok(())
+        }
+    }
+
+    let mut count = Count(0);
+    ("a", 1).visit(&mut count).unwrap();
+    ("a", 1).visit(&mut &mut count).unwrap();
+
+    assert_eq!(2, count.0);
+}
+
+#[test]
+fn visit_api() {
+    // Only `visit_any` is required
+    struct Any(Option<String>);
+
+    impl<'v> Visit<'v> for Any {
+        fn visit_any(&mut self, value: Value) -> Result<(), Error> {
+            self.0 = Some(value.to_string());
+            Ok(())
+        }
+    }
+
+    let mut any = Any(None);
+    Value::from(1u8).visit(&mut any).unwrap();
+    assert_eq!(Some("1"), any.0.as_deref());
+
+    // Primitives can be visited without formatting
+    struct Primitives(Option<String>);
+
+    impl<'v> Visit<'v> for Primitives {
+        fn visit_any(&mut self, _: Value) -> Result<(), Error> {
+            Err(Error::msg("unexpected value"))
+        }
+
+        fn visit_u64(&mut self, value: u64) -> Result<(), Error> {
+            self.0 = Some(format!("u64 {}", value));
+            Ok(())
+        }
+
+        fn visit_i64(&mut self, value: i64) -> Result<(), Error> {
+            self.0 = Some(format!("i64 {}", value));
+            Ok(())
+        }
+
+        fn visit_u128(&mut self, value: u128) -> Result<(), Error> {
+            self.0 = Some(format!("u128 {}", value));
+            Ok(())
+        }
+
+        fn visit_i128(&mut self, value: i128) -> Result<(), Error> {
+            self.0 = Some(format!("i128 {}", value));
+            Ok(())
+        }
+
+        fn visit_f64(&mut self, value: f64) -> Result<(), Error> {
+            self.0 = Some(format!("f64 {}", value));
+            Ok(())
+        }
+
+        fn visit_bool(&mut self, value: bool) -> Result<(), Error> {
+            self.0 = Some(format!("bool {}", value));
+            Ok(())
+        }
+
+        fn visit_str(&mut self, value: &str) -> Result<(), Error> {
+            self.0 = Some(format!("str {}", value));
+            Ok(())
+        }
+
+        fn visit_borrowed_str(&mut self, value: &'v str) -> Result<(), Error> {
+            self.0 = Some(format!("borrowed_str {}", value));
+            Ok(())
+        }
+
+        fn visit_char(&mut self, value: char) -> Result<(), Error> {
+            self.0 = Some(format!("char {}", value));
+            Ok(())
+        }
+
+        fn visit_duration(&mut self, value: std::time::Duration) -> Result<(), Error> {
+            self.0 = Some(format!("duration {:?}", value));
+            Ok(())
+        }
+
+        fn visit_bytes(&mut self, value: &[u8]) -> Result<(), Error> {
+            self.0 = Some(format!("bytes {:?}", value));
+            Ok(())
+        }
+    }
+
+    let visit = |value: Value| {
+        let mut primitives = Primitives(None);
+        value.visit(&mut primitives).unwrap();
+        primitives.0.unwrap()
+    };
+
+    assert_eq!("u64 1", visit(Value::from(1u8)));
+    assert_eq!("i64 -1", visit(Value::from(-1i8)));
+    assert_eq!("u128 1", visit(Value::from(&1u128)));
+    assert_eq!("i128 -1", visit(Value::from(&-1i128)));
+    assert_eq!("f64 1.5", visit(Value::from(1.5f64)));
+    assert_eq!("bool true", visit(Value::from(true)));
+    assert_eq!("borrowed_str a", visit(Value::from("a")));
+    assert_eq!("char a", visit(Value::from('a')));
+    assert_eq!("bytes [1, 2]", visit(Value::from(&[1u8, 2][..])));
+    assert_eq!(
+        "duration 1s",
+        visit(Value::from(std::time::Duration::from_secs(1)))
+    );
+}
+
+#[test]
+fn bytes_api() {
+    use log::kv::value::{BytesFormat, FormatBytes};
+
+    fn assert_bytes_format<T: fmt::Debug + Clone + Copy + Default + Eq + Hash>() {}
+
+    assert_bytes_format::<BytesFormat>();
+
+    let _: fn(&'static [u8]) -> FormatBytes<'static> = FormatBytes::new;
+    let _: fn(FormatBytes<'static>, BytesFormat) -> FormatBytes<'static> = FormatBytes::format;
+    let _: fn(FormatBytes<'static>, usize) -> FormatBytes<'static> = FormatBytes::truncate;
+
+    let bytes = [0xde, 0xad, 0xbe, 0xef];
+
+    assert_eq!(Some(&bytes[..]), bytes.to_value().to_borrowed_bytes());
+    assert_eq!("deadbeef", bytes.to_value().to_string());
+    assert_eq!(
+        "3q2+7w==",
+        FormatBytes::new(&bytes)
+            .format(BytesFormat::Base64)
+            .to_string()
+    );
+    assert_eq!(
+        "dead... (4 bytes)",
+        FormatBytes::new(&bytes).truncate(2).to_string()
+    );
+}
+
+#[test]
+fn error_api() {
+    fn assert_error<T: fmt::Debug + fmt::Display + From<fmt::Error>>() {}
+
+    assert_error::<Error>();
+
+    let _: fn(&'static str) -> Error = Error::msg;
+    let _: fn() -> Error = Error::stop;
+    let _: fn(&Error) -> bool = Error::is_break;
+    let _: fn(&Error) -> ErrorKind = Error::kind;
+
+    fn assert_error_kind<T: fmt::Debug + Clone + Copy + Eq + Hash>() {}
+
+    assert_error_kind::<ErrorKind>();
+
+    assert_eq!(ErrorKind::Break, Error::stop().kind());
+    assert_eq!(ErrorKind::Custom, Error::msg("failed").kind());
+    assert_eq!(ErrorKind::Fmt, Error::from(fmt::Error).kind());
+
+    assert_eq!("failed", Error::msg("failed").to_string());
+}
+
+#[cfg(feature = "kv_std")]
+#[test]
+fn std_api() {
+    use std::borrow::Cow;
+    use std::collections::{BTreeMap, HashMap};
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    assert_to_value::<String>();
+    assert_to_value::<Cow<str>>();
+    assert_to_value::<Box<u8>>();
+    assert_to_value::<Arc<u8>>();
+    assert_to_value::<Rc<u8>>();
+    assert_to_value::<std::time::SystemTime>();
+    assert_to_value::<Vec<u8>>();
+
+    assert_source::<Box<dyn Source>>();
+    assert_source::<Arc<dyn Source>>();
+    assert_source::<Rc<dyn Source>>();
+    assert_source::<Vec<(String, u8)>>();
+    assert_source::<BTreeMap<String, u8>>();
+    assert_source::<HashMap<String, u8>>();
+
+    let _: fn(&'static (dyn std::error::Error + 'static)) -> Value<'static> = Value::from_dyn_error;
+    let _: fn(&'static std::io::Error) -> Value<'static> = Value::capture_error::<std::io::Error>;
+    let _: fn(&Value<'static>) -> Option<Cow<'static, str>> = Value::to_str;
+    let _: fn(&Value<'static>) -> Option<std::time::SystemTime> = Value::to_timestamp;
+
+    let _: fn(std::io::Error) -> Error = Error::boxed::<std::io::Error>;
+    let _: fn(std::io::Error) -> Error = Error::from;
+
+    assert_eq!(
+        ErrorKind::Io,
+        Error::from(std::io::Error::from(std::io::ErrorKind::Other)).kind()
+    );
+
+    let err = std::io::Error::from(std::io::ErrorKind::Other);
+    assert!(Value::from_dyn_error(&err).to_borrowed_error().is_some());
+
+    struct Timestamp(Option<std::time::SystemTime>);
+
+    impl<'v> Visit<'v> for Timestamp {
+        fn visit_any(&mut self, _: Value) -> Result<(), Error> {
+            Err(Error::msg("unexpected value"))
+        }
+
+        fn visit_timestamp(&mut self, value: std::time::SystemTime) -> Result<(), Error> {
+            self.0 = Some(value);
+            Ok(())
+        }
+    }
+
+    let now = std::time::SystemTime::now();
+    let mut timestamp = Timestamp(None);
+    Value::from(now).visit(&mut timestamp).unwrap();
+    assert_eq!(Some(now), timestamp.0);
+}
+
+#[cfg(feature = "kv_serde")]
+#[test]
+fn serde_api() {
+    fn assert_serialize<T: serde::Serialize>() {}
+
+    assert_serialize::<Value>();
+    assert_serialize::<Key>();
+    assert_serialize::<log::kv::source::AsMap<&dyn Source>>();
+    assert_serialize::<log::kv::source::AsList<&dyn Source>>();
+    assert_serialize::<log::Metadata>();
+    assert_serialize::<log::Record>();
+
+    let _: fn(&'static u8) -> Value<'static> = Value::capture_serde::<u8>;
+    let _: fn(&'static u8) -> Value<'static> = Value::from_serde::<u8>;
+}
+
+#[cfg(feature = "kv_sval")]
+#[test]
+fn sval_api() {
+    fn assert_sval<T: sval::Value>() {}
+
+    assert_sval::<Value>();
+    assert_sval::<Key>();
+    assert_sval::<log::kv::source::AsMap<&dyn Source>>();
+    assert_sval::<log::kv::source::AsList<&dyn Source>>();
+
+    let _: fn(&'static u8) -> Value<'static> = Value::capture_sval::<u8>;
+    let _: fn(&'static u8) -> Value<'static> = Value::from_sval::<u8>;
+}
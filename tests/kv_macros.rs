@@ -0,0 +1,189 @@
+//! Checks the records built by the log macros with key-values.
+//!
+//! Records are captured by a global logger, so these tests run in their own
+//! process.
+
+#![cfg(feature = "kv")]
+
+use std::cell::RefCell;
+use std::sync::Once;
+
+use log::kv::{Error, Key, Value, Visitor};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+#[derive(Debug, PartialEq)]
+struct Captured {
+    target: String,
+    args: String,
+    kvs: Vec<(String, String)>,
+}
+
+thread_local! {
+    static LAST: RefCell<Option<Captured>> = const { RefCell::new(None) };
+}
+
+struct Logger;
+
+impl Log for Logger {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        struct Collect(Vec<(String, String)>);
+
+        impl<'kvs> Visitor<'kvs> for Collect {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                self.0.push((key.to_string(), value.to_string()));
+                Ok(())
+            }
+        }
+
+        let mut kvs = Collect(Vec::new());
+        record.key_values().visit(&mut kvs).unwrap();
+
+        LAST.with(|last| {
+            *last.borrow_mut() = Some(Captured {
+                target: record.target().to_owned(),
+                args: record.args().to_string(),
+                kvs: kvs.0,
+            })
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+// Capture the `info` record logged on this thread by `f`
+//
+// Returns `None` if `info` records are filtered out by a `max_level_*` feature
+fn capture(f: impl FnOnce()) -> Option<Captured> {
+    static LOGGER: Logger = Logger;
+    static INIT: Once = Once::new();
+
+    INIT.call_once(|| {
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(LevelFilter::Trace);
+    });
+
+    f();
+    let captured = LAST.with(|last| last.borrow_mut().take());
+
+    if Level::Info <= log::STATIC_MAX_LEVEL {
+        assert!(captured.is_some(), "no record was logged");
+    }
+
+    captured
+}
+
+#[test]
+fn kv_interpolated() {
+    #[derive(Debug)]
+    struct Debuggable;
+
+    let captured = match capture(
+        || log::info!(target: "my_target", user = "chashu", "cat count" = 2, d:? = Debuggable; "hello {user} {d:?}"),
+    ) {
+        Some(captured) => captured,
+        None => return,
+    };
+
+    assert_eq!(
+        Captured {
+            target: "my_target".to_owned(),
+            args: "hello chashu Debuggable".to_owned(),
+            kvs: vec![
+                ("user".to_owned(), "chashu".to_owned()),
+                ("cat count".to_owned(), "2".to_owned()),
+                ("d".to_owned(), "Debuggable".to_owned()),
+            ],
+        },
+        captured
+    );
+}
+
+#[test]
+fn kv_interpolated_evaluated_once() {
+    let mut count = 0;
+    let mut next = || {
+        count += 1;
+        count
+    };
+
+    let captured = match capture(|| log::info!(n = next(); "{n} {n}")) {
+        Some(captured) => captured,
+        None => return,
+    };
+
+    assert_eq!("1 1", captured.args);
+    assert_eq!(vec![("n".to_owned(), "1".to_owned())], captured.kvs);
+    assert_eq!(1, count);
+}
+
+#[test]
+fn kv_interpolated_no_shadowing() {
+    let user = ("chashu", 2);
+    let name = "my_target";
+
+    let captured = match capture(
+        || log::info!(target: name, user = user.0, name = user.1, "n" = user.1; "{user} {name}"),
+    ) {
+        Some(captured) => captured,
+        None => return,
+    };
+
+    assert_eq!("my_target", captured.target);
+    assert_eq!("chashu 2", captured.args);
+}
+
+#[test]
+fn kv_duplicate_keys() {
+    let a = 3;
+
+    let captured = match capture(|| log::info!(a = 1, a = 2; "{a}")) {
+        Some(captured) => captured,
+        None => return,
+    };
+
+    assert_eq!("2", captured.args);
+    assert_eq!(
+        vec![
+            ("a".to_owned(), "1".to_owned()),
+            ("a".to_owned(), "2".to_owned()),
+        ],
+        captured.kvs
+    );
+
+    // Shorthand keys refer to the caller's variable, not an earlier key
+    let captured = capture(|| log::info!(a = 1, a; "{a}")).unwrap();
+
+    assert_eq!("1", captured.args);
+    assert_eq!(
+        vec![
+            ("a".to_owned(), "1".to_owned()),
+            ("a".to_owned(), "3".to_owned()),
+        ],
+        captured.kvs
+    );
+}
+
+#[test]
+fn kv_key_named_like_local() {
+    let user = "caller";
+    let other = "key";
+
+    let captured = match capture(|| log::info!(user = other; "hello {}", user)) {
+        Some(captured) => captured,
+        None => return,
+    };
+
+    assert_eq!("hello caller", captured.args);
+    assert_eq!(vec![("user".to_owned(), "key".to_owned())], captured.kvs);
+
+    let captured = capture(|| log::info!(user = other; "hello {name}", name = user)).unwrap();
+    assert_eq!("hello caller", captured.args);
+
+    // Only the format string sees the key
+    let captured = capture(|| log::info!(user = other; "{user} {}", user)).unwrap();
+    assert_eq!("key caller", captured.args);
+}
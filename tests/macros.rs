@@ -85,7 +85,7 @@ fn expr() {
 }
 
 #[test]
-#[cfg(feature = "kv_unstable")]
+#[cfg(feature = "kv")]
 fn kv_no_args() {
     for lvl in log::Level::iter() {
         log!(target: "my_target", lvl, cat_1 = "chashu", cat_2 = "nori", cat_count = 2; "hello");
@@ -99,7 +99,7 @@ fn kv_no_args() {
 }
 
 #[test]
-#[cfg(feature = "kv_unstable")]
+#[cfg(feature = "kv")]
 fn kv_expr_args() {
     for lvl in log::Level::iter() {
         log!(target: "my_target", lvl, cat_math = { let mut x = 0; x += 1; x + 1 }; "hello");
@@ -114,7 +114,7 @@ fn kv_expr_args() {
 }
 
 #[test]
-#[cfg(feature = "kv_unstable")]
+#[cfg(feature = "kv")]
 fn kv_anonymous_args() {
     for lvl in log::Level::iter() {
         log!(target: "my_target", lvl, cat_1 = "chashu", cat_2 = "nori", cat_count = 2; "hello {}", "world");
@@ -129,7 +129,7 @@ fn kv_anonymous_args() {
 }
 
 #[test]
-#[cfg(feature = "kv_unstable")]
+#[cfg(feature = "kv")]
 fn kv_named_args() {
     for lvl in log::Level::iter() {
         log!(target: "my_target", lvl, cat_1 = "chashu", cat_2 = "nori", cat_count = 2; "hello {world}", world = "world");
@@ -144,7 +144,7 @@ fn kv_named_args() {
 }
 
 #[test]
-#[cfg(feature = "kv_unstable")]
+#[cfg(feature = "kv")]
 fn kv_expr_context() {
     match "chashu" {
         cat_1 => {
@@ -174,14 +174,14 @@ fn implicit_named_args() {
     all_log_macros!(target: "my_target", "hello {world}");
     all_log_macros!(target: "my_target", "hello {world}",);
 
-    #[cfg(feature = "kv_unstable")]
+    #[cfg(feature = "kv")]
     all_log_macros!(target = "my_target"; "hello {world}");
-    #[cfg(feature = "kv_unstable")]
+    #[cfg(feature = "kv")]
     all_log_macros!(target = "my_target"; "hello {world}",);
 }
 
 #[test]
-#[cfg(feature = "kv_unstable")]
+#[cfg(feature = "kv")]
 fn kv_implicit_named_args() {
     let world = "world";
 
@@ -197,7 +197,7 @@ fn kv_implicit_named_args() {
 }
 
 #[test]
-#[cfg(feature = "kv_unstable")]
+#[cfg(feature = "kv")]
 fn kv_string_keys() {
     for lvl in log::Level::iter() {
         log!(target: "my_target", lvl, "also dogs" = "Fílos", "key/that-can't/be/an/ident" = "hi"; "hello {world}", world = "world");
@@ -207,7 +207,7 @@ fn kv_string_keys() {
 }
 
 #[test]
-#[cfg(feature = "kv_unstable")]
+#[cfg(feature = "kv")]
 fn kv_common_value_types() {
     all_log_macros!(
         u8 = 42u8,
@@ -228,6 +228,67 @@ fn kv_common_value_types() {
     );
 }
 
+#[test]
+#[cfg(feature = "kv")]
+fn kv_capture_modifiers() {
+    #[derive(Debug)]
+    struct Debuggable;
+
+    let path = std::path::PathBuf::from("/a");
+
+    for lvl in log::Level::iter() {
+        log!(target: "my_target", lvl, a:? = Debuggable, b:% = 42, c = 1; "hello");
+        log!(lvl, path:? = path, "also dogs":% = "Fílos"; "hello {}", "world");
+    }
+
+    all_log_macros!(a:? = Debuggable, b:% = 42, c = 1; "hello");
+    all_log_macros!(target: "my_target", a:? = Debuggable; "hello {world}", world = "world");
+}
+
+#[test]
+#[cfg(feature = "kv_std")]
+fn kv_capture_error() {
+    let err = std::io::Error::from(std::io::ErrorKind::Other);
+
+    all_log_macros!(err:err = err; "hello");
+}
+
+#[test]
+#[cfg(feature = "kv_serde")]
+fn kv_capture_serde() {
+    all_log_macros!(a:serde = 42, b:serde = "a string"; "hello");
+}
+
+#[test]
+#[cfg(feature = "kv_sval")]
+fn kv_capture_sval() {
+    all_log_macros!(a:sval = 42, b:sval = "a string"; "hello");
+}
+
+#[test]
+#[cfg(feature = "kv")]
+fn kv_shorthand() {
+    let cat_1 = "chashu";
+    let cat_count = 2;
+
+    for lvl in log::Level::iter() {
+        log!(target: "my_target", lvl, cat_1, cat_count; "hello");
+        log!(lvl, cat_1:%, cat_count, cat_2 = "nori"; "hello {cat_1}");
+    }
+
+    all_log_macros!(cat_1; "hello");
+    all_log_macros!(target: "my_target", cat_1:?, cat_count; "hello {}", "world");
+}
+
+#[test]
+#[cfg(feature = "kv")]
+fn kv_duplicate_keys() {
+    let a = 3;
+
+    all_log_macros!(a = 1, a = 2; "dup");
+    all_log_macros!(a = 1, a:? = 2, a; "dup");
+}
+
 /// Some and None (from Option) are used in the macros.
 #[derive(Debug)]
 enum Type {
@@ -0,0 +1,64 @@
+//! The panic hook installs a global logger and panic hook, so it's tested in
+//! its own process.
+
+use std::panic;
+use std::sync::{Arc, Mutex};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+struct Captured {
+    level: Level,
+    target: String,
+    message: String,
+    file: Option<String>,
+}
+
+struct Capture(Arc<Mutex<Vec<Captured>>>);
+
+impl Log for Capture {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.0.lock().unwrap().push(Captured {
+            level: record.level(),
+            target: record.target().into(),
+            message: record.args().to_string(),
+            file: record.file().map(Into::into),
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+fn main() {
+    // The hook respects static level filtering, so there's nothing to check
+    // when it turns error records off
+    if Level::Error > log::STATIC_MAX_LEVEL {
+        return;
+    }
+
+    let captured = Arc::new(Mutex::new(Vec::new()));
+    log::set_boxed_logger(Box::new(Capture(captured.clone()))).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+    // The hook chains to the previous one, so replace the default to keep
+    // the expected panic out of the test output
+    panic::set_hook(Box::new(|_| {}));
+    log::panic::install_hook(Level::Error);
+
+    let result = panic::catch_unwind(|| panic!("boom {}", 42));
+    assert!(result.is_err());
+
+    let captured = captured.lock().unwrap();
+    assert_eq!(1, captured.len());
+
+    let record = &captured[0];
+    assert_eq!(Level::Error, record.level);
+    assert_eq!("panic", record.target);
+    assert_eq!("boom 42", record.message);
+    assert!(record
+        .file
+        .as_deref()
+        .map_or(false, |file| file.ends_with("panic_hook.rs")));
+}
@@ -12,3 +12,7 @@ mod filters;
 #[cfg(test)]
 #[path = "../macros.rs"]
 mod macros;
+
+#[cfg(test)]
+#[path = "../kv_api.rs"]
+mod kv_api;